@@ -0,0 +1,6 @@
+pub mod book;
+pub mod eval;
+pub mod player;
+pub mod search;
+pub mod syzygy;
+pub mod tt;
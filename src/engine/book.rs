@@ -0,0 +1,360 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::engine::player::{MoveProvider, PlayerMove};
+use crate::engine::search::SearchLimits;
+use crate::game::{Color, Game, Move, PieceType, Square};
+use crate::game::errors::{GameErr, GameResult};
+use crate::game::random::SplitMix64;
+use crate::notation::pgn::{self, PgnGame};
+use crate::ruleengine;
+
+mod keys;
+
+use keys::RANDOM64;
+
+const CASTLING: usize = 768;
+const EN_PASSANT: usize = CASTLING + 4;
+const TURN: usize = EN_PASSANT + 8;
+/// Size of one entry in a `.bin` file.
+pub const ENTRY_SIZE: usize = 16;
+
+/// Computes the key Polyglot books use for the position. It differs from
+/// `Game::zobrist_key` in the key table and in counting the en passant file
+/// whenever a pawn of the player to move stands next to the pawn that just
+/// made its double step, even if the capture would be illegal.
+pub fn polyglot_key(game: &Game) -> u64 {
+    let mut key = 0;
+    for (square, piece) in Square::all().zip(game.board().iter()) {
+        if let Some(piece) = piece {
+            let kind = 2 * kind_index(piece.piece_type) + usize::from(piece.color == Color::White);
+            key ^= RANDOM64[64 * kind + square.index()];
+        }
+    }
+    for (i, right) in ['K', 'Q'].into_iter().enumerate() {
+        if game.white_can_castle.contains(right) {
+            key ^= RANDOM64[CASTLING + i];
+        }
+    }
+    for (i, right) in ['k', 'q'].into_iter().enumerate() {
+        if game.black_can_castle.contains(right) {
+            key ^= RANDOM64[CASTLING + 2 + i];
+        }
+    }
+    if let Some(target) = game.en_passant_target() {
+        let forward = if game.current_player == Color::White { 1 } else { -1 };
+        let capturer = [-1, 1].iter().filter_map(|&offset| target.offset(offset, -forward)).any(|from| {
+            ruleengine::get_piece_at_pos(game.board(), from)
+                .is_some_and(|p| p.piece_type == PieceType::Pawn && p.color == game.current_player)
+        });
+        if capturer {
+            key ^= RANDOM64[EN_PASSANT + (target.file() as u8 - b'a') as usize];
+        }
+    }
+    if game.current_player == Color::White {
+        key ^= RANDOM64[TURN];
+    }
+    key
+}
+
+fn kind_index(piece_type: PieceType) -> usize {
+    match piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    }
+}
+
+/// Encodes a move the Polyglot way: to file, to rank, from file and from rank
+/// in three bits each, then the promotion piece. Castling is written as the
+/// king taking its own rook, "e1h1" instead of "e1g1".
+pub fn encode_move(game: &Game, mv: Move) -> u16 {
+    let to = match castling_rook(game, mv) {
+        Some(rook) => rook,
+        None => mv.to,
+    };
+    let promotion = match mv.promotion {
+        Some(PieceType::Knight) => 1,
+        Some(PieceType::Bishop) => 2,
+        Some(PieceType::Rook) => 3,
+        Some(PieceType::Queen) => 4,
+        _ => 0,
+    };
+    (promotion << 12 | mv.from.index() << 6 | to.index()) as u16
+}
+
+/// Reverses `encode_move`. Returns `None` if the move is not legal in `game`.
+pub fn decode_move(game: &Game, raw: u16) -> Option<Move> {
+    let from = Square::from_index(((raw >> 6) & 0x3f) as usize)?;
+    let mut to = Square::from_index((raw & 0x3f) as usize)?;
+    let promotion = match (raw >> 12) & 0x7 {
+        1 => Some(PieceType::Knight),
+        2 => Some(PieceType::Bishop),
+        3 => Some(PieceType::Rook),
+        4 => Some(PieceType::Queen),
+        _ => None,
+    };
+    let king = ruleengine::get_piece_at_pos(game.board(), from).is_some_and(|p| p.piece_type == PieceType::King);
+    let rook = ruleengine::get_piece_at_pos(game.board(), to).is_some_and(|p| p.piece_type == PieceType::Rook);
+    if king && rook && from.file() == 'e' && to.rank() == from.rank() {
+        to = match to.file() {
+            'h' => from.offset(2, 0)?,
+            'a' => from.offset(-2, 0)?,
+            _ => to,
+        };
+    }
+    let mv = Move { from, to, promotion };
+    game.legal_moves().contains(&mv).then_some(mv)
+}
+
+/// The rook square of a castling move, `None` for other moves.
+fn castling_rook(game: &Game, mv: Move) -> Option<Square> {
+    let king = ruleengine::get_piece_at_pos(game.board(), mv.from).is_some_and(|p| p.piece_type == PieceType::King);
+    if !king || mv.from.rank() != mv.to.rank() {
+        return None;
+    }
+    match mv.to.index() as i32 - mv.from.index() as i32 {
+        2 => mv.from.offset(3, 0),
+        -2 => mv.from.offset(-4, 0),
+        _ => None,
+    }
+}
+
+/// One entry of a Polyglot book, the move still in its encoded form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookEntry {
+    pub key: u64,
+    pub raw_move: u16,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+impl BookEntry {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            key: u64::from_be_bytes(bytes[0..8].try_into().unwrap_or_default()),
+            raw_move: u16::from_be_bytes([bytes[8], bytes[9]]),
+            weight: u16::from_be_bytes([bytes[10], bytes[11]]),
+            learn: u32::from_be_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]),
+        }
+    }
+
+    fn to_bytes(self) -> [u8; ENTRY_SIZE] {
+        let mut bytes = [0; ENTRY_SIZE];
+        bytes[0..8].copy_from_slice(&self.key.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.raw_move.to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.weight.to_be_bytes());
+        bytes[12..16].copy_from_slice(&self.learn.to_be_bytes());
+        bytes
+    }
+}
+
+/// A legal book move in a position with its weight.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookMove {
+    pub mv: Move,
+    pub weight: u16,
+}
+
+/// How to pick among the book moves of a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BookChoice {
+    /// Always the move with the highest weight.
+    Best,
+    /// A random move, with a chance proportional to its weight.
+    #[default]
+    WeightedRandom,
+}
+
+/// A Polyglot opening book, the entries sorted by key as in the file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpeningBook {
+    entries: Vec<BookEntry>,
+}
+
+impl OpeningBook {
+    /// Reads a `.bin` book.
+    pub fn open(path: impl AsRef<Path>) -> GameResult<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|e| GameErr::InvalidBook(format!("{}: {}", path.display(), e)))?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> GameResult<Self> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(GameErr::InvalidBook(format!("size {} is not a multiple of {}", bytes.len(), ENTRY_SIZE)));
+        }
+        let mut entries: Vec<BookEntry> = bytes.chunks_exact(ENTRY_SIZE).map(BookEntry::from_bytes).collect();
+        // Books are sorted by key already, a stable sort keeps the order of
+        // their moves if one is not.
+        entries.sort_by_key(|e| e.key);
+        Ok(Self { entries })
+    }
+
+    /// A book of the given entries, in any order.
+    pub fn from_entries(mut entries: Vec<BookEntry>) -> Self {
+        entries.sort_by(|a, b| a.key.cmp(&b.key).then(b.weight.cmp(&a.weight)).then(a.raw_move.cmp(&b.raw_move)));
+        Self { entries }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.entries.iter().flat_map(|e| e.to_bytes()).collect()
+    }
+
+    /// Writes the book as a `.bin` file.
+    pub fn save(&self, path: impl AsRef<Path>) -> GameResult<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_bytes()).map_err(|e| GameErr::InvalidBook(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn entries(&self) -> &[BookEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entries stored for the key.
+    pub fn lookup(&self, key: u64) -> &[BookEntry] {
+        let start = self.entries.partition_point(|e| e.key < key);
+        let end = start + self.entries[start..].partition_point(|e| e.key == key);
+        &self.entries[start..end]
+    }
+
+    /// The legal book moves of the position, highest weight first. Moves with
+    /// weight 0 are kept, they are only played when nothing else is left.
+    pub fn moves(&self, game: &Game) -> Vec<BookMove> {
+        let mut moves: Vec<BookMove> = self.lookup(polyglot_key(game)).iter()
+            .filter_map(|e| decode_move(game, e.raw_move).map(|mv| BookMove { mv, weight: e.weight }))
+            .collect();
+        moves.sort_by_key(|m| Reverse(m.weight));
+        moves
+    }
+
+    /// Picks a book move, `random` is any random number and only used by
+    /// `BookChoice::WeightedRandom`.
+    pub fn choose(&self, game: &Game, choice: BookChoice, random: u64) -> Option<BookMove> {
+        let moves = self.moves(game);
+        let total: u64 = moves.iter().map(|m| m.weight as u64).sum();
+        if choice == BookChoice::Best || total == 0 {
+            return moves.first().copied();
+        }
+        let mut pick = random % total;
+        for mv in &moves {
+            if pick < mv.weight as u64 {
+                return Some(*mv);
+            }
+            pick -= mv.weight as u64;
+        }
+        None
+    }
+}
+
+/// Collects the moves of games into a book. Every move counts 2 for a win of
+/// the player making it, 1 for a draw or an unfinished game and 0 for a loss,
+/// like Polyglot's own book builder.
+#[derive(Debug, Clone, Default)]
+pub struct BookBuilder {
+    /// Plies per game that go into the book, all when `None`.
+    pub max_ply: Option<usize>,
+    /// Moves played in fewer games are left out.
+    pub min_games: u32,
+    moves: HashMap<(u64, u16), (u32, u32)>,
+}
+
+impl BookBuilder {
+    pub fn new(max_ply: Option<usize>) -> Self {
+        Self { max_ply, min_games: 1, moves: HashMap::new() }
+    }
+
+    /// Adds the mainline of a game.
+    pub fn add_game(&mut self, game: &PgnGame) -> GameResult<()> {
+        let mut position = Game::from_fen(game.game.start_fen())?;
+        let limit = self.max_ply.unwrap_or(usize::MAX);
+        for record in game.game.history().iter().take(limit) {
+            let points = match (game.result.as_str(), position.current_player) {
+                ("1-0", Color::White) | ("0-1", Color::Black) => 2,
+                ("1-0", Color::Black) | ("0-1", Color::White) => 0,
+                _ => 1,
+            };
+            let entry = self.moves.entry((polyglot_key(&position), encode_move(&position, record.mv))).or_default();
+            entry.0 += 1;
+            entry.1 += points;
+            position.make_move(record.mv)?;
+        }
+        Ok(())
+    }
+
+    /// Adds every game of a PGN text.
+    pub fn add_pgn(&mut self, text: &str) -> GameResult<()> {
+        for game in pgn::parse(text)? {
+            self.add_game(&game)?;
+        }
+        Ok(())
+    }
+
+    /// The book of all moves added so far. Weights are scaled down when the
+    /// largest does not fit into 16 bits.
+    pub fn build(&self) -> OpeningBook {
+        let max = self.moves.values().map(|(_, points)| *points).max().unwrap_or(0);
+        let scale = |points: u32| if max > u16::MAX as u32 {
+            (points as u64 * u16::MAX as u64 / max as u64) as u16
+        } else {
+            points as u16
+        };
+        let entries = self.moves.iter()
+            .filter(|(_, (games, _))| *games >= self.min_games)
+            .map(|(&(key, raw_move), &(_, points))| BookEntry { key, raw_move, weight: scale(points), learn: 0 })
+            .collect();
+        OpeningBook::from_entries(entries)
+    }
+}
+
+/// Plays from an opening book while the position is in it and lets another
+/// player choose once it is not.
+pub struct BookPlayer {
+    book: OpeningBook,
+    inner: Box<dyn MoveProvider>,
+    choice: BookChoice,
+    random: SplitMix64,
+}
+
+impl BookPlayer {
+    pub fn new(book: OpeningBook, inner: Box<dyn MoveProvider>, choice: BookChoice) -> Self {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64);
+        Self::with_seed(book, inner, choice, seed)
+    }
+
+    /// Like `new`, with a fixed seed for the random choices.
+    pub fn with_seed(book: OpeningBook, inner: Box<dyn MoveProvider>, choice: BookChoice, seed: u64) -> Self {
+        Self { book, inner, choice, random: SplitMix64::new(seed) }
+    }
+}
+
+impl MoveProvider for BookPlayer {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn choose_move(&mut self, game: &Game, limits: SearchLimits) -> GameResult<PlayerMove> {
+        let random = self.random.next_u64();
+        match self.book.choose(game, self.choice, random) {
+            Some(book_move) => Ok(PlayerMove::new(book_move.mv)),
+            None => self.inner.choose_move(game, limits),
+        }
+    }
+
+    fn new_game(&mut self) -> GameResult<()> {
+        self.inner.new_game()
+    }
+}
@@ -0,0 +1,357 @@
+use std::fmt;
+use std::fmt::Formatter;
+use crate::game::bitboard::{self, Bitboard, Bitboards, FILE_A};
+use crate::game::{Color, Game, PieceType, Square};
+
+/// Game phase of the starting position, knights and bishops count 1, rooks 2
+/// and queens 4. Terms are blended from their middlegame value at full phase
+/// to their endgame value at phase 0.
+pub const MAX_PHASE: i32 = 24;
+
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King,
+];
+
+/// Middlegame and endgame values of a term.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Score(i32, i32);
+
+impl Score {
+    fn add(&mut self, other: Score, sign: i32) {
+        self.0 += other.0 * sign;
+        self.1 += other.1 * sign;
+    }
+
+    fn taper(self, phase: i32) -> i32 {
+        (self.0 * phase + self.1 * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+fn material(piece_type: PieceType) -> Score {
+    match piece_type {
+        PieceType::Pawn => Score(82, 94),
+        PieceType::Knight => Score(337, 281),
+        PieceType::Bishop => Score(365, 297),
+        PieceType::Rook => Score(477, 512),
+        PieceType::Queen => Score(1025, 936),
+        PieceType::King => Score(0, 0),
+    }
+}
+
+fn phase_weight(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Knight | PieceType::Bishop => 1,
+        PieceType::Rook => 2,
+        PieceType::Queen => 4,
+        PieceType::Pawn | PieceType::King => 0,
+    }
+}
+
+// Piece-square tables from White's side, written with rank 8 on top.
+#[rustfmt::skip]
+const PAWN_MG: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+#[rustfmt::skip]
+const PAWN_EG: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    80, 80, 80, 80, 80, 80, 80, 80,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    30, 30, 30, 30, 30, 30, 30, 30,
+    20, 20, 20, 20, 20, 20, 20, 20,
+    10, 10, 10, 10, 10, 10, 10, 10,
+    10, 10, 10, 10, 10, 10, 10, 10,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+#[rustfmt::skip]
+const KNIGHT: [i32; 64] = [
+   -50,-40,-30,-30,-30,-30,-40,-50,
+   -40,-20,  0,  0,  0,  0,-20,-40,
+   -30,  0, 10, 15, 15, 10,  0,-30,
+   -30,  5, 15, 20, 20, 15,  5,-30,
+   -30,  0, 15, 20, 20, 15,  0,-30,
+   -30,  5, 10, 15, 15, 10,  5,-30,
+   -40,-20,  0,  5,  5,  0,-20,-40,
+   -50,-40,-30,-30,-30,-30,-40,-50,
+];
+#[rustfmt::skip]
+const BISHOP: [i32; 64] = [
+   -20,-10,-10,-10,-10,-10,-10,-20,
+   -10,  0,  0,  0,  0,  0,  0,-10,
+   -10,  0,  5, 10, 10,  5,  0,-10,
+   -10,  5,  5, 10, 10,  5,  5,-10,
+   -10,  0, 10, 10, 10, 10,  0,-10,
+   -10, 10, 10, 10, 10, 10, 10,-10,
+   -10,  5,  0,  0,  0,  0,  5,-10,
+   -20,-10,-10,-10,-10,-10,-10,-20,
+];
+#[rustfmt::skip]
+const ROOK: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+#[rustfmt::skip]
+const QUEEN: [i32; 64] = [
+   -20,-10,-10, -5, -5,-10,-10,-20,
+   -10,  0,  0,  0,  0,  0,  0,-10,
+   -10,  0,  5,  5,  5,  5,  0,-10,
+    -5,  0,  5,  5,  5,  5,  0, -5,
+     0,  0,  5,  5,  5,  5,  0, -5,
+   -10,  5,  5,  5,  5,  5,  0,-10,
+   -10,  0,  5,  0,  0,  0,  0,-10,
+   -20,-10,-10, -5, -5,-10,-10,-20,
+];
+#[rustfmt::skip]
+const KING_MG: [i32; 64] = [
+   -30,-40,-40,-50,-50,-40,-40,-30,
+   -30,-40,-40,-50,-50,-40,-40,-30,
+   -30,-40,-40,-50,-50,-40,-40,-30,
+   -30,-40,-40,-50,-50,-40,-40,-30,
+   -20,-30,-30,-40,-40,-30,-30,-20,
+   -10,-20,-20,-20,-20,-20,-20,-10,
+    20, 20,  0,  0,  0,  0, 20, 20,
+    20, 30, 10,  0,  0, 10, 30, 20,
+];
+#[rustfmt::skip]
+const KING_EG: [i32; 64] = [
+   -50,-40,-30,-20,-20,-30,-40,-50,
+   -30,-20,-10,  0,  0,-10,-20,-30,
+   -30,-10, 20, 30, 30, 20,-10,-30,
+   -30,-10, 30, 40, 40, 30,-10,-30,
+   -30,-10, 30, 40, 40, 30,-10,-30,
+   -30,-10, 20, 30, 30, 20,-10,-30,
+   -30,-30,  0,  0,  0,  0,-30,-30,
+   -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+fn piece_square(piece_type: PieceType, color: Color, square: Square) -> Score {
+    // The tables are written rank 8 first, Black uses them mirrored.
+    let index = match color {
+        Color::White => (8 - square.rank() as usize) * 8 + square.index() % 8,
+        Color::Black => square.index(),
+    };
+    match piece_type {
+        PieceType::Pawn => Score(PAWN_MG[index], PAWN_EG[index]),
+        PieceType::Knight => Score(KNIGHT[index], KNIGHT[index]),
+        PieceType::Bishop => Score(BISHOP[index], BISHOP[index]),
+        PieceType::Rook => Score(ROOK[index], ROOK[index]),
+        PieceType::Queen => Score(QUEEN[index], QUEEN[index]),
+        PieceType::King => Score(KING_MG[index], KING_EG[index]),
+    }
+}
+
+const DOUBLED_PAWN: Score = Score(-10, -20);
+const ISOLATED_PAWN: Score = Score(-10, -15);
+/// Bonus for a passed pawn by how many ranks it has advanced.
+const PASSED_PAWN: [Score; 8] = [
+    Score(0, 0), Score(5, 10), Score(10, 15), Score(15, 25),
+    Score(25, 45), Score(40, 70), Score(60, 110), Score(0, 0),
+];
+const BISHOP_PAIR: Score = Score(30, 50);
+/// Per square a piece can move to.
+fn mobility_weight(piece_type: PieceType) -> Score {
+    match piece_type {
+        PieceType::Knight => Score(4, 4),
+        PieceType::Bishop => Score(5, 5),
+        PieceType::Rook => Score(2, 4),
+        PieceType::Queen => Score(1, 2),
+        PieceType::Pawn | PieceType::King => Score(0, 0),
+    }
+}
+/// Per own pawn in front of the king.
+const PAWN_SHIELD: Score = Score(10, 0);
+/// Per attack by an enemy piece on a square next to the king.
+const KING_ZONE_ATTACK: Score = Score(-8, 0);
+
+/// The evaluation split into its terms, all in centipawns from White's point
+/// of view and already blended by the game phase.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Evaluation {
+    pub material: i32,
+    pub piece_squares: i32,
+    pub pawn_structure: i32,
+    pub mobility: i32,
+    pub king_safety: i32,
+    pub bishop_pair: i32,
+    /// From `MAX_PHASE` with all pieces on the board down to 0.
+    pub phase: i32,
+}
+
+impl Evaluation {
+    /// The sum of all terms, from White's point of view.
+    pub fn total(&self) -> i32 {
+        self.material + self.piece_squares + self.pawn_structure + self.mobility + self.king_safety + self.bishop_pair
+    }
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Material        {:>6}", self.material)?;
+        writeln!(f, "Piece squares   {:>6}", self.piece_squares)?;
+        writeln!(f, "Pawn structure  {:>6}", self.pawn_structure)?;
+        writeln!(f, "Mobility        {:>6}", self.mobility)?;
+        writeln!(f, "King safety     {:>6}", self.king_safety)?;
+        writeln!(f, "Bishop pair     {:>6}", self.bishop_pair)?;
+        writeln!(f, "Total (White)   {:>6}", self.total())?;
+        write!(f, "Phase           {:>6}", self.phase)
+    }
+}
+
+/// Value of a piece in centipawns at the start of the game, the king is not counted.
+pub fn piece_value(piece_type: PieceType) -> i32 {
+    material(piece_type).0
+}
+
+/// The evaluation in centipawns, from the point of view of the player to move.
+pub fn evaluate(game: &Game) -> i32 {
+    let total = evaluate_breakdown(game).total();
+    match game.current_player {
+        Color::White => total,
+        Color::Black => -total,
+    }
+}
+
+/// Evaluates the position term by term, see `Evaluation`.
+pub fn evaluate_breakdown(game: &Game) -> Evaluation {
+    let bitboards = game.bitboards();
+    let phase = PIECE_TYPES.iter()
+        .map(|&t| phase_weight(t) * (bitboards.pieces(Color::White, t) | bitboards.pieces(Color::Black, t)).count_ones() as i32)
+        .sum::<i32>()
+        .min(MAX_PHASE);
+
+    let (mut material_score, mut psqt, mut pawns, mut mobility, mut king, mut pair) =
+        (Score::default(), Score::default(), Score::default(), Score::default(), Score::default(), Score::default());
+    for color in [Color::White, Color::Black] {
+        let sign = if color == Color::White { 1 } else { -1 };
+        for piece_type in PIECE_TYPES {
+            for square in bitboard::squares(bitboards.pieces(color, piece_type)) {
+                material_score.add(material(piece_type), sign);
+                psqt.add(piece_square(piece_type, color, square), sign);
+            }
+        }
+        pawns.add(pawn_structure(bitboards, color), sign);
+        mobility.add(piece_mobility(bitboards, color), sign);
+        king.add(king_safety(bitboards, color), sign);
+        if bitboards.pieces(color, PieceType::Bishop).count_ones() >= 2 {
+            pair.add(BISHOP_PAIR, sign);
+        }
+    }
+
+    Evaluation {
+        material: material_score.taper(phase),
+        piece_squares: psqt.taper(phase),
+        pawn_structure: pawns.taper(phase),
+        mobility: mobility.taper(phase),
+        king_safety: king.taper(phase),
+        bishop_pair: pair.taper(phase),
+        phase,
+    }
+}
+
+fn opponent(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    }
+}
+
+fn file_mask(file: usize) -> Bitboard {
+    FILE_A << file
+}
+
+fn adjacent_files(file: usize) -> Bitboard {
+    let left = if file > 0 { file_mask(file - 1) } else { 0 };
+    let right = if file < 7 { file_mask(file + 1) } else { 0 };
+    left | right
+}
+
+/// The squares strictly in front of `square`, seen from `color`.
+fn ranks_ahead(square: Square, color: Color) -> Bitboard {
+    let rank = square.index() / 8;
+    match color {
+        Color::White if rank < 7 => !0u64 << ((rank + 1) * 8),
+        Color::Black if rank > 0 => !0u64 >> ((8 - rank) * 8),
+        _ => 0,
+    }
+}
+
+/// Doubled, isolated and passed pawns.
+fn pawn_structure(bitboards: &Bitboards, color: Color) -> Score {
+    let own = bitboards.pieces(color, PieceType::Pawn);
+    let enemy = bitboards.pieces(opponent(color), PieceType::Pawn);
+    let mut score = Score::default();
+
+    for file in 0..8 {
+        let on_file = (own & file_mask(file)).count_ones() as i32;
+        if on_file > 1 {
+            score.add(DOUBLED_PAWN, on_file - 1);
+        }
+        if on_file > 0 && own & adjacent_files(file) == 0 {
+            score.add(ISOLATED_PAWN, on_file);
+        }
+    }
+    for square in bitboard::squares(own) {
+        let file = square.index() % 8;
+        let blockers = enemy & ranks_ahead(square, color) & (file_mask(file) | adjacent_files(file));
+        if blockers == 0 {
+            let advanced = match color {
+                Color::White => square.rank() - 1,
+                Color::Black => 8 - square.rank(),
+            };
+            score.add(PASSED_PAWN[advanced as usize], 1);
+        }
+    }
+    score
+}
+
+/// Squares the pieces can move to, not counting squares taken by own pieces.
+fn piece_mobility(bitboards: &Bitboards, color: Color) -> Score {
+    let own = bitboards.color(color);
+    let mut score = Score::default();
+    for piece_type in [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen] {
+        for square in bitboard::squares(bitboards.pieces(color, piece_type)) {
+            let moves = (bitboards.attacks_from(square) & !own).count_ones() as i32;
+            score.add(mobility_weight(piece_type), moves);
+        }
+    }
+    score
+}
+
+/// The pawn shield in front of the king and attacks on the squares around it.
+/// Only counts in the middlegame.
+fn king_safety(bitboards: &Bitboards, color: Color) -> Score {
+    let Some(king) = bitboards.king_square(color) else {
+        return Score::default();
+    };
+    let mut score = Score::default();
+
+    let file = king.index() % 8;
+    let shield_files = file_mask(file) | adjacent_files(file);
+    let forward = if color == Color::White { 1 } else { -1 };
+    let shield_ranks = [king.offset(0, forward), king.offset(0, 2 * forward)].into_iter().flatten()
+        .fold(0, |acc, square| acc | (bitboard::RANK_1 << (square.index() / 8 * 8)));
+    let shield = bitboards.pieces(color, PieceType::Pawn) & shield_files & shield_ranks;
+    score.add(PAWN_SHIELD, shield.count_ones().min(3) as i32);
+
+    let zone = bitboard::king_attacks(king);
+    let enemy = opponent(color);
+    for piece_type in [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen] {
+        for square in bitboard::squares(bitboards.pieces(enemy, piece_type)) {
+            score.add(KING_ZONE_ATTACK, (bitboards.attacks_from(square) & zone).count_ones() as i32);
+        }
+    }
+    score
+}
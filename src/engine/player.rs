@@ -0,0 +1,167 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::io::{self, BufRead};
+use std::time::Duration;
+use crate::engine::search::{self, SearchLimits, Searcher};
+use crate::game::{Color, Game, Move};
+use crate::game::errors::{GameErr, GameResult};
+use crate::notation::san;
+
+/// An evaluation from the point of view of the player to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    Centipawns(i32),
+    /// Moves until mate, negative when the player to move gets mated.
+    Mate(i32),
+}
+
+impl Score {
+    /// Converts a score of the built-in search.
+    pub fn from_search(score: i32) -> Self {
+        search::mate_in(score).map_or(Score::Centipawns(score), Score::Mate)
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Score::Centipawns(cp) => write!(f, "{:+.2}", *cp as f64 / 100.0),
+            Score::Mate(moves) => write!(f, "#{}", moves),
+        }
+    }
+}
+
+/// The move a player chose, with its evaluation when the player has one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerMove {
+    pub mv: Move,
+    pub score: Option<Score>,
+}
+
+impl PlayerMove {
+    pub fn new(mv: Move) -> Self {
+        Self { mv, score: None }
+    }
+}
+
+/// Anything that can choose a move in a position: a remote API, an engine
+/// binary, the built-in search or a person at the keyboard.
+pub trait MoveProvider {
+    /// Shown in the game output and used for the PGN tags.
+    fn name(&self) -> String;
+
+    /// Chooses a move for the player to move in `game`. Players that think
+    /// respect `limits` as far as they support them.
+    fn choose_move(&mut self, game: &Game, limits: SearchLimits) -> GameResult<PlayerMove>;
+
+    /// Called before the first move of a new game.
+    fn new_game(&mut self) -> GameResult<()> {
+        Ok(())
+    }
+}
+
+impl MoveProvider for Searcher {
+    fn name(&self) -> String {
+        "Built-in engine".into()
+    }
+
+    fn choose_move(&mut self, game: &Game, limits: SearchLimits) -> GameResult<PlayerMove> {
+        let result = self.search(game, limits);
+        let mv = result.best_move.ok_or_else(|| GameErr::Engine("no legal move".into()))?;
+        Ok(PlayerMove { mv, score: Some(Score::from_search(result.score)) })
+    }
+}
+
+/// A person entering moves, either in SAN like "Nf3" or as "g1=>f3".
+/// "HINT" asks the built-in engine for a move and "QUIT" ends the game.
+pub struct HumanPlayer {
+    name: String,
+    input: Box<dyn BufRead>,
+}
+
+impl HumanPlayer {
+    pub fn new(name: &str, input: Box<dyn BufRead>) -> Self {
+        Self { name: name.into(), input }
+    }
+
+    /// Reads the moves from standard input.
+    pub fn stdin(name: &str) -> Self {
+        Self::new(name, Box::new(io::BufReader::new(io::stdin())))
+    }
+}
+
+impl MoveProvider for HumanPlayer {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn choose_move(&mut self, game: &Game, _limits: SearchLimits) -> GameResult<PlayerMove> {
+        loop {
+            println!(" ");
+            println!("Current move is: {}", game.current_player.display());
+            println!("Please enter a move like 'Nf3' or in the format 'a1=>a2', add the piece when promoting 'a7=>a8q', or 'HINT': ");
+            let mut input = String::new();
+            let read = self.input.read_line(&mut input).map_err(|e| GameErr::Engine(e.to_string()))?;
+            let input = input.trim();
+            if read == 0 || input.eq_ignore_ascii_case("QUIT") {
+                return Err(GameErr::PlayerQuit);
+            }
+            if input.eq_ignore_ascii_case("HINT") {
+                let result = Searcher::new().search(game, SearchLimits::movetime(Duration::from_millis(1000)));
+                if let Some(hint) = result.best_move {
+                    println!("Hint: {}", san::to_san(game, hint).unwrap_or_else(|_| hint.to_string()));
+                }
+                continue;
+            }
+            match parse_input(game, input) {
+                Ok(mv) => return Ok(PlayerMove::new(mv)),
+                Err(e) => println!("Error: {}", e),
+            }
+        }
+    }
+}
+
+/// Parses and checks a move typed by a player.
+fn parse_input(game: &Game, input: &str) -> GameResult<Move> {
+    let mv = match input.split_once("=>") {
+        Some((from, to)) => format!("{}{}", from.trim(), to.trim()).parse()?,
+        None => san::parse_san(game, input)?,
+    };
+    game.clone().make_move(mv)?;
+    Ok(mv)
+}
+
+/// A move made in `play_game`, passed to its `on_move` callback.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayedMove {
+    pub player: String,
+    pub san: String,
+    pub chosen: PlayerMove,
+}
+
+/// Plays `game` to the end, asking `white` and `black` for their moves.
+/// `on_move` is called with the position after every move. A player that
+/// fails or quits ends the game early with its error, `game` then holds the
+/// moves played so far.
+pub fn play_game(
+    game: &mut Game,
+    white: &mut dyn MoveProvider,
+    black: &mut dyn MoveProvider,
+    limits: SearchLimits,
+    mut on_move: impl FnMut(&Game, &PlayedMove),
+) -> GameResult<()> {
+    white.new_game()?;
+    black.new_game()?;
+    while !game.status().is_over() {
+        let player: &mut dyn MoveProvider = match game.current_player {
+            Color::White => &mut *white,
+            Color::Black => &mut *black,
+        };
+        let chosen = player.choose_move(game, limits)?;
+        let san = san::to_san(game, chosen.mv).unwrap_or_else(|_| chosen.mv.to_string());
+        game.make_move(chosen.mv)
+            .map_err(|e| GameErr::Engine(format!("{} played {}: {}", player.name(), chosen.mv, e)))?;
+        on_move(game, &PlayedMove { player: player.name(), san, chosen });
+    }
+    Ok(())
+}
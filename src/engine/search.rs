@@ -0,0 +1,423 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use crate::engine::eval;
+use crate::engine::syzygy::{Tablebase, Wdl};
+use crate::engine::tt::{self, Bound, TranspositionTable};
+use crate::game::{Game, Move, Piece, PieceType};
+use crate::ruleengine;
+
+/// Score of giving mate right now, a mate `n` plies away scores `MATE - n`.
+pub const MATE: i32 = 30_000;
+const INFINITY: i32 = 32_000;
+const MAX_PLY: usize = 128;
+/// Score of a tablebase win, below all mate scores.
+pub const TB_WIN: i32 = MATE - 2 * MAX_PLY as i32;
+/// History scores stay below the killer move scores.
+const HISTORY_MAX: i32 = 60_000;
+
+/// When to stop searching. Without any limit the search runs until it is
+/// stopped through `Searcher::stop_flag`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub movetime: Option<Duration>,
+    pub nodes: Option<u64>,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u32) -> Self {
+        Self { depth: Some(depth), ..Self::default() }
+    }
+
+    pub fn movetime(movetime: Duration) -> Self {
+        Self { movetime: Some(movetime), ..Self::default() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    /// `None` only when the player to move has no legal move.
+    pub best_move: Option<Move>,
+    /// Centipawns from the point of view of the player to move.
+    pub score: i32,
+    /// Depth of the last completed iteration.
+    pub depth: u32,
+    pub nodes: u64,
+    /// The expected line of play, starting with the best move.
+    pub pv: Vec<Move>,
+    /// Use of the transposition table per thousand.
+    pub hashfull: u32,
+}
+
+impl SearchResult {
+    /// Moves until mate when the score is a mate score, negative when the
+    /// player to move gets mated.
+    pub fn mate_in(&self) -> Option<i32> {
+        mate_in(self.score)
+    }
+}
+
+/// Moves until mate for a mate score, see `SearchResult::mate_in`.
+pub fn mate_in(score: i32) -> Option<i32> {
+    let plies = MATE - score.abs();
+    if plies > MAX_PLY as i32 {
+        return None;
+    }
+    Some(if score > 0 { (plies + 1) / 2 } else { -(plies / 2) })
+}
+
+type InfoCallback = Box<dyn FnMut(&SearchResult) + Send>;
+
+/// Negamax search with alpha-beta pruning, iterative deepening, a
+/// transposition table and a quiescence search over captures. Moves are
+/// ordered by the previous iteration's line, the table's best move, captures
+/// by MVV-LVA, killer moves and the history heuristic. The table is kept
+/// between searches. With endgame tablebases only the best moves by the
+/// tables are searched at the root, and positions in the tables are scored
+/// without searching.
+pub struct Searcher {
+    stop: Arc<AtomicBool>,
+    limits: SearchLimits,
+    start: Instant,
+    nodes: u64,
+    stopped: bool,
+    pv_hint: Vec<Move>,
+    killers: Vec<[Option<Move>; 2]>,
+    history: Box<[[i32; 64]; 64]>,
+    on_iteration: Option<InfoCallback>,
+    tt: TranspositionTable,
+    tablebase: Option<Arc<Tablebase>>,
+    /// The moves searched at the root, `None` for all legal moves.
+    root_moves: Option<Vec<Move>>,
+}
+
+impl Default for Searcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Searcher {
+    pub fn new() -> Self {
+        Self {
+            stop: Arc::new(AtomicBool::new(false)),
+            limits: SearchLimits::default(),
+            start: Instant::now(),
+            nodes: 0,
+            stopped: false,
+            pv_hint: Vec::new(),
+            killers: vec![[None; 2]; MAX_PLY],
+            history: Box::new([[0; 64]; 64]),
+            on_iteration: None,
+            tt: TranspositionTable::default(),
+            tablebase: None,
+            root_moves: None,
+        }
+    }
+
+    /// Replaces the transposition table with an empty one of `megabytes`.
+    pub fn set_hash_size(&mut self, megabytes: usize) {
+        self.tt.resize(megabytes);
+    }
+
+    /// Empties the transposition table, for a new game.
+    pub fn clear_hash(&mut self) {
+        self.tt.clear();
+    }
+
+    /// Uses the tablebases for positions with few pieces.
+    pub fn set_tablebase(&mut self, tablebase: Option<Arc<Tablebase>>) {
+        self.tablebase = tablebase;
+    }
+
+    /// Setting the flag stops a running search, which then returns the best
+    /// move found so far. The flag is cleared when the search returns.
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+
+    /// Calls `callback` with the result of every completed iteration.
+    pub fn set_info_callback(&mut self, callback: impl FnMut(&SearchResult) + Send + 'static) {
+        self.on_iteration = Some(Box::new(callback));
+    }
+
+    /// Searches for the best move of the player to move.
+    pub fn search(&mut self, game: &Game, limits: SearchLimits) -> SearchResult {
+        self.limits = limits;
+        self.start = Instant::now();
+        self.nodes = 0;
+        self.stopped = false;
+        self.pv_hint.clear();
+        self.killers = vec![[None; 2]; MAX_PLY];
+        self.history.iter_mut().for_each(|row| row.fill(0));
+        self.tt.new_search();
+
+        // The search only decides between the moves that keep the best
+        // tablebase result the fastest.
+        self.root_moves = None;
+        let mut root_wdl = None;
+        if let Some(moves) = self.tablebase.as_ref().and_then(|tb| tb.root_moves(game))
+            && let Some(&best) = moves.first() {
+            root_wdl = Some(best.wdl);
+            let best_moves = moves.iter().take_while(|m| m.wdl == best.wdl && m.dtz == best.dtz);
+            self.root_moves = Some(best_moves.map(|m| m.mv).collect());
+        }
+
+        // Moves are played unchecked on this copy and always undone before
+        // the search returns.
+        let mut game = game.clone();
+        let mut result = SearchResult { best_move: None, score: 0, depth: 0, nodes: 0, pv: Vec::new(), hashfull: 0 };
+        let max_depth = limits.depth.unwrap_or(MAX_PLY as u32 / 2).clamp(1, MAX_PLY as u32 / 2);
+        for depth in 1..=max_depth {
+            let mut pv = Vec::new();
+            let score = self.negamax(&mut game, depth as i32, 0, -INFINITY, INFINITY, &mut pv);
+            if self.stopped {
+                // An unfinished iteration is only used when there is nothing better.
+                if result.best_move.is_none() && !pv.is_empty() {
+                    result.best_move = pv.first().copied();
+                    result.pv = pv;
+                }
+                break;
+            }
+            // Mates aside, the tables know the result better than the search.
+            let reported = match root_wdl {
+                Some(wdl) if mate_in(score).is_none() => tablebase_score(wdl, 0),
+                _ => score,
+            };
+            result = SearchResult {
+                best_move: pv.first().copied(),
+                score: reported,
+                depth,
+                nodes: self.nodes,
+                pv: pv.clone(),
+                hashfull: self.tt.hashfull(),
+            };
+            self.pv_hint = pv;
+            if let Some(callback) = self.on_iteration.as_mut() {
+                callback(&result);
+            }
+            // A mate found within the depth can not get any shorter.
+            if mate_in(score).is_some() || result.best_move.is_none() {
+                break;
+            }
+        }
+        if result.best_move.is_none() {
+            let root_moves = self.root_moves.clone().unwrap_or_else(|| game.legal_moves());
+            result.best_move = root_moves.first().copied();
+        }
+        result.nodes = self.nodes;
+        self.stop.store(false, Ordering::Relaxed);
+        result
+    }
+
+    fn negamax(&mut self, game: &mut Game, mut depth: i32, ply: usize, mut alpha: i32, beta: i32, pv: &mut Vec<Move>) -> i32 {
+        pv.clear();
+        if self.should_stop() {
+            return 0;
+        }
+        if ply > 0 && is_draw(game) {
+            return 0;
+        }
+        let in_check = game.bitboards().is_in_check(game.current_player);
+        // Look one ply further when in check, so checks are not cut off at the horizon.
+        if in_check {
+            depth += 1;
+        }
+        if depth <= 0 || ply >= MAX_PLY - 1 {
+            return self.quiescence(game, ply, alpha, beta);
+        }
+        self.nodes += 1;
+
+        let key = game.zobrist_key();
+        // Tables are only probed right after captures and pawn moves, where
+        // the fifty-move rule starts over and the table result holds.
+        if ply > 0
+            && game.half_time_moves == 0
+            && let Some(wdl) = self.tablebase.as_ref().and_then(|tb| tb.probe_wdl(game)) {
+            let score = tablebase_score(wdl, ply);
+            self.tt.store(key, MAX_PLY as i32, score, Bound::Exact, None, ply);
+            return score;
+        }
+
+        let entry = self.tt.probe(key);
+        if let Some(entry) = entry
+            && ply > 0
+            && entry.depth >= depth {
+            let score = tt::score_from_tt(entry.score, ply);
+            let cutoff = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+            if cutoff {
+                if entry.bound == Bound::Exact {
+                    pv.extend(entry.best_move);
+                }
+                return score;
+            }
+        }
+
+        let mut moves = game.legal_moves();
+        if moves.is_empty() {
+            return if in_check { -MATE + ply as i32 } else { 0 };
+        }
+        if ply == 0 && let Some(root_moves) = &self.root_moves {
+            moves.retain(|mv| root_moves.contains(mv));
+        }
+        self.order_moves(game, &mut moves, ply, entry.and_then(|e| e.best_move));
+
+        let original_alpha = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        let mut line = Vec::new();
+        for mv in moves {
+            let quiet = captured_piece(game, mv).is_none() && mv.promotion.is_none();
+            game.make_move_unchecked(mv);
+            let score = -self.negamax(game, depth - 1, ply + 1, -beta, -alpha, &mut line);
+            let _ = game.undo();
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best {
+                best = score;
+                best_move = Some(mv);
+                if score > alpha {
+                    alpha = score;
+                    pv.clear();
+                    pv.push(mv);
+                    pv.extend_from_slice(&line);
+                }
+            }
+            if alpha >= beta {
+                if quiet {
+                    self.store_killer(ply, mv);
+                    let entry = &mut self.history[mv.from.index()][mv.to.index()];
+                    *entry = (*entry + depth * depth).min(HISTORY_MAX);
+                }
+                break;
+            }
+        }
+
+        let bound = if best >= beta {
+            Bound::Lower
+        } else if best > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.tt.store(key, depth, best, bound, best_move, ply);
+        best
+    }
+
+    /// Searches captures and queen promotions until the position is quiet, so
+    /// the evaluation never happens in the middle of an exchange.
+    fn quiescence(&mut self, game: &mut Game, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+        let stand_pat = eval::evaluate(game);
+        if stand_pat >= beta || ply >= MAX_PLY - 1 {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let mut moves: Vec<Move> = game.legal_moves().into_iter()
+            .filter(|mv| captured_piece(game, *mv).is_some() || mv.promotion == Some(PieceType::Queen))
+            .collect();
+        self.order_moves(game, &mut moves, ply, None);
+        for mv in moves {
+            game.make_move_unchecked(mv);
+            let score = -self.quiescence(game, ply + 1, -beta, -alpha);
+            let _ = game.undo();
+            if self.stopped {
+                return 0;
+            }
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+        let out_of_time = self.nodes.is_multiple_of(1024)
+            && self.limits.movetime.is_some_and(|movetime| self.start.elapsed() >= movetime);
+        if out_of_time
+            || self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes)
+            || self.stop.load(Ordering::Relaxed) {
+            self.stopped = true;
+        }
+        self.stopped
+    }
+
+    fn order_moves(&self, game: &Game, moves: &mut [Move], ply: usize, tt_move: Option<Move>) {
+        moves.sort_by_cached_key(|mv| -self.move_score(game, *mv, ply, tt_move));
+    }
+
+    fn move_score(&self, game: &Game, mv: Move, ply: usize, tt_move: Option<Move>) -> i32 {
+        if self.pv_hint.get(ply) == Some(&mv) {
+            return 1_000_000;
+        }
+        if tt_move == Some(mv) {
+            return 900_000;
+        }
+        if let Some(victim) = captured_piece(game, mv) {
+            let attacker = game.board()[mv.from.index()].map_or(0, |p| p.get_points());
+            return 100_000 + victim.get_points() * 100 - attacker;
+        }
+        if let Some(piece_type) = mv.promotion {
+            return 90_000 + piece_type as i32;
+        }
+        match self.killers[ply] {
+            [Some(killer), _] if killer == mv => 80_000,
+            [_, Some(killer)] if killer == mv => 70_000,
+            _ => self.history[mv.from.index()][mv.to.index()],
+        }
+    }
+
+    fn store_killer(&mut self, ply: usize, mv: Move) {
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+    }
+}
+
+/// The piece the move takes, including a pawn taken en passant.
+fn captured_piece(game: &Game, mv: Move) -> Option<Piece> {
+    if let Some(piece) = game.board()[mv.to.index()] {
+        return Some(piece);
+    }
+    let is_pawn = game.board()[mv.from.index()].is_some_and(|p| p.piece_type == PieceType::Pawn);
+    if is_pawn && mv.from.file() != mv.to.file() {
+        return game.board()[ruleengine::en_passant_capture_square(mv.from, mv.to).index()];
+    }
+    None
+}
+
+/// Wins and losses by the tables score like mates beyond the search's
+/// reach, the fifty-move rule draws cursed wins and blessed losses.
+fn tablebase_score(wdl: Wdl, ply: usize) -> i32 {
+    match wdl {
+        Wdl::Win => TB_WIN - ply as i32,
+        Wdl::Loss => -TB_WIN + ply as i32,
+        _ => 0,
+    }
+}
+
+/// Draws inside the search: a repetition counts as soon as it occurs once.
+/// A checkmate on the hundredth half move still wins.
+fn is_draw(game: &Game) -> bool {
+    let is_checkmate = || game.bitboards().is_in_check(game.current_player) && game.legal_moves().is_empty();
+    (game.half_time_moves >= 100 && !is_checkmate())
+        || game.repetition_count() >= 2
+        || ruleengine::is_insufficient_material(game.board())
+}
@@ -0,0 +1,375 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use crate::game::{Color, Game, Move, PieceType};
+use crate::game::errors::{GameErr, GameResult};
+use crate::game::tablebase::EndgameTables;
+
+mod table;
+
+use table::{Material, PieceCode, ProbeFail, Table, TableKind};
+
+pub use crate::game::tablebase::{TablebaseProbe, Wdl};
+
+/// A table by its name, like "KQvKR", and kind.
+type TableId = (String, TableKind);
+
+/// A legal move of a tablebase position with its outcome for the player
+/// making it, see `Tablebase::root_moves`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TablebaseMove {
+    pub mv: Move,
+    /// The outcome when the fifty-move rule counts from the current position,
+    /// so a slow win turns into a cursed win.
+    pub wdl: Wdl,
+    /// Plies until the next capture or pawn move, counting the move itself.
+    pub dtz: i32,
+}
+
+/// Syzygy endgame tablebases read from local directories. Tables are found
+/// by their file names and read into memory on their first probe. Only WDL
+/// (`.rtbw`) tables are needed for the result of a position, DTZ (`.rtbz`)
+/// tables add the distance to the next capture or pawn move. Positions with
+/// castling rights are not in the tables.
+pub struct Tablebase {
+    files: HashMap<TableId, PathBuf>,
+    max_pieces: usize,
+    /// `None` for tables that could not be read.
+    tables: Mutex<HashMap<TableId, Option<Arc<Table>>>>,
+}
+
+impl fmt::Debug for Tablebase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Tablebase").field("tables", &self.files.len()).field("max_pieces", &self.max_pieces).finish()
+    }
+}
+
+impl Tablebase {
+    /// Scans the directories in `paths`, separated like in `PATH`, for table files.
+    pub fn open(paths: impl AsRef<OsStr>) -> GameResult<Self> {
+        let mut files = HashMap::new();
+        for dir in env::split_paths(&paths) {
+            let entries = fs::read_dir(&dir)
+                .map_err(|e| GameErr::InvalidTablebase(format!("{}: {}", dir.display(), e)))?;
+            for path in entries.flatten().map(|entry| entry.path()) {
+                let kind = match path.extension().and_then(OsStr::to_str) {
+                    Some("rtbw") => TableKind::Wdl,
+                    Some("rtbz") => TableKind::Dtz,
+                    _ => continue,
+                };
+                if let Some(name) = path.file_stem().and_then(OsStr::to_str).filter(|name| split_name(name).is_some()) {
+                    files.entry((name.to_string(), kind)).or_insert(path);
+                }
+            }
+        }
+        let max_pieces = files.keys()
+            .filter(|(_, kind)| *kind == TableKind::Wdl)
+            .map(|(name, _)| name.len() - 1)
+            .max()
+            .unwrap_or(0);
+        Ok(Self { files, max_pieces, tables: Mutex::new(HashMap::new()) })
+    }
+
+    /// Number of table files found.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Most pieces, kings included, of the WDL tables found.
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    /// Whether the position could be in the tables: no castling rights and
+    /// no more pieces than the largest table.
+    pub fn covers(&self, game: &Game) -> bool {
+        let pieces = game.board().iter().flatten().count();
+        pieces <= self.max_pieces && game.white_can_castle.is_empty() && game.black_can_castle.is_empty()
+    }
+
+    /// The result of the position, `None` when a table is missing or damaged.
+    pub fn probe_wdl(&self, game: &Game) -> Option<Wdl> {
+        if !self.covers(game) {
+            return None;
+        }
+        self.search(&mut game.clone(), false).map(|(wdl, _)| Wdl::from_value(wdl))
+    }
+
+    /// Plies until the next capture or pawn move with best play, negative when
+    /// losing, 0 for draws. Cursed wins and blessed losses are 100 plies
+    /// further away, beyond the fifty-move rule.
+    pub fn probe_dtz(&self, game: &Game) -> Option<i32> {
+        if !self.covers(game) {
+            return None;
+        }
+        self.dtz(&mut game.clone())
+    }
+
+    /// The result and, if the DTZ table is there, the distance to zeroing.
+    /// `None` when the position is not in the tables, for example with
+    /// castling rights or too many pieces.
+    pub fn probe(&self, game: &Game) -> Option<TablebaseProbe> {
+        let wdl = self.probe_wdl(game)?;
+        Some(TablebaseProbe { wdl, dtz: self.probe_dtz(game) })
+    }
+
+    /// Every legal move with its outcome, the best first. Wins are ordered by
+    /// the shortest way to the next capture or pawn move, losses by the
+    /// longest. `None` when a table is missing or the game is over.
+    pub fn root_moves(&self, game: &Game) -> Option<Vec<TablebaseMove>> {
+        if !self.covers(game) {
+            return None;
+        }
+        let mut game = game.clone();
+        let moves = game.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+        let clock = game.half_time_moves;
+        let mut root_moves = Vec::with_capacity(moves.len());
+        for mv in moves {
+            game.make_move(mv).ok()?;
+            let mut dtz = if game.half_time_moves == 0 {
+                dtz_before_zeroing(-self.search(&mut game, false)?.0)
+            } else if game.half_time_moves >= 100 || game.repetition_count() >= 3 {
+                0
+            } else {
+                let dtz = -self.dtz(&mut game)?;
+                dtz + dtz.signum()
+            };
+            // A mating move ends the game, it does not need to zero.
+            if dtz == 2 && is_checkmate(&game) {
+                dtz = 1;
+            }
+            game.undo().ok()?;
+            root_moves.push(TablebaseMove { mv, wdl: wdl_with_clock(dtz, clock), dtz });
+        }
+        root_moves.sort_by_key(|m| Reverse(rank(m)));
+        Some(root_moves)
+    }
+
+    /// The table of the position's material, read on first use.
+    fn table(&self, game: &Game, kind: TableKind) -> Option<(Arc<Table>, bool)> {
+        let (white, black) = material(game);
+        let (name, black_stronger) = [(format!("{}v{}", white, black), false), (format!("{}v{}", black, white), true)]
+            .into_iter()
+            .find(|(name, _)| self.files.contains_key(&(name.clone(), kind)))?;
+        let mut tables = self.tables.lock().ok()?;
+        let table = tables.entry((name.clone(), kind)).or_insert_with(|| {
+            let path = &self.files[&(name.clone(), kind)];
+            let (white, black) = split_name(&name)?;
+            let table = fs::read(path).ok().and_then(|data| Table::new(kind, Material::new(white, black), data));
+            if table.is_none() {
+                log::warn!("Invalid tablebase file {}", path.display());
+            }
+            table.map(Arc::new)
+        });
+        Some((table.clone()?, black_stronger))
+    }
+
+    /// Looks the position up in its table without searching captures.
+    fn probe_table(&self, game: &Game, kind: TableKind, wdl: i32) -> Result<i32, ProbeFail> {
+        let pieces: Vec<(usize, PieceCode)> = game.board().iter().enumerate()
+            .filter_map(|(square, piece)| piece.map(|p| (square, piece_code(p.piece_type, p.color))))
+            .collect();
+        // Bare kings have no table.
+        if pieces.len() == 2 {
+            return Ok(0);
+        }
+        let (table, black_stronger) = self.table(game, kind).ok_or(ProbeFail::Corrupt)?;
+        table.probe(&pieces, game.current_player == Color::White, black_stronger, wdl)
+    }
+
+    /// The WDL value from -2 to 2 and whether the best move captures or, with
+    /// `check_zeroing`, moves a pawn. Tables do not store en passant rights,
+    /// and a capture may be better than the stored value, so captures are
+    /// searched first.
+    fn search(&self, game: &mut Game, check_zeroing: bool) -> Option<(i32, bool)> {
+        let moves = game.legal_moves();
+        let total = moves.len();
+        let mut searched = 0;
+        let mut best = -3;
+        for mv in moves {
+            let (capture, pawn) = zeroing_kind(game, mv);
+            if !capture && (!check_zeroing || !pawn) {
+                continue;
+            }
+            searched += 1;
+            game.make_move(mv).ok()?;
+            let value = self.search(game, false).map(|(value, _)| -value);
+            game.undo().ok()?;
+            let value = value?;
+            if value > best {
+                best = value;
+                if value >= 2 {
+                    return Some((value, true));
+                }
+            }
+        }
+
+        // With every move searched the table is not needed, and its value
+        // could be wrong with en passant rights.
+        let all_searched = searched > 0 && searched == total;
+        let value = if all_searched {
+            best
+        } else if total == 0 {
+            if game.bitboards().is_in_check(game.current_player) { -2 } else { 0 }
+        } else {
+            self.probe_table(game, TableKind::Wdl, 0).ok()?
+        };
+        if best >= value {
+            return Some((best, best > 0 || all_searched));
+        }
+        Some((value, false))
+    }
+
+    fn dtz(&self, game: &mut Game) -> Option<i32> {
+        let (wdl, zeroing) = self.search(game, true)?;
+        if wdl == 0 {
+            return Some(0);
+        }
+        if zeroing {
+            return Some(dtz_before_zeroing(wdl));
+        }
+        match self.probe_table(game, TableKind::Dtz, wdl) {
+            Ok(dtz) => return Some((dtz + if wdl.abs() == 1 { 100 } else { 0 }) * wdl.signum()),
+            Err(ProbeFail::Corrupt) => return None,
+            Err(ProbeFail::ChangeStm) => {}
+        }
+
+        // The table only has the other side to move, one ply more is
+        // searched. The best move does not zero here, or it would have been
+        // found above.
+        let mut min = i32::MAX;
+        for mv in game.legal_moves() {
+            let (capture, pawn) = zeroing_kind(game, mv);
+            game.make_move(mv).ok()?;
+            // A mating move ends the game, the table of the mated side is
+            // not asked.
+            let dtz = if is_checkmate(game) {
+                Some(1)
+            } else if capture || pawn {
+                self.search(game, false).map(|(value, _)| -dtz_before_zeroing(value))
+            } else {
+                self.dtz(game).map(|dtz| {
+                    let dtz = -dtz;
+                    dtz + dtz.signum()
+                })
+            };
+            game.undo().ok()?;
+            let dtz = dtz?;
+            if dtz.signum() == wdl.signum() && dtz < min {
+                min = dtz;
+            }
+        }
+        Some(if min == i32::MAX { -1 } else { min })
+    }
+}
+
+impl EndgameTables for Tablebase {
+    fn probe(&self, game: &Game) -> Option<TablebaseProbe> {
+        Tablebase::probe(self, game)
+    }
+}
+
+/// Splits a table name like "KQvKR" into the pieces of both sides.
+fn split_name(name: &str) -> Option<(&str, &str)> {
+    let (white, black) = name.split_once('v')?;
+    let valid = |side: &str| side.starts_with('K') && side.chars().skip(1).all(|c| "QRBNP".contains(c));
+    (valid(white) && valid(black) && white.len() + black.len() <= table::MAX_PIECES).then_some((white, black))
+}
+
+/// The pieces of both sides in table name order, like ("KQ", "KR").
+fn material(game: &Game) -> (String, String) {
+    let side = |color: Color| {
+        let mut pieces: Vec<PieceType> = game.board().iter().flatten()
+            .filter(|p| p.color == color)
+            .map(|p| p.piece_type)
+            .collect();
+        pieces.sort_by_key(|&piece_type| Reverse(piece_type_order(piece_type)));
+        pieces.into_iter().map(piece_letter).collect::<String>()
+    };
+    (side(Color::White), side(Color::Black))
+}
+
+fn piece_type_order(piece_type: PieceType) -> u8 {
+    match piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    }
+}
+
+fn piece_letter(piece_type: PieceType) -> char {
+    match piece_type {
+        PieceType::Pawn => 'P',
+        PieceType::Knight => 'N',
+        PieceType::Bishop => 'B',
+        PieceType::Rook => 'R',
+        PieceType::Queen => 'Q',
+        PieceType::King => 'K',
+    }
+}
+
+fn piece_code(piece_type: PieceType, color: Color) -> PieceCode {
+    piece_type_order(piece_type) + 1 + if color == Color::Black { 8 } else { 0 }
+}
+
+/// Whether the move captures and whether it moves a pawn.
+fn zeroing_kind(game: &Game, mv: Move) -> (bool, bool) {
+    let pawn = game.board()[mv.from.index()].is_some_and(|p| p.piece_type == PieceType::Pawn);
+    let capture = game.board()[mv.to.index()].is_some() || (pawn && mv.from.file() != mv.to.file());
+    (capture, pawn)
+}
+
+fn is_checkmate(game: &Game) -> bool {
+    game.bitboards().is_in_check(game.current_player) && game.legal_moves().is_empty()
+}
+
+/// DTZ of a position whose best move zeroes the fifty-move counter.
+fn dtz_before_zeroing(wdl: i32) -> i32 {
+    match wdl {
+        2 => 1,
+        1 => 101,
+        -1 => -101,
+        -2 => -1,
+        _ => 0,
+    }
+}
+
+/// The outcome of a root move when `clock` half moves have been played
+/// since the last capture or pawn move.
+fn wdl_with_clock(dtz: i32, clock: i32) -> Wdl {
+    match dtz {
+        0 => Wdl::Draw,
+        dtz if dtz > 0 && dtz + clock <= 100 => Wdl::Win,
+        dtz if dtz > 0 => Wdl::CursedWin,
+        dtz if -dtz + clock <= 100 => Wdl::Loss,
+        _ => Wdl::BlessedLoss,
+    }
+}
+
+/// Orders root moves by outcome, then by distance: quick wins and slow losses first.
+fn rank(m: &TablebaseMove) -> i32 {
+    const BAND: i32 = 1 << 16;
+    let distance = m.dtz.abs().min(BAND - 1);
+    match m.wdl {
+        Wdl::Win => 2 * BAND - distance,
+        Wdl::CursedWin => BAND - distance,
+        Wdl::Draw => 0,
+        Wdl::BlessedLoss => -BAND + distance,
+        Wdl::Loss => -2 * BAND + distance,
+    }
+}
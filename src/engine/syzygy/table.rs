@@ -0,0 +1,742 @@
+// Decoding of Syzygy table files. A table stores one value per position of
+// its material, the positions are numbered by an index that removes the
+// board symmetries and the values are compressed with recursive pairing and
+// canonical Huffman codes. The layout follows the probing code that comes
+// with the tables.
+use std::sync::OnceLock;
+
+pub(super) const MAX_PIECES: usize = 7;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+// Flags of a table part.
+const FLAG_STM: u8 = 1;
+const FLAG_MAPPED: u8 = 2;
+const FLAG_WIN_PLIES: u8 = 4;
+const FLAG_LOSS_PLIES: u8 = 8;
+const FLAG_WIDE: u8 = 16;
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+// Flags of the file header.
+const HEADER_HAS_PAWNS: u8 = 2;
+
+/// Number of placements of three unique pieces with the first one in the
+/// a1-d1-d4 triangle, and of two kings.
+const UNIQUE_PIECES_SIZE: u64 = 31_332;
+const KINGS_SIZE: u64 = 462;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum TableKind {
+    Wdl,
+    Dtz,
+}
+
+/// Why a table gave no value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ProbeFail {
+    /// DTZ tables store only one side to move, this position is not in it.
+    ChangeStm,
+    /// The file is damaged.
+    Corrupt,
+}
+
+/// Piece codes of the format: 1 to 6 for the white pawn, knight, bishop,
+/// rook, queen and king, 8 more for the black pieces.
+pub(super) type PieceCode = u8;
+
+/// Squares are numbered like `Square::index`, a1 = 0 to h8 = 63.
+fn file_of(square: usize) -> usize {
+    square & 7
+}
+
+fn rank_of(square: usize) -> usize {
+    square >> 3
+}
+
+/// Negative below the a1-h8 diagonal, 0 on it.
+fn off_diagonal(square: usize) -> i32 {
+    rank_of(square) as i32 - file_of(square) as i32
+}
+
+/// Lookup tables of the position index, the same for every table.
+struct Indexes {
+    /// Squares of the a1-d1-d4 triangle to 0..10, the diagonal last.
+    a1d1d4: [usize; 64],
+    /// Squares below the a1-h8 diagonal to 0..28.
+    b1h1h7: [usize; 64],
+    /// Both kings to 0..462, the first one in the a1-d1-d4 triangle.
+    kk: [[usize; 64]; 10],
+    binomial: [[u64; 64]; 8],
+    /// Pawn squares to 0..48, higher towards the edge and the second rank.
+    pawns: [usize; 64],
+    lead_pawn_index: [[u64; 64]; 8],
+    lead_pawns_size: [[u64; 4]; 8],
+}
+
+fn indexes() -> &'static Indexes {
+    static INDEXES: OnceLock<Indexes> = OnceLock::new();
+    INDEXES.get_or_init(Indexes::new)
+}
+
+impl Indexes {
+    fn new() -> Self {
+        let mut ix = Indexes {
+            a1d1d4: [0; 64],
+            b1h1h7: [0; 64],
+            kk: [[0; 64]; 10],
+            binomial: [[0; 64]; 8],
+            pawns: [0; 64],
+            lead_pawn_index: [[0; 64]; 8],
+            lead_pawns_size: [[0; 4]; 8],
+        };
+
+        let mut code = 0;
+        for square in 0..64 {
+            if off_diagonal(square) < 0 {
+                ix.b1h1h7[square] = code;
+                code += 1;
+            }
+        }
+
+        let mut code = 0;
+        let mut diagonal = Vec::new();
+        for rank in 0..4 {
+            for file in 0..4 {
+                let square = rank * 8 + file;
+                match off_diagonal(square) {
+                    off if off < 0 => {
+                        ix.a1d1d4[square] = code;
+                        code += 1;
+                    }
+                    0 => diagonal.push(square),
+                    _ => {}
+                }
+            }
+        }
+        for square in diagonal {
+            ix.a1d1d4[square] = code;
+            code += 1;
+        }
+
+        // Placements with both kings on the diagonal are numbered last. With
+        // the first king on the diagonal the second one is not above it.
+        let mut code = 0;
+        let mut both_on_diagonal = Vec::new();
+        for idx in 0..10 {
+            for s1 in 0..28 {
+                // Squares outside the triangle are mapped to 0 as well as b1.
+                if ix.a1d1d4[s1] != idx || (idx == 0 && s1 != 1) || (file_of(s1) > 3) {
+                    continue;
+                }
+                for s2 in 0..64 {
+                    let adjacent = file_of(s1).abs_diff(file_of(s2)) <= 1 && rank_of(s1).abs_diff(rank_of(s2)) <= 1;
+                    if adjacent || (off_diagonal(s1) == 0 && off_diagonal(s2) > 0) {
+                        continue;
+                    }
+                    if off_diagonal(s1) == 0 && off_diagonal(s2) == 0 {
+                        both_on_diagonal.push((idx, s2));
+                    } else {
+                        ix.kk[idx][s2] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, s2) in both_on_diagonal {
+            ix.kk[idx][s2] = code;
+            code += 1;
+        }
+
+        ix.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..ix.binomial.len().min(n + 1) {
+                ix.binomial[k][n] = if k > 0 { ix.binomial[k - 1][n - 1] } else { 0 }
+                    + if k < n { ix.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        // The leading pawns are indexed per file of the leading one, which is
+        // the pawn nearest the edge and, on the same file, the lowest one.
+        let mut available = 47;
+        for lead_pawns in 1..=5 {
+            for file in 0..4 {
+                let mut idx = 0;
+                for rank in 1..7 {
+                    let square = rank * 8 + file;
+                    if lead_pawns == 1 {
+                        ix.pawns[square] = available;
+                        ix.pawns[square ^ 7] = available - 1;
+                        available = available.saturating_sub(2);
+                    }
+                    ix.lead_pawn_index[lead_pawns][square] = idx;
+                    idx += ix.binomial[lead_pawns - 1][ix.pawns[square]];
+                }
+                ix.lead_pawns_size[lead_pawns][file] = idx;
+            }
+        }
+        ix
+    }
+}
+
+/// One part of a table: the values of one side to move and, with pawns, one
+/// file of the leading pawn. Offsets point into the table's data.
+#[derive(Debug, Clone, Default)]
+struct PairsData {
+    flags: u8,
+    max_sym_len: usize,
+    /// The value itself for `FLAG_SINGLE_VALUE`.
+    min_sym_len: usize,
+    num_blocks: usize,
+    block_size: usize,
+    /// A sparse index entry is kept for every `span` values.
+    span: u64,
+    lowest_sym: usize,
+    btree: usize,
+    block_length: usize,
+    block_length_size: usize,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    data: usize,
+    /// The lowest code of every symbol length, left aligned.
+    base64: Vec<u64>,
+    /// Number of values a symbol stands for, minus one.
+    symlen: Vec<u8>,
+    pieces: [PieceCode; MAX_PIECES],
+    group_idx: [u64; MAX_PIECES + 1],
+    group_len: [usize; MAX_PIECES + 1],
+    /// Offsets of the DTZ value maps for win, loss, cursed win and blessed loss.
+    map_idx: [usize; 4],
+}
+
+/// The material of a table, like "KQvKR" which is white's "KQ" and black's "KR".
+#[derive(Debug, Clone)]
+pub(super) struct Material {
+    /// Both sides have the same pieces.
+    pub(super) symmetric: bool,
+    pub(super) has_pawns: bool,
+    has_unique_pieces: bool,
+    pub(super) piece_count: usize,
+    /// Pawns of the leading color, the one with fewer pawns, and of the other.
+    pawn_count: [usize; 2],
+}
+
+impl Material {
+    pub(super) fn new(white: &str, black: &str) -> Self {
+        let count = |side: &str, piece: char| side.chars().filter(|&c| c == piece).count();
+        let (white_pawns, black_pawns) = (count(white, 'P'), count(black, 'P'));
+        let lead_white = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+        Self {
+            symmetric: white == black,
+            has_pawns: white_pawns + black_pawns > 0,
+            has_unique_pieces: [white, black].iter().any(|side| "PNBRQ".chars().any(|p| count(side, p) == 1)),
+            piece_count: white.len() + black.len(),
+            pawn_count: if lead_white { [white_pawns, black_pawns] } else { [black_pawns, white_pawns] },
+        }
+    }
+}
+
+/// A WDL or DTZ table read into memory.
+pub(super) struct Table {
+    kind: TableKind,
+    material: Material,
+    data: Vec<u8>,
+    /// Indexed by side to move, then by file of the leading pawn.
+    parts: Vec<Vec<PairsData>>,
+    /// Start of the DTZ value maps.
+    map: usize,
+}
+
+impl Table {
+    /// Parses a table file, `None` when it is not a valid table of the material.
+    pub(super) fn new(kind: TableKind, material: Material, data: Vec<u8>) -> Option<Self> {
+        let magic = match kind {
+            TableKind::Wdl => WDL_MAGIC,
+            TableKind::Dtz => DTZ_MAGIC,
+        };
+        // Table files are padded to 64 bytes and followed by a 16 byte checksum.
+        if data.len() % 64 != 16 || data[..4] != magic {
+            return None;
+        }
+        let mut table = Table { kind, material, data, parts: Vec::new(), map: 0 };
+        table.parts = table.parse()?;
+        Some(table)
+    }
+
+    fn byte(&self, offset: usize) -> Option<u8> {
+        self.data.get(offset).copied()
+    }
+
+    fn u16_le(&self, offset: usize) -> Option<u16> {
+        Some(u16::from_le_bytes(self.data.get(offset..offset + 2)?.try_into().ok()?))
+    }
+
+    fn u32_le(&self, offset: usize) -> Option<u32> {
+        Some(u32::from_le_bytes(self.data.get(offset..offset + 4)?.try_into().ok()?))
+    }
+
+    fn u32_be(&self, offset: usize) -> Option<u32> {
+        Some(u32::from_be_bytes(self.data.get(offset..offset + 4)?.try_into().ok()?))
+    }
+
+    fn u64_be(&self, offset: usize) -> Option<u64> {
+        Some(u64::from_be_bytes(self.data.get(offset..offset + 8)?.try_into().ok()?))
+    }
+
+    /// The symbols a pair symbol expands to, 0xfff on the right for a leaf.
+    fn pair(&self, d: &PairsData, symbol: usize) -> Option<(usize, usize)> {
+        let at = d.btree + 3 * symbol;
+        let (b0, b1, b2) = (self.byte(at)? as usize, self.byte(at + 1)? as usize, self.byte(at + 2)? as usize);
+        Some((((b1 & 0xf) << 8) | b0, (b2 << 4) | (b1 >> 4)))
+    }
+
+    fn parse(&mut self) -> Option<Vec<Vec<PairsData>>> {
+        let material = self.material.clone();
+        let header = self.byte(4)?;
+        if (header & HEADER_HAS_PAWNS != 0) != material.has_pawns || material.piece_count > MAX_PIECES {
+            return None;
+        }
+        let sides = if self.kind == TableKind::Wdl && !material.symmetric { 2 } else { 1 };
+        let files = if material.has_pawns { 4 } else { 1 };
+        let both_pawns = material.has_pawns && material.pawn_count[1] > 0;
+        let mut parts = vec![vec![PairsData::default(); files]; sides];
+
+        let mut p = 5;
+        for file in 0..files {
+            let first = self.byte(p)? as usize;
+            let second = if both_pawns { self.byte(p + 1)? as usize } else { 0xff };
+            let order = [[first & 0xf, second & 0xf], [first >> 4, second >> 4]];
+            p += 1 + usize::from(both_pawns);
+            for k in 0..material.piece_count {
+                let pieces = self.byte(p)?;
+                for (side, part) in parts.iter_mut().enumerate() {
+                    part[file].pieces[k] = if side == 1 { pieces >> 4 } else { pieces & 0xf };
+                }
+                p += 1;
+            }
+            for (side, part) in parts.iter_mut().enumerate() {
+                set_groups(&mut part[file], &material, order[side], file)?;
+            }
+        }
+        p += p & 1;
+
+        for file in 0..files {
+            for part in parts.iter_mut() {
+                p = self.set_sizes(&mut part[file], p)?;
+            }
+        }
+        if self.kind == TableKind::Dtz {
+            p = self.set_dtz_map(&mut parts[0], p)?;
+        }
+        for file in 0..files {
+            for part in parts.iter_mut() {
+                part[file].sparse_index = p;
+                p += part[file].sparse_index_size * 6;
+            }
+        }
+        for file in 0..files {
+            for part in parts.iter_mut() {
+                part[file].block_length = p;
+                p += part[file].block_length_size * 2;
+            }
+        }
+        for file in 0..files {
+            for part in parts.iter_mut() {
+                p = (p + 0x3f) & !0x3f;
+                part[file].data = p;
+                p += part[file].num_blocks * part[file].block_size;
+            }
+        }
+        (p <= self.data.len()).then_some(parts)
+    }
+
+    fn set_sizes(&self, d: &mut PairsData, mut p: usize) -> Option<usize> {
+        d.flags = self.byte(p)?;
+        p += 1;
+        if d.flags & FLAG_SINGLE_VALUE != 0 {
+            d.min_sym_len = self.byte(p)? as usize;
+            return Some(p + 1);
+        }
+
+        let size = d.group_idx[d.group_len.iter().position(|&len| len == 0)?];
+        let (block_bits, span_bits) = (self.byte(p)? as u32, self.byte(p + 1)? as u32);
+        d.block_size = 1usize.checked_shl(block_bits)?;
+        d.span = 1u64.checked_shl(span_bits)?;
+        d.sparse_index_size = size.div_ceil(d.span) as usize;
+        let padding = self.byte(p + 2)? as usize;
+        d.num_blocks = self.u32_le(p + 3)? as usize;
+        d.block_length_size = d.num_blocks + padding;
+        d.max_sym_len = self.byte(p + 7)? as usize;
+        d.min_sym_len = self.byte(p + 8)? as usize;
+        p += 9;
+        if d.min_sym_len == 0 || d.max_sym_len < d.min_sym_len || d.max_sym_len > 64 {
+            return None;
+        }
+
+        // Canonical Huffman codes: longer codes have lower values, so the
+        // lowest code of every length, left aligned to 64 bits, tells the
+        // length of the code at the start of the bit buffer.
+        d.lowest_sym = p;
+        let lengths = d.max_sym_len - d.min_sym_len + 1;
+        d.base64 = vec![0; lengths];
+        for i in (0..lengths - 1).rev() {
+            let lowest = self.u16_le(d.lowest_sym + 2 * i)? as u64;
+            let next_lowest = self.u16_le(d.lowest_sym + 2 * (i + 1))? as u64;
+            d.base64[i] = d.base64[i + 1].wrapping_add(lowest).wrapping_sub(next_lowest) / 2;
+        }
+        for (i, base) in d.base64.iter_mut().enumerate() {
+            *base = base.checked_shl((64 - i - d.min_sym_len) as u32).unwrap_or(0);
+        }
+        p += lengths * 2;
+
+        let symbols = self.u16_le(p)? as usize;
+        p += 2;
+        d.btree = p;
+        d.symlen = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for symbol in 0..symbols {
+            if !visited[symbol] {
+                d.symlen[symbol] = self.set_symlen(d, symbol, &mut visited)?;
+            }
+        }
+        Some(p + symbols * 3 + (symbols & 1))
+    }
+
+    /// Counts the values a symbol stands for by expanding its pairs.
+    fn set_symlen(&self, d: &mut PairsData, symbol: usize, visited: &mut [bool]) -> Option<u8> {
+        visited[symbol] = true;
+        let (left, right) = self.pair(d, symbol)?;
+        if right == 0xfff {
+            return Some(0);
+        }
+        for child in [left, right] {
+            if !*visited.get(child)? {
+                d.symlen[child] = self.set_symlen(d, child, visited)?;
+            }
+        }
+        Some(d.symlen[left].wrapping_add(d.symlen[right]).wrapping_add(1))
+    }
+
+    fn set_dtz_map(&mut self, parts: &mut [PairsData], mut p: usize) -> Option<usize> {
+        self.map = p;
+        for d in parts.iter_mut() {
+            if d.flags & FLAG_MAPPED == 0 {
+                continue;
+            }
+            if d.flags & FLAG_WIDE != 0 {
+                p += p & 1;
+                for i in 0..4 {
+                    d.map_idx[i] = (p - self.map) / 2 + 1;
+                    p += 2 * self.u16_le(p)? as usize + 2;
+                }
+            } else {
+                for i in 0..4 {
+                    d.map_idx[i] = p - self.map + 1;
+                    p += self.byte(p)? as usize + 1;
+                }
+            }
+        }
+        Some(p + (p & 1))
+    }
+
+    /// The value stored at a position index.
+    fn decompress(&self, d: &PairsData, idx: u64) -> Option<usize> {
+        if d.flags & FLAG_SINGLE_VALUE != 0 {
+            return Some(d.min_sym_len);
+        }
+
+        // The sparse index gives the block and offset of every span-th value,
+        // from there the block lengths lead to the block holding `idx`.
+        let k = (idx / d.span) as usize;
+        let mut block = self.u32_le(d.sparse_index + 6 * k)? as usize;
+        let mut offset = self.u16_le(d.sparse_index + 6 * k + 4)? as i64;
+        offset += (idx % d.span) as i64 - (d.span / 2) as i64;
+        let block_length = |block: usize| -> Option<i64> {
+            (block < d.block_length_size).then_some(())?;
+            Some(self.u16_le(d.block_length + 2 * block)? as i64)
+        };
+        while offset < 0 {
+            block = block.checked_sub(1)?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+
+        // Reads the symbols of the block until the one covering the offset.
+        let mut ptr = d.data + block * d.block_size;
+        let mut buf = self.u64_be(ptr)?;
+        ptr += 8;
+        let mut buf_size = 64;
+        let mut symbol;
+        loop {
+            let mut len = 0;
+            while buf < d.base64[len] {
+                len += 1;
+                if len == d.base64.len() {
+                    return None;
+                }
+            }
+            symbol = ((buf - d.base64[len]) >> (64 - len - d.min_sym_len)) as usize;
+            symbol += self.u16_le(d.lowest_sym + 2 * len)? as usize;
+            let values = *d.symlen.get(symbol)? as i64 + 1;
+            if offset < values {
+                break;
+            }
+            offset -= values;
+            let bits = len + d.min_sym_len;
+            buf = buf.checked_shl(bits as u32).unwrap_or(0);
+            buf_size -= bits as i32;
+            if buf_size <= 32 {
+                buf_size += 32;
+                buf |= (self.u32_be(ptr)? as u64) << (64 - buf_size);
+                ptr += 4;
+            }
+        }
+
+        // The symbol stands for several values, its pairs are expanded down
+        // to the single value at the offset.
+        while d.symlen[symbol] != 0 {
+            let (left, right) = self.pair(d, symbol)?;
+            let left_values = *d.symlen.get(left)? as i64 + 1;
+            if offset < left_values {
+                symbol = left;
+            } else {
+                offset -= left_values;
+                symbol = right;
+            }
+            d.symlen.get(symbol)?;
+        }
+        Some(self.pair(d, symbol)?.0)
+    }
+
+    /// Looks up the position given by its pieces, in square order. `wdl` is
+    /// the result of the position, only used by DTZ tables. WDL tables give
+    /// -2 (loss) to 2 (win), DTZ tables the plies to the next capture or
+    /// pawn move.
+    pub(super) fn probe(&self, pieces: &[(usize, PieceCode)], white_to_move: bool, black_stronger: bool, wdl: i32)
+        -> Result<i32, ProbeFail> {
+        let ix = indexes();
+        let material = &self.material;
+        // Tables are stored with the stronger side as white, and symmetric
+        // tables only with white to move. Other positions are mirrored.
+        let flip = (!white_to_move && material.symmetric) || black_stronger;
+        let (flip_color, flip_squares) = if flip { (8, 56) } else { (0, 0) };
+        let stm = usize::from(flip) ^ usize::from(!white_to_move);
+
+        let mut squares = [0usize; MAX_PIECES];
+        let mut codes = [0; MAX_PIECES];
+        let mut size = 0;
+        let mut lead_pawns = 0;
+        let mut tb_file = 0;
+        let lead_pawn = self.parts[0][0].pieces[0] ^ flip_color;
+        if material.has_pawns {
+            for &(square, code) in pieces.iter().filter(|(_, code)| *code == lead_pawn) {
+                *squares.get_mut(size).ok_or(ProbeFail::Corrupt)? = square ^ flip_squares;
+                codes[size] = code;
+                size += 1;
+            }
+            lead_pawns = size;
+            let mut lead = 0;
+            for i in 1..lead_pawns {
+                if ix.pawns[squares[i]] > ix.pawns[squares[lead]] {
+                    lead = i;
+                }
+            }
+            squares.swap(0, lead);
+            tb_file = file_of(squares[0]).min(7 - file_of(squares[0]));
+        }
+
+        if self.kind == TableKind::Dtz {
+            let flags = self.parts[0][tb_file].flags;
+            // Symmetric tables without pawns hold both sides to move.
+            let both_sides = material.symmetric && !material.has_pawns;
+            if !both_sides && (flags & FLAG_STM) as usize != stm {
+                return Err(ProbeFail::ChangeStm);
+            }
+        }
+
+        for &(square, code) in pieces {
+            if material.has_pawns && code == lead_pawn {
+                continue;
+            }
+            *squares.get_mut(size).ok_or(ProbeFail::Corrupt)? = square ^ flip_squares;
+            codes[size] = code ^ flip_color;
+            size += 1;
+        }
+        if size < 2 || size != material.piece_count {
+            return Err(ProbeFail::Corrupt);
+        }
+
+        let d = &self.parts[stm % self.parts.len()][tb_file];
+        // Puts the pieces in the order of the table.
+        for i in lead_pawns..size - 1 {
+            for j in i + 1..size {
+                if d.pieces[i] == codes[j] {
+                    codes.swap(i, j);
+                    squares.swap(i, j);
+                    break;
+                }
+            }
+        }
+
+        // Mirrors the board so the leading piece is on files a to d.
+        if file_of(squares[0]) > 3 {
+            squares[..size].iter_mut().for_each(|square| *square ^= 7);
+        }
+
+        let mut idx;
+        if material.has_pawns {
+            idx = ix.lead_pawn_index[lead_pawns][squares[0]];
+            squares[1..lead_pawns].sort_by_key(|&square| ix.pawns[square]);
+            for (i, &square) in squares.iter().enumerate().take(lead_pawns).skip(1) {
+                idx += ix.binomial[i][ix.pawns[square]];
+            }
+        } else {
+            // Without pawns the leading piece also goes below the fifth rank
+            // and, if needed, below the a1-h8 diagonal.
+            if rank_of(squares[0]) > 3 {
+                squares[..size].iter_mut().for_each(|square| *square ^= 56);
+            }
+            for i in 0..d.group_len[0] {
+                match off_diagonal(squares[i]) {
+                    0 => continue,
+                    off if off > 0 => {
+                        for square in &mut squares[i..size] {
+                            *square = ((*square >> 3) | (*square << 3)) & 63;
+                        }
+                    }
+                    _ => {}
+                }
+                break;
+            }
+
+            if material.has_unique_pieces {
+                let adjust1 = usize::from(squares[1] > squares[0]);
+                let adjust2 = usize::from(squares[2] > squares[0]) + usize::from(squares[2] > squares[1]);
+                idx = if off_diagonal(squares[0]) != 0 {
+                    (ix.a1d1d4[squares[0]] * 63 + (squares[1] - adjust1)) * 62 + squares[2] - adjust2
+                } else if off_diagonal(squares[1]) != 0 {
+                    (6 * 63 + rank_of(squares[0]) * 28 + ix.b1h1h7[squares[1]]) * 62 + squares[2] - adjust2
+                } else if off_diagonal(squares[2]) != 0 {
+                    6 * 63 * 62 + 4 * 28 * 62
+                        + rank_of(squares[0]) * 7 * 28
+                        + (rank_of(squares[1]) - adjust1) * 28
+                        + ix.b1h1h7[squares[2]]
+                } else {
+                    6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28
+                        + rank_of(squares[0]) * 7 * 6
+                        + (rank_of(squares[1]) - adjust1) * 6
+                        + (rank_of(squares[2]) - adjust2)
+                } as u64;
+            } else {
+                idx = ix.kk[ix.a1d1d4[squares[0]]][squares[1]] as u64;
+            }
+        }
+
+        // The other groups are placed on the squares left by the earlier ones.
+        idx *= d.group_idx[0];
+        let mut start = d.group_len[0];
+        let mut remaining_pawns = material.has_pawns && material.pawn_count[1] > 0;
+        let mut next = 1;
+        while d.group_len[next] != 0 {
+            let len = d.group_len[next];
+            let group = start..start + len;
+            squares.get_mut(group.clone()).ok_or(ProbeFail::Corrupt)?.sort_unstable();
+            let mut n = 0;
+            for (i, &square) in squares[group].iter().enumerate() {
+                let adjust = squares[..start].iter().filter(|&&s| square > s).count();
+                let free = square.checked_sub(adjust + 8 * usize::from(remaining_pawns)).ok_or(ProbeFail::Corrupt)?;
+                n += ix.binomial[i + 1][free];
+            }
+            remaining_pawns = false;
+            idx += n * d.group_idx[next];
+            start += len;
+            next += 1;
+        }
+
+        let value = self.decompress(d, idx).ok_or(ProbeFail::Corrupt)?;
+        match self.kind {
+            TableKind::Wdl => Ok(value as i32 - 2),
+            TableKind::Dtz => self.map_dtz(tb_file, value, wdl).ok_or(ProbeFail::Corrupt),
+        }
+    }
+
+    /// Turns a stored DTZ value into plies.
+    fn map_dtz(&self, tb_file: usize, value: usize, wdl: i32) -> Option<i32> {
+        // The maps are stored for win, loss, cursed win and blessed loss.
+        const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+        let d = &self.parts[0][tb_file];
+        let mut value = value;
+        if d.flags & FLAG_MAPPED != 0 {
+            let idx = d.map_idx[WDL_MAP[(wdl + 2) as usize]] + value;
+            value = if d.flags & FLAG_WIDE != 0 {
+                self.u16_le(self.map + 2 * idx)? as usize
+            } else {
+                self.byte(self.map + idx)? as usize
+            };
+        }
+        // Some tables store moves instead of plies.
+        let in_moves = (wdl == 2 && d.flags & FLAG_WIN_PLIES == 0)
+            || (wdl == -2 && d.flags & FLAG_LOSS_PLIES == 0)
+            || wdl == 1
+            || wdl == -1;
+        if in_moves {
+            value *= 2;
+        }
+        Some(value as i32 + 1)
+    }
+}
+
+/// Computes the group sizes of a table part and the factor of every group in
+/// the position index.
+fn set_groups(d: &mut PairsData, material: &Material, order: [usize; 2], file: usize) -> Option<()> {
+    let ix = indexes();
+    let mut first_len: i32 = if material.has_pawns { 0 } else if material.has_unique_pieces { 3 } else { 2 };
+    let mut n = 0;
+    d.group_len[0] = 1;
+    for i in 1..material.piece_count {
+        first_len -= 1;
+        if first_len > 0 || d.pieces[i] == d.pieces[i - 1] {
+            d.group_len[n] += 1;
+        } else {
+            n += 1;
+            d.group_len[n] = 1;
+        }
+    }
+    n += 1;
+    d.group_len[n] = 0;
+
+    // The groups are multiplied in the order the table gives, the leading
+    // group at `order[0]` and the other pawns at `order[1]`.
+    let both_pawns = material.has_pawns && material.pawn_count[1] > 0;
+    let mut next = if both_pawns { 2 } else { 1 };
+    let mut free_squares = 64 - d.group_len[0] - if both_pawns { d.group_len[1] } else { 0 };
+    let mut idx: u64 = 1;
+    let mut k = 0;
+    while next < n || k == order[0] || k == order[1] {
+        if k == order[0] {
+            d.group_idx[0] = idx;
+            let size = if material.has_pawns {
+                *ix.lead_pawns_size.get(d.group_len[0])?.get(file)?
+            } else if material.has_unique_pieces {
+                UNIQUE_PIECES_SIZE
+            } else {
+                KINGS_SIZE
+            };
+            idx = idx.checked_mul(size)?;
+        } else if k == order[1] {
+            d.group_idx[1] = idx;
+            idx = idx.checked_mul(*ix.binomial.get(d.group_len[1])?.get(48 - d.group_len[0])?)?;
+        } else {
+            d.group_idx[next] = idx;
+            idx = idx.checked_mul(*ix.binomial.get(d.group_len[next])?.get(free_squares)?)?;
+            free_squares = free_squares.checked_sub(d.group_len[next])?;
+            next += 1;
+        }
+        k += 1;
+    }
+    d.group_idx[n] = idx;
+    Some(())
+}
@@ -0,0 +1,130 @@
+use std::mem;
+use crate::engine::search::MATE;
+use crate::game::Move;
+
+pub const DEFAULT_HASH_MB: usize = 16;
+/// Scores beyond this are mate scores, see `score_to_tt`.
+const MATE_BOUND: i32 = MATE - 1_000;
+
+/// How the stored score relates to the real score of the position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Bound {
+    /// The search ran out of moves to try, so the score is exact.
+    #[default]
+    Exact,
+    /// The search failed high, the real score is at least the stored score.
+    Lower,
+    /// No move reached alpha, the real score is at most the stored score.
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TtEntry {
+    pub key: u64,
+    pub best_move: Option<Move>,
+    /// Mate scores are stored relative to this position, see `score_to_tt`.
+    pub score: i32,
+    pub depth: i32,
+    pub bound: Bound,
+    generation: u8,
+}
+
+/// A fixed-size hash table of search results, indexed by Zobrist key. A new
+/// entry replaces the one in its slot unless that one is from the current
+/// search and searched deeper.
+pub struct TranspositionTable {
+    entries: Vec<TtEntry>,
+    generation: u8,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_MB)
+    }
+}
+
+impl TranspositionTable {
+    /// A table using at most `megabytes` of memory, at least one entry.
+    pub fn new(megabytes: usize) -> Self {
+        let fits = (megabytes * 1024 * 1024 / mem::size_of::<TtEntry>()).max(1);
+        // A power of two lets the key be masked instead of divided.
+        let len = 1 << fits.ilog2();
+        Self { entries: vec![TtEntry::default(); len], generation: 1 }
+    }
+
+    pub fn resize(&mut self, megabytes: usize) {
+        *self = Self::new(megabytes);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(TtEntry::default());
+        self.generation = 1;
+    }
+
+    /// Called before every search, entries of older searches are replaced first.
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1).max(1);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key as usize) & (self.entries.len() - 1)
+    }
+
+    pub fn probe(&self, key: u64) -> Option<TtEntry> {
+        let entry = self.entries[self.index(key)];
+        (entry.generation != 0 && entry.key == key).then_some(entry)
+    }
+
+    /// Stores a search result, `score` as the search sees it `ply` plies from the root.
+    pub fn store(&mut self, key: u64, depth: i32, score: i32, bound: Bound, best_move: Option<Move>, ply: usize) {
+        let generation = self.generation;
+        let index = self.index(key);
+        let slot = &mut self.entries[index];
+        let replace = slot.generation != generation || slot.key == key || depth >= slot.depth;
+        if !replace {
+            return;
+        }
+        // Keep the move of an earlier search of this position when there is no new one.
+        let best_move = best_move.or(if slot.key == key { slot.best_move } else { None });
+        *slot = TtEntry { key, best_move, score: score_to_tt(score, ply), depth, bound, generation };
+    }
+
+    /// Used entries of the current search per thousand, from a sample of the table.
+    pub fn hashfull(&self) -> u32 {
+        let sample = &self.entries[..self.entries.len().min(1000)];
+        let used = sample.iter().filter(|e| e.generation == self.generation).count();
+        (used * 1000 / sample.len()) as u32
+    }
+}
+
+/// A mate score counts the plies from the root. In the table it is stored as
+/// the plies from the position itself, so it stays correct when the position
+/// is reached at another ply.
+pub fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score > MATE_BOUND {
+        score + ply as i32
+    } else if score < -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+/// Reverses `score_to_tt` for a position `ply` plies from the root.
+pub fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score > MATE_BOUND {
+        score - ply as i32
+    } else if score < -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
@@ -1,7 +1,14 @@
 pub mod enums;
 pub mod model;
 pub mod errors;
+pub mod square;
+pub mod bitboard;
+pub mod zobrist;
+pub mod random;
+pub mod tablebase;
 
 pub use model::*;
 
-pub use enums::*;
\ No newline at end of file
+pub use enums::*;
+
+pub use square::*;
@@ -0,0 +1,271 @@
+use crate::game::{Color, Move, Piece, PieceType, Square};
+
+/// A set of squares, bit `n` is the square with index `n`.
+pub type Bitboard = u64;
+
+pub const FILE_A: Bitboard = 0x0101_0101_0101_0101;
+pub const FILE_H: Bitboard = FILE_A << 7;
+pub const RANK_1: Bitboard = 0xff;
+pub const RANK_8: Bitboard = RANK_1 << 56;
+
+const KNIGHT_OFFSETS: [(i32, i32); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const KING_OFFSETS: [(i32, i32); 8] = [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
+
+// Ray directions, the first four run towards higher square indices.
+const NORTH: usize = 0;
+const NORTH_EAST: usize = 1;
+const EAST: usize = 2;
+const NORTH_WEST: usize = 3;
+const SOUTH: usize = 4;
+const SOUTH_WEST: usize = 5;
+const WEST: usize = 6;
+const SOUTH_EAST: usize = 7;
+const DIRECTIONS: [(i32, i32); 8] = [(0, 1), (1, 1), (1, 0), (-1, 1), (0, -1), (-1, -1), (-1, 0), (1, -1)];
+
+static KNIGHT_ATTACKS: [Bitboard; 64] = offset_table(&KNIGHT_OFFSETS);
+static KING_ATTACKS: [Bitboard; 64] = offset_table(&KING_OFFSETS);
+static RAYS: [[Bitboard; 64]; 8] = ray_table();
+
+const fn offset_table(offsets: &[(i32, i32); 8]) -> [Bitboard; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut i = 0;
+        while i < offsets.len() {
+            let file = (square % 8) as i32 + offsets[i].0;
+            let rank = (square / 8) as i32 + offsets[i].1;
+            if file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                table[square] |= 1 << (rank * 8 + file);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+/// For every direction and square, the squares up to the edge of the board.
+const fn ray_table() -> [[Bitboard; 64]; 8] {
+    let mut table = [[0; 64]; 8];
+    let mut direction = 0;
+    while direction < 8 {
+        let (df, dr) = DIRECTIONS[direction];
+        let mut square = 0;
+        while square < 64 {
+            let mut file = (square % 8) as i32 + df;
+            let mut rank = (square / 8) as i32 + dr;
+            while file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                table[direction][square] |= 1 << (rank * 8 + file);
+                file += df;
+                rank += dr;
+            }
+            square += 1;
+        }
+        direction += 1;
+    }
+    table
+}
+
+pub fn bit(square: Square) -> Bitboard {
+    1 << square.index()
+}
+
+/// The squares in the set, lowest index first.
+pub fn squares(mut bitboard: Bitboard) -> impl Iterator<Item = Square> {
+    std::iter::from_fn(move || {
+        let square = Square::from_index(bitboard.trailing_zeros() as usize)?;
+        bitboard &= bitboard - 1;
+        Some(square)
+    })
+}
+
+pub fn knight_attacks(square: Square) -> Bitboard {
+    KNIGHT_ATTACKS[square.index()]
+}
+
+pub fn king_attacks(square: Square) -> Bitboard {
+    KING_ATTACKS[square.index()]
+}
+
+/// The squares a pawn of `color` on `square` captures on.
+pub fn pawn_attacks(square: Square, color: Color) -> Bitboard {
+    let b = bit(square);
+    match color {
+        Color::White => ((b & !FILE_A) << 7) | ((b & !FILE_H) << 9),
+        Color::Black => ((b & !FILE_H) >> 7) | ((b & !FILE_A) >> 9),
+    }
+}
+
+/// The ray from `square` in `direction`, cut off after the first piece in `occupied`.
+fn ray_attacks(square: Square, occupied: Bitboard, direction: usize) -> Bitboard {
+    let ray = RAYS[direction][square.index()];
+    let blockers = ray & occupied;
+    if blockers == 0 {
+        return ray;
+    }
+    let first = if direction < SOUTH {
+        blockers.trailing_zeros()
+    } else {
+        63 - blockers.leading_zeros()
+    };
+    ray ^ RAYS[direction][first as usize]
+}
+
+pub fn rook_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    [NORTH, EAST, SOUTH, WEST].iter().fold(0, |acc, &d| acc | ray_attacks(square, occupied, d))
+}
+
+pub fn bishop_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    [NORTH_EAST, NORTH_WEST, SOUTH_WEST, SOUTH_EAST].iter().fold(0, |acc, &d| acc | ray_attacks(square, occupied, d))
+}
+
+pub fn queen_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    rook_attacks(square, occupied) | bishop_attacks(square, occupied)
+}
+
+pub fn color_index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+pub fn piece_index(piece_type: PieceType) -> usize {
+    match piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    }
+}
+
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King,
+];
+
+/// The position as one bitboard per colour and piece type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Bitboards {
+    pieces: [[Bitboard; 6]; 2],
+    colors: [Bitboard; 2],
+}
+
+impl Bitboards {
+    pub fn from_board(board: &[Option<Piece>; 64]) -> Self {
+        let mut bitboards = Self::default();
+        for (square, piece) in Square::all().zip(board.iter()) {
+            if let Some(piece) = piece {
+                bitboards.put(square, *piece);
+            }
+        }
+        bitboards
+    }
+
+    pub fn pieces(&self, color: Color, piece_type: PieceType) -> Bitboard {
+        self.pieces[color_index(color)][piece_index(piece_type)]
+    }
+
+    pub fn color(&self, color: Color) -> Bitboard {
+        self.colors[color_index(color)]
+    }
+
+    pub fn occupied(&self) -> Bitboard {
+        self.colors[0] | self.colors[1]
+    }
+
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        let b = bit(square);
+        let color = if self.colors[0] & b != 0 {
+            Color::White
+        } else if self.colors[1] & b != 0 {
+            Color::Black
+        } else {
+            return None;
+        };
+        let piece_type = PIECE_TYPES.into_iter().find(|&t| self.pieces(color, t) & b != 0)?;
+        Some(Piece { color, piece_type })
+    }
+
+    pub fn put(&mut self, square: Square, piece: Piece) {
+        self.remove(square);
+        let b = bit(square);
+        self.pieces[color_index(piece.color)][piece_index(piece.piece_type)] |= b;
+        self.colors[color_index(piece.color)] |= b;
+    }
+
+    pub fn remove(&mut self, square: Square) -> Option<Piece> {
+        let piece = self.piece_at(square)?;
+        let b = bit(square);
+        self.pieces[color_index(piece.color)][piece_index(piece.piece_type)] &= !b;
+        self.colors[color_index(piece.color)] &= !b;
+        Some(piece)
+    }
+
+    /// Plays the move on the bitboards, like `ruleengine::apply_to_board`
+    /// does on the array board.
+    pub fn apply(&mut self, mv: Move, castling: &str) {
+        if let Some((rook_from, rook_to)) = crate::ruleengine::castling_rook_move(castling)
+            && let Some(rook) = self.remove(rook_from) {
+            self.put(rook_to, rook);
+        }
+        let Some(piece) = self.remove(mv.from) else {
+            return;
+        };
+        if piece.piece_type == PieceType::Pawn && mv.from.file() != mv.to.file() && self.piece_at(mv.to).is_none() {
+            self.remove(crate::ruleengine::en_passant_capture_square(mv.from, mv.to));
+        }
+        let piece_type = mv.promotion.unwrap_or(piece.piece_type);
+        self.put(mv.to, Piece { color: piece.color, piece_type });
+    }
+
+    pub fn king_square(&self, color: Color) -> Option<Square> {
+        squares(self.pieces(color, PieceType::King)).next()
+    }
+
+    /// All pieces of the color `by` attacking `square`.
+    pub fn attackers(&self, square: Square, by: Color) -> Bitboard {
+        let occupied = self.occupied();
+        let opponent = match by {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        let rooks = self.pieces(by, PieceType::Rook) | self.pieces(by, PieceType::Queen);
+        let bishops = self.pieces(by, PieceType::Bishop) | self.pieces(by, PieceType::Queen);
+        // A pawn attacks the square if a pawn of the other colour on the square would attack the pawn.
+        (pawn_attacks(square, opponent) & self.pieces(by, PieceType::Pawn))
+            | (knight_attacks(square) & self.pieces(by, PieceType::Knight))
+            | (king_attacks(square) & self.pieces(by, PieceType::King))
+            | (rook_attacks(square, occupied) & rooks)
+            | (bishop_attacks(square, occupied) & bishops)
+    }
+
+    pub fn is_attacked(&self, square: Square, by: Color) -> bool {
+        self.attackers(square, by) != 0
+    }
+
+    pub fn is_in_check(&self, color: Color) -> bool {
+        let opponent = match color {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        self.king_square(color).is_some_and(|king| self.is_attacked(king, opponent))
+    }
+
+    /// The squares the piece on `square` attacks, empty when there is none.
+    pub fn attacks_from(&self, square: Square) -> Bitboard {
+        let Some(piece) = self.piece_at(square) else {
+            return 0;
+        };
+        let occupied = self.occupied();
+        match piece.piece_type {
+            PieceType::Pawn => pawn_attacks(square, piece.color),
+            PieceType::Knight => knight_attacks(square),
+            PieceType::Bishop => bishop_attacks(square, occupied),
+            PieceType::Rook => rook_attacks(square, occupied),
+            PieceType::Queen => queen_attacks(square, occupied),
+            PieceType::King => king_attacks(square),
+        }
+    }
+}
@@ -24,3 +24,56 @@ impl Color {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameStatus {
+    Ongoing,
+    Check,
+    Checkmate,
+    Stalemate,
+    Draw(DrawReason),
+}
+
+impl GameStatus {
+    pub fn is_over(&self) -> bool {
+        matches!(self, GameStatus::Checkmate | GameStatus::Stalemate | GameStatus::Draw(_))
+    }
+
+    pub fn display(&self) -> String {
+        match self {
+            GameStatus::Ongoing => "ONGOING".into(),
+            GameStatus::Check => "CHECK".into(),
+            GameStatus::Checkmate => "CHECKMATE".into(),
+            GameStatus::Stalemate => "STALEMATE".into(),
+            GameStatus::Draw(reason) => format!("DRAW ({})", reason.display()),
+        }
+    }
+}
+
+/// Why a game is drawn. The fifty-move rule and threefold repetition can be
+/// claimed by a player, the seventy-five-move rule and fivefold repetition end
+/// the game automatically.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DrawReason {
+    FiftyMoveRule,
+    SeventyFiveMoveRule,
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    InsufficientMaterial,
+}
+
+impl DrawReason {
+    pub fn is_claimable(&self) -> bool {
+        matches!(self, DrawReason::FiftyMoveRule | DrawReason::ThreefoldRepetition)
+    }
+
+    pub fn display(&self) -> String {
+        match self {
+            DrawReason::FiftyMoveRule => "fifty-move rule".into(),
+            DrawReason::SeventyFiveMoveRule => "seventy-five-move rule".into(),
+            DrawReason::ThreefoldRepetition => "threefold repetition".into(),
+            DrawReason::FivefoldRepetition => "fivefold repetition".into(),
+            DrawReason::InsufficientMaterial => "insufficient material".into(),
+        }
+    }
+}
@@ -24,6 +24,50 @@ pub enum GameErr {
     IllegalBishopMove,
     #[error("Illegal rook move.")]
     IllegalRookMove,
+    #[error("Move would leave your king in check.")]
+    KingInCheck,
+    #[error("Castling is not allowed, the king or rook has moved.")]
+    CastlingNotAllowed,
+    #[error("Castling is blocked, the squares between king and rook must be empty.")]
+    CastlingPathBlocked,
+    #[error("Cannot castle out of check.")]
+    CastlingOutOfCheck,
+    #[error("Cannot castle through an attacked square.")]
+    CastlingThroughCheck,
+    #[error("Cannot castle into check.")]
+    CastlingIntoCheck,
+    #[error("Pawn must be promoted, choose a piece.")]
+    PromotionRequired,
+    #[error("Invalid promotion.")]
+    InvalidPromotion,
+    #[error("No move to undo.")]
+    NothingToUndo,
+    #[error("No move to redo.")]
+    NothingToRedo,
+    #[error("Invalid SAN move: {0}")]
+    InvalidSan(String),
+    #[error("No legal move matches {0}")]
+    IllegalSan(String),
+    #[error("More than one legal move matches {0}")]
+    AmbiguousSan(String),
+    #[error("Invalid FEN: {0}")]
+    InvalidFen(String),
+    #[error("Invalid square: {0}")]
+    InvalidSquare(String),
+    #[error("Invalid UCI move: {0}")]
+    InvalidUciMove(String),
+    #[error("Invalid PGN: {0}")]
+    InvalidPgn(String),
+    #[error("Illegal move {san} in game {game} at ply {ply}")]
+    IllegalPgnMove { game: usize, ply: usize, san: String },
+    #[error("Invalid opening book: {0}")]
+    InvalidBook(String),
+    #[error("Invalid tablebase: {0}")]
+    InvalidTablebase(String),
+    #[error("Engine error: {0}")]
+    Engine(String),
+    #[error("The player quit the game.")]
+    PlayerQuit,
 
 }
 
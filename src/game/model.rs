@@ -1,8 +1,11 @@
-use std::cmp::PartialEq;
 use std::fmt;
 use std::fmt::Formatter;
+use std::str::FromStr;
 use crate::game::errors::{GameErr, GameResult};
-use crate::game::{Color, PieceType};
+use crate::game::bitboard::Bitboards;
+use crate::game::{Color, DrawReason, GameStatus, IntoSquare, PieceType, Square};
+use crate::game::tablebase::{EndgameTables, TablebaseProbe};
+use crate::game::zobrist;
 use crate::ruleengine;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -30,6 +33,20 @@ impl Piece {
         }
     }
 
+    pub fn from_char_code(c: char) -> Option<Piece> {
+        let piece_type = match c.to_ascii_uppercase() {
+            'P' => PieceType::Pawn,
+            'N' => PieceType::Knight,
+            'B' => PieceType::Bishop,
+            'R' => PieceType::Rook,
+            'Q' => PieceType::Queen,
+            'K' => PieceType::King,
+            _ => return None,
+        };
+        let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+        Some(Piece { color, piece_type })
+    }
+
     pub fn get_points(&self) -> i32 {
         match self.piece_type {
             PieceType::Pawn => 1,
@@ -41,9 +58,79 @@ impl Piece {
         }
     }
 }
-#[derive(Debug)]
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<PieceType>,
+}
+
+impl Move {
+    pub fn new(from: Square, to: Square) -> Self {
+        Self { from, to, promotion: None }
+    }
+
+    pub fn with_promotion(from: Square, to: Square, promotion: PieceType) -> Self {
+        Self { from, to, promotion: Some(promotion) }
+    }
+}
+
+/// Writes the move in UCI notation, like "e2e4" or "e7e8q".
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.from, self.to)?;
+        if let Some(piece_type) = self.promotion {
+            let piece = Piece { color: Color::Black, piece_type };
+            write!(f, "{}", piece.get_char_code())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Move {
+    type Err = GameErr;
+
+    /// Parses a move in UCI notation, like "e2e4" or "e7e8q".
+    fn from_str(s: &str) -> GameResult<Move> {
+        let invalid = || GameErr::InvalidUciMove(s.to_string());
+        let from: Square = s.get(0..2).ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+        let to: Square = s.get(2..4).ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+        match s[4..].chars().collect::<Vec<char>>()[..] {
+            [] => Ok(Move::new(from, to)),
+            [c @ ('q' | 'r' | 'b' | 'n')] => {
+                let piece = Piece::from_char_code(c).ok_or_else(invalid)?;
+                Ok(Move::with_promotion(from, to, piece.piece_type))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// A move played in the game, with everything needed to take it back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveRecord {
+    pub mv: Move,
+    pub piece: Piece,
+    pub captured: Option<Piece>,
+    pub is_en_passant: bool,
+    /// The castling right used by the move, "-" when not castling.
+    pub castling: &'static str,
+    pub points: i32,
+    // State before the move
+    pub white_can_castle: String,
+    pub black_can_castle: String,
+    pub enpassang_target: Option<Square>,
+    pub half_time_moves: i32,
+    pub fen: String,
+    pub zobrist_key: u64,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone)]
 pub struct Game {
-    pub board: [Option<Piece>; 64],
+    /// Changed only together with `bitboards`, see `put_piece`.
+    board: [Option<Piece>; 64],
     pub fen: String,
     pub current_player: Color,
     pub score_white: i32,
@@ -51,8 +138,16 @@ pub struct Game {
     pub white_can_castle: String,
     pub black_can_castle: String,
     pub half_time_moves: i32,
-    enpassang_target: Option<String>,
-    moves: u8
+    enpassang_target: Option<Square>,
+    moves: u32,
+    /// The key of every position reached in the game, used to detect repetitions.
+    position_history: Vec<u64>,
+    history: Vec<MoveRecord>,
+    undone: Vec<Move>,
+    start_fen: String,
+    /// The same position as `board`, updated together with it.
+    bitboards: Bitboards,
+    zobrist_key: u64,
 }
 impl Game {
     pub fn new() -> Self {
@@ -79,7 +174,7 @@ impl Game {
                 _ => None,
             }
         });
-        Self {
+        let mut game = Self {
             board,
             fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".into(),
             current_player: Color::White,
@@ -90,58 +185,291 @@ impl Game {
             half_time_moves: 0,
             moves: 1,
             enpassang_target: None,
-        }
+            position_history: Vec::new(),
+            history: Vec::new(),
+            undone: Vec::new(),
+            start_fen: String::new(),
+            bitboards: Bitboards::from_board(&board),
+            zobrist_key: 0,
+        };
+        game.zobrist_key = zobrist::hash(&game);
+        game.position_history.push(game.zobrist_key);
+        game.start_fen = game.fen.clone();
+        game
     }
 
+    /// Creates a game from a FEN string. The halfmove clock and fullmove number
+    /// may be left out, they then default to 0 and 1.
+    pub fn from_fen(fen: &str) -> GameResult<Self> {
+        let invalid = |msg: String| GameErr::InvalidFen(msg);
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 || fields.len() > 6 {
+            return Err(invalid(format!("expected 4 to 6 fields, found {}", fields.len())));
+        }
 
-    pub fn move_piece(&mut self, from: (char, i32), to: (char, i32)) -> GameResult<()> {
-        let piece = match ruleengine::get_piece_at_pos(&self.board, from) {
-            Some(p) => p.clone(),                // requires Piece: Clone
-            None => return Err(GameErr::NoPieceAtPosition),
+        // Piece placement, from rank 8 down to rank 1
+        let mut board: [Option<Piece>; 64] = [None; 64];
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(invalid(format!("expected 8 ranks, found {}", ranks.len())));
+        }
+        for (i, rank_str) in ranks.iter().enumerate() {
+            let rank = 7 - i;
+            let mut file = 0;
+            for c in rank_str.chars() {
+                if let Some(empty) = c.to_digit(10).filter(|d| (1..=8).contains(d)) {
+                    file += empty as usize;
+                } else if let Some(piece) = Piece::from_char_code(c) {
+                    if file < 8 {
+                        board[rank * 8 + file] = Some(piece);
+                    }
+                    file += 1;
+                } else {
+                    return Err(invalid(format!("invalid character '{}' in rank {}", c, rank + 1)));
+                }
+            }
+            if file != 8 {
+                return Err(invalid(format!("rank {} has {} squares", rank + 1, file)));
+            }
+        }
+        for color in [Color::White, Color::Black] {
+            let kings = board.iter()
+                .filter(|p| p.is_some_and(|p| p.piece_type == PieceType::King && p.color == color))
+                .count();
+            if kings != 1 {
+                return Err(invalid(format!("{} has {} kings", color.display(), kings)));
+            }
+        }
+        let back_ranks = board[0..8].iter().chain(board[56..64].iter());
+        if back_ranks.flatten().any(|p| p.piece_type == PieceType::Pawn) {
+            return Err(invalid("pawn on the first or last rank".into()));
+        }
+
+        let current_player = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            other => return Err(invalid(format!("invalid side to move '{}'", other))),
         };
 
-        let points = match ruleengine::is_castling_move(self, from, to, self.current_player) {
-            "K" => {
-                // Move rook
-                let rook = ruleengine::get_piece_at_pos(&self.board, ('h',1)).expect("No rook at position.");
-                let rook_index = ruleengine::get_index_based_on_pos(('h',1));
-                self.board[rook_index - 2] = Some(rook.clone());
-                self.board[rook_index] = None;
-                Ok(1)
-            },
-            "Q" => {
-                let rook = ruleengine::get_piece_at_pos(&self.board, ('a',1)).expect("No rook at position.");
-                let rook_index = ruleengine::get_index_based_on_pos(('a',1));
-                self.board[rook_index - 2] = Some(rook.clone());
-                self.board[rook_index] = None;
-                Ok(1)
-            },
-            "k" => {
-                let rook = ruleengine::get_piece_at_pos(&self.board, ('h',8)).expect("No rook at position.");
-                let rook_index = ruleengine::get_index_based_on_pos(('h',8));
-                self.board[rook_index - 2] = Some(rook.clone());
-                self.board[rook_index] = None;
-                Ok(1)
-            },
-            "q" => {
-                let rook = ruleengine::get_piece_at_pos(&self.board, ('a',8)).expect("No rook at position.");
-                let rook_index = ruleengine::get_index_based_on_pos(('a',8));
-                self.board[rook_index - 2] = Some(rook.clone());
-                self.board[rook_index] = None;
-                Ok(1)
-            },
-            _ => {
-                Ok(ruleengine::is_allowed_move(&self, from, to, self.current_player))?
+        let mut white_can_castle = String::new();
+        let mut black_can_castle = String::new();
+        if fields[2] != "-" {
+            for c in fields[2].chars() {
+                let rights = match c {
+                    'K' | 'Q' => &mut white_can_castle,
+                    'k' | 'q' => &mut black_can_castle,
+                    _ => return Err(invalid(format!("invalid castling rights '{}'", fields[2]))),
+                };
+                if rights.contains(c) {
+                    return Err(invalid(format!("invalid castling rights '{}'", fields[2])));
+                }
+                rights.push(c);
             }
-        }.expect("Illegal castling move");
+        }
+        // Keep the same order as a fresh game, kingside first.
+        let order = |rights: String, king: char| -> String {
+            let mut rights: Vec<char> = rights.chars().collect();
+            rights.sort_by_key(|&c| c != king);
+            rights.into_iter().collect()
+        };
+        let white_can_castle = order(white_can_castle, 'K');
+        let black_can_castle = order(black_can_castle, 'k');
+
+        let enpassang_target = match fields[3] {
+            "-" => None,
+            target => {
+                let expected_rank = if current_player == Color::White { 6 } else { 3 };
+                match target.parse::<Square>() {
+                    Ok(square) if square.rank() == expected_rank => Some(square),
+                    _ => return Err(invalid(format!("invalid en passant target '{}'", target))),
+                }
+            }
+        };
+
+        let half_time_moves = match fields.get(4) {
+            Some(field) => field.parse::<i32>().ok().filter(|n| *n >= 0)
+                .ok_or_else(|| invalid(format!("invalid halfmove clock '{}'", field)))?,
+            None => 0,
+        };
+        let moves = match fields.get(5) {
+            Some(field) => field.parse::<u32>().ok().filter(|n| *n >= 1)
+                .ok_or_else(|| invalid(format!("invalid fullmove number '{}'", field)))?,
+            None => 1,
+        };
+
+        let mut game = Self {
+            board,
+            fen: String::new(),
+            current_player,
+            score_white: 0,
+            score_black: 0,
+            white_can_castle,
+            black_can_castle,
+            half_time_moves,
+            moves,
+            enpassang_target,
+            position_history: Vec::new(),
+            history: Vec::new(),
+            undone: Vec::new(),
+            start_fen: String::new(),
+            bitboards: Bitboards::from_board(&board),
+            zobrist_key: 0,
+        };
+        game.fen = get_fen(&game);
+        game.zobrist_key = zobrist::hash(&game);
+        game.position_history.push(game.zobrist_key);
+        game.start_fen = game.fen.clone();
+        Ok(game)
+    }
+
+    /// Moves the piece at `from` to `to`, squares can be given as `Square`,
+    /// `('e', 2)` or "e2".
+    pub fn move_piece(&mut self, from: impl IntoSquare, to: impl IntoSquare) -> GameResult<()> {
+        self.make_move(Move::new(from.into_square()?, to.into_square()?))
+    }
 
-        // Clear square
-        let from_idx = ruleengine::get_index_based_on_pos(from);
-        self.board[from_idx] = None;
+    /// Plays a move, use this when the move is a promotion.
+    pub fn make_move(&mut self, mv: Move) -> GameResult<()> {
+        self.play(mv)?;
+        // A new move starts a new line, the undone moves can not be redone.
+        self.undone.clear();
+        Ok(())
+    }
 
-        // Move piece to new square
-        let to_idx = ruleengine::get_index_based_on_pos(to);
-        self.board[to_idx] = Some(piece);
+    /// Plays a move taken from `legal_moves` without validating it again.
+    /// Only for the search on its own copy of the game: the FEN is left
+    /// empty until the move is undone, so the game is not handed out before.
+    pub(crate) fn make_move_unchecked(&mut self, mv: Move) {
+        let points = ruleengine::move_points(self, mv);
+        self.apply_move(mv, points);
+        self.undone.clear();
+    }
+
+    /// The moves played so far, oldest first.
+    pub fn history(&self) -> &[MoveRecord] {
+        &self.history
+    }
+
+    /// The FEN of the position the game started from.
+    pub fn start_fen(&self) -> &str {
+        &self.start_fen
+    }
+
+    /// Adds a comment to the last move played.
+    pub fn set_comment(&mut self, comment: &str) -> GameResult<()> {
+        let record = self.history.last_mut().ok_or(GameErr::NoMoveRegistered)?;
+        record.comment = Some(comment.to_string());
+        Ok(())
+    }
+
+    /// Takes back the last move and returns it.
+    pub fn undo(&mut self) -> GameResult<Move> {
+        let record = self.history.pop().ok_or(GameErr::NothingToUndo)?;
+        let (from, to) = (record.mv.from, record.mv.to);
+
+        // Put the piece back, as a pawn if it was promoted.
+        self.board[from.index()] = Some(record.piece);
+        self.board[to.index()] = None;
+        self.bitboards.remove(to);
+        self.bitboards.put(from, record.piece);
+        let captured_at = if record.is_en_passant { ruleengine::en_passant_capture_square(from, to) } else { to };
+        self.board[captured_at.index()] = record.captured;
+        if let Some(captured) = record.captured {
+            self.bitboards.put(captured_at, captured);
+        }
+        if let Some((rook_from, rook_to)) = ruleengine::castling_rook_move(record.castling) {
+            self.board[rook_from.index()] = self.board[rook_to.index()];
+            self.board[rook_to.index()] = None;
+            if let Some(rook) = self.bitboards.remove(rook_to) {
+                self.bitboards.put(rook_from, rook);
+            }
+        }
+
+        self.current_player = record.piece.color;
+        match self.current_player {
+            Color::White => self.score_white -= record.points,
+            Color::Black => {
+                self.score_black -= record.points;
+                self.moves -= 1;
+            }
+        }
+        self.white_can_castle = record.white_can_castle;
+        self.black_can_castle = record.black_can_castle;
+        self.enpassang_target = record.enpassang_target;
+        self.half_time_moves = record.half_time_moves;
+        self.fen = record.fen;
+        self.zobrist_key = record.zobrist_key;
+        self.position_history.pop();
+
+        self.undone.push(record.mv);
+        Ok(record.mv)
+    }
+
+    /// Plays the last undone move again and returns it.
+    pub fn redo(&mut self) -> GameResult<Move> {
+        let mv = self.undone.pop().ok_or(GameErr::NothingToRedo)?;
+        self.play(mv)?;
+        Ok(mv)
+    }
+
+    fn play(&mut self, mv: Move) -> GameResult<()> {
+        // Validate the move, this also rejects moves leaving the own king in check.
+        let points = ruleengine::check_move(self, mv)?;
+        self.apply_move(mv, points);
+        self.fen = get_fen(self);
+        Ok(())
+    }
+
+    /// Plays a legal move on the board and updates the game state, except
+    /// for the FEN.
+    fn apply_move(&mut self, mv: Move, points: i32) {
+        let (from, to) = (mv.from, mv.to);
+        let Some(piece) = self.board[from.index()] else {
+            return;
+        };
+
+        // Remember what is needed to undo the move
+        let castling = ruleengine::is_castling_move(self, from, to, self.current_player);
+        let is_en_passant = piece.piece_type == PieceType::Pawn
+            && from.file() != to.file()
+            && ruleengine::get_piece_at_pos(&self.board, to).is_none();
+        let captured_at = if is_en_passant { ruleengine::en_passant_capture_square(from, to) } else { to };
+        let captured = ruleengine::get_piece_at_pos(&self.board, captured_at).copied();
+        self.history.push(MoveRecord {
+            mv,
+            piece,
+            captured,
+            is_en_passant,
+            castling,
+            points,
+            white_can_castle: self.white_can_castle.clone(),
+            black_can_castle: self.black_can_castle.clone(),
+            enpassang_target: self.enpassang_target,
+            half_time_moves: self.half_time_moves,
+            fen: std::mem::take(&mut self.fen),
+            zobrist_key: self.zobrist_key,
+            comment: None,
+        });
+
+        // Take the old castling rights and en passant file out of the key, and
+        // update it for the pieces that move.
+        let mut key = self.zobrist_key
+            ^ zobrist::castling_rights_key(&self.white_can_castle)
+            ^ zobrist::castling_rights_key(&self.black_can_castle)
+            ^ self.en_passant_zobrist_key();
+        key ^= zobrist::piece_key(piece, from);
+        if let Some(captured) = captured {
+            key ^= zobrist::piece_key(captured, captured_at);
+        }
+        key ^= zobrist::piece_key(Piece { color: piece.color, piece_type: mv.promotion.unwrap_or(piece.piece_type) }, to);
+        if let Some((rook_from, rook_to)) = ruleengine::castling_rook_move(castling) {
+            let rook = Piece { color: piece.color, piece_type: PieceType::Rook };
+            key ^= zobrist::piece_key(rook, rook_from) ^ zobrist::piece_key(rook, rook_to);
+        }
+
+        // Move the piece, and the rook if the move is castling
+        ruleengine::apply_to_board(&mut self.board, mv, castling);
+        self.bitboards.apply(mv, castling);
 
         // Can player still castle? Has King or Rook moved?
         if piece.piece_type == PieceType::King {
@@ -151,19 +479,14 @@ impl Game {
                 self.white_can_castle = "".to_string()
             }
         }
-        if piece.piece_type == PieceType::Rook {
-            if from.0 < 'e' {
-                if self.current_player == Color::White {
-                    self.white_can_castle = self.white_can_castle.replace("Q", "");
-                } else {
-                    self.black_can_castle = self.black_can_castle.replace("q", "");
-                }
-            } else {
-                if self.current_player == Color::White {
-                    self.white_can_castle = self.white_can_castle.replace("K", "");
-                } else {
-                    self.black_can_castle = self.black_can_castle.replace("k", "");
-                }
+        // A rook leaving or being captured on its corner ends castling on that side.
+        for corner in [from, to] {
+            match corner {
+                Square::A1 => self.white_can_castle = self.white_can_castle.replace('Q', ""),
+                Square::H1 => self.white_can_castle = self.white_can_castle.replace('K', ""),
+                Square::A8 => self.black_can_castle = self.black_can_castle.replace('q', ""),
+                Square::H8 => self.black_can_castle = self.black_can_castle.replace('k', ""),
+                _ => {}
             }
         }
 
@@ -175,15 +498,13 @@ impl Game {
         }
         // En passang move
         self.enpassang_target = None;
-        if piece.piece_type == PieceType::Pawn && (to.1 - from.1).abs() == 2 {
-            if self.current_player == Color::White {
-                self.enpassang_target = Some(format!("{}{}", from.0,  to.1 - 1))
-            } else {
-                self.enpassang_target = Some(format!("{}{}", from.0,  to.1 + 1))
-            }
+        if piece.piece_type == PieceType::Pawn && (to.rank() - from.rank()).abs() == 2 {
+            self.enpassang_target = Square::from_index((from.index() + to.index()) / 2);
+        }
+        // Increase move counter after Black has moved
+        if self.current_player == Color::Black {
+            self.moves += 1;
         }
-        // Increase move counter
-        self.moves += 1;
 
         // Set next player
         self.current_player = match self.current_player {
@@ -197,11 +518,125 @@ impl Game {
             },
         };
 
-        // Update FEN
-        self.fen = get_fen(self);
-        // Return Ok
+        // Update the key
+        self.zobrist_key = key
+            ^ zobrist::castling_rights_key(&self.white_can_castle)
+            ^ zobrist::castling_rights_key(&self.black_can_castle)
+            ^ self.en_passant_zobrist_key()
+            ^ zobrist::side_key();
+        self.position_history.push(self.zobrist_key);
+    }
+
+    /// The pieces by square index, a1 = 0 to h8 = 63.
+    pub fn board(&self) -> &[Option<Piece>; 64] {
+        &self.board
+    }
+
+    /// Puts a piece on a square, replacing the piece there, to set up a
+    /// position. The bitboards, the key and the FEN follow.
+    pub fn put_piece(&mut self, square: impl IntoSquare, piece: Piece) -> GameResult<()> {
+        let square = square.into_square()?;
+        self.board[square.index()] = Some(piece);
+        self.bitboards.put(square, piece);
+        self.position_edited();
         Ok(())
     }
+
+    /// Takes the piece off a square and returns it.
+    pub fn remove_piece(&mut self, square: impl IntoSquare) -> GameResult<Option<Piece>> {
+        let square = square.into_square()?;
+        let piece = self.board[square.index()].take();
+        self.bitboards.remove(square);
+        self.position_edited();
+        Ok(piece)
+    }
+
+    /// The edited position replaces the current one in the repetition history.
+    fn position_edited(&mut self) {
+        self.zobrist_key = zobrist::hash(self);
+        if let Some(key) = self.position_history.last_mut() {
+            *key = self.zobrist_key;
+        }
+        self.fen = get_fen(self);
+    }
+
+    /// The square passed by the last double pawn step.
+    pub fn en_passant_target(&self) -> Option<Square> {
+        self.enpassang_target
+    }
+
+    /// The position as bitboards, for fast move generation and attack queries.
+    pub fn bitboards(&self) -> &Bitboards {
+        &self.bitboards
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        ruleengine::legal_moves(self)
+    }
+
+    /// Status of the game for the player to move.
+    pub fn status(&self) -> GameStatus {
+        let in_check = self.bitboards.is_in_check(self.current_player);
+        let status = match (in_check, ruleengine::has_legal_move(self)) {
+            (true, false) => return GameStatus::Checkmate,
+            (false, false) => return GameStatus::Stalemate,
+            (true, true) => GameStatus::Check,
+            (false, true) => GameStatus::Ongoing,
+        };
+
+        let repetitions = self.repetition_count();
+        if repetitions >= 5 {
+            GameStatus::Draw(DrawReason::FivefoldRepetition)
+        } else if self.half_time_moves >= 150 {
+            GameStatus::Draw(DrawReason::SeventyFiveMoveRule)
+        } else if ruleengine::is_insufficient_material(&self.board) {
+            GameStatus::Draw(DrawReason::InsufficientMaterial)
+        } else if repetitions >= 3 {
+            GameStatus::Draw(DrawReason::ThreefoldRepetition)
+        } else if self.half_time_moves >= 100 {
+            GameStatus::Draw(DrawReason::FiftyMoveRule)
+        } else {
+            status
+        }
+    }
+
+    /// The tablebase result of the position, `None` when it is not in the
+    /// tables, for example with castling rights or too many pieces.
+    pub fn probe_tablebase(&self, tablebase: &dyn EndgameTables) -> Option<TablebaseProbe> {
+        tablebase.probe(self)
+    }
+
+    /// How many times the current position has occurred in the game.
+    pub fn repetition_count(&self) -> usize {
+        self.position_history.iter().filter(|key| **key == self.zobrist_key).count()
+    }
+
+    /// The Zobrist key of the position: placement, side to move, castling
+    /// rights and the en passant file, the latter only if the capture is possible.
+    pub fn zobrist_key(&self) -> u64 {
+        self.zobrist_key
+    }
+
+    fn en_passant_zobrist_key(&self) -> u64 {
+        match self.enpassang_target {
+            Some(target) if self.can_capture_en_passant(target) => zobrist::en_passant_key(target.file()),
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn can_capture_en_passant(&self, target: Square) -> bool {
+        let forward = if self.current_player == Color::White { 1 } else { -1 };
+        [-1, 1].iter().filter_map(|&offset| target.offset(offset, -forward)).any(|from| {
+            ruleengine::get_piece_at_pos(&self.board, from).is_some_and(|p| p.piece_type == PieceType::Pawn)
+                && ruleengine::check_move(self, Move::new(from, target)).is_ok()
+        })
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl fmt::Display for Game {
@@ -255,36 +690,34 @@ fn get_fen(game: &Game) -> String{
     for rank in (0..8).rev() { // 8→1 top-down
         for file in 0..8 {
             let index = rank * 8 + file;
-            let empty_squares = 1;
             match game.board[index] {
                 Some(piece) => fen.push(piece.get_char_code()),
                 None => {
-                    fen.push_str(("1"))
+                    fen.push('1')
                 }
             }
         }
-        fen.push_str("/");
+        fen.push('/');
     }
     fen.remove(fen.len() - 1);
     fen = compress_ones(fen);
     let mut color = 'w';
-    if (game.current_player == Color::Black) {
+    if game.current_player == Color::Black {
         color = 'b';
     }
     let mut castle_rights = String::from("");
     castle_rights.push_str(game.white_can_castle.as_str());
     castle_rights.push_str(game.black_can_castle.as_str());
 
-    if (castle_rights.len() == 0) {
+    if castle_rights.is_empty() {
         castle_rights = "-".to_string();
     }
     fen.push(' ');
-    fen.push(color.into());
+    fen.push(color);
     fen.push(' ');
     fen.push_str(castle_rights.as_str());
     fen.push(' ');
-    //fen.push_str(game.enpassang_target.as_deref().unwrap_or("-"));
-    fen.push_str("-");
+    fen.push_str(&game.enpassang_target.map_or("-".to_string(), |s| s.to_string()));
     fen.push(' ');
     fen.push_str(game.half_time_moves.to_string().as_str());
     fen.push(' ');
@@ -0,0 +1,171 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+use crate::game::errors::{GameErr, GameResult};
+
+/// A square of the board. The index runs from a1 = 0 to h8 = 63, rank by rank,
+/// the same as the index into `Game::board`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Square(u8);
+
+impl Square {
+    pub const A1: Square = Square(0);
+    pub const B1: Square = Square(1);
+    pub const C1: Square = Square(2);
+    pub const D1: Square = Square(3);
+    pub const E1: Square = Square(4);
+    pub const F1: Square = Square(5);
+    pub const G1: Square = Square(6);
+    pub const H1: Square = Square(7);
+    pub const A2: Square = Square(8);
+    pub const B2: Square = Square(9);
+    pub const C2: Square = Square(10);
+    pub const D2: Square = Square(11);
+    pub const E2: Square = Square(12);
+    pub const F2: Square = Square(13);
+    pub const G2: Square = Square(14);
+    pub const H2: Square = Square(15);
+    pub const A3: Square = Square(16);
+    pub const B3: Square = Square(17);
+    pub const C3: Square = Square(18);
+    pub const D3: Square = Square(19);
+    pub const E3: Square = Square(20);
+    pub const F3: Square = Square(21);
+    pub const G3: Square = Square(22);
+    pub const H3: Square = Square(23);
+    pub const A4: Square = Square(24);
+    pub const B4: Square = Square(25);
+    pub const C4: Square = Square(26);
+    pub const D4: Square = Square(27);
+    pub const E4: Square = Square(28);
+    pub const F4: Square = Square(29);
+    pub const G4: Square = Square(30);
+    pub const H4: Square = Square(31);
+    pub const A5: Square = Square(32);
+    pub const B5: Square = Square(33);
+    pub const C5: Square = Square(34);
+    pub const D5: Square = Square(35);
+    pub const E5: Square = Square(36);
+    pub const F5: Square = Square(37);
+    pub const G5: Square = Square(38);
+    pub const H5: Square = Square(39);
+    pub const A6: Square = Square(40);
+    pub const B6: Square = Square(41);
+    pub const C6: Square = Square(42);
+    pub const D6: Square = Square(43);
+    pub const E6: Square = Square(44);
+    pub const F6: Square = Square(45);
+    pub const G6: Square = Square(46);
+    pub const H6: Square = Square(47);
+    pub const A7: Square = Square(48);
+    pub const B7: Square = Square(49);
+    pub const C7: Square = Square(50);
+    pub const D7: Square = Square(51);
+    pub const E7: Square = Square(52);
+    pub const F7: Square = Square(53);
+    pub const G7: Square = Square(54);
+    pub const H7: Square = Square(55);
+    pub const A8: Square = Square(56);
+    pub const B8: Square = Square(57);
+    pub const C8: Square = Square(58);
+    pub const D8: Square = Square(59);
+    pub const E8: Square = Square(60);
+    pub const F8: Square = Square(61);
+    pub const G8: Square = Square(62);
+    pub const H8: Square = Square(63);
+
+    /// The square on file 'a' to 'h' and rank 1 to 8.
+    pub fn new(file: char, rank: i32) -> GameResult<Square> {
+        if !('a'..='h').contains(&file) || !(1..=8).contains(&rank) {
+            return Err(GameErr::InvalidSquare(format!("{}{}", file, rank)));
+        }
+        Ok(Square((rank as u8 - 1) * 8 + (file as u8 - b'a')))
+    }
+
+    pub fn from_index(index: usize) -> Option<Square> {
+        (index < 64).then_some(Square(index as u8))
+    }
+
+    /// The square at zero based file and rank coordinates, `None` when off the board.
+    pub fn from_coords(file: i32, rank: i32) -> Option<Square> {
+        if (0..8).contains(&file) && (0..8).contains(&rank) {
+            Some(Square((rank * 8 + file) as u8))
+        } else {
+            None
+        }
+    }
+
+    /// All 64 squares, a1 first.
+    pub fn all() -> impl Iterator<Item = Square> {
+        (0..64).map(Square)
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn file(self) -> char {
+        (b'a' + self.0 % 8) as char
+    }
+
+    pub fn rank(self) -> i32 {
+        (self.0 / 8) as i32 + 1
+    }
+
+    /// The square moved by the given number of files and ranks, `None` when
+    /// that leaves the board.
+    pub fn offset(self, files: i32, ranks: i32) -> Option<Square> {
+        Square::from_coords((self.0 % 8) as i32 + files, (self.0 / 8) as i32 + ranks)
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.file(), self.rank())
+    }
+}
+
+impl FromStr for Square {
+    type Err = GameErr;
+
+    /// Parses a square like "e3".
+    fn from_str(s: &str) -> GameResult<Square> {
+        let invalid = || GameErr::InvalidSquare(s.to_string());
+        let mut chars = s.chars();
+        match (chars.next(), chars.next().and_then(|c| c.to_digit(10)), chars.next()) {
+            (Some(file), Some(rank), None) => Square::new(file, rank as i32).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl TryFrom<(char, i32)> for Square {
+    type Error = GameErr;
+
+    fn try_from((file, rank): (char, i32)) -> GameResult<Square> {
+        Square::new(file, rank)
+    }
+}
+
+/// Anything naming a square: a `Square`, a `('e', 4)` tuple or a string like "e4".
+pub trait IntoSquare {
+    fn into_square(self) -> GameResult<Square>;
+}
+
+impl IntoSquare for Square {
+    fn into_square(self) -> GameResult<Square> {
+        Ok(self)
+    }
+}
+
+impl IntoSquare for (char, i32) {
+    fn into_square(self) -> GameResult<Square> {
+        Square::try_from(self)
+    }
+}
+
+impl IntoSquare for &str {
+    fn into_square(self) -> GameResult<Square> {
+        self.parse()
+    }
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+use crate::game::Game;
+
+/// Result of a position for the player to move. Cursed wins and blessed
+/// losses are decided on the board but drawn by the fifty-move rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Wdl {
+    Loss = -2,
+    BlessedLoss = -1,
+    Draw = 0,
+    CursedWin = 1,
+    Win = 2,
+}
+
+impl Wdl {
+    pub(crate) fn from_value(value: i32) -> Self {
+        match value {
+            i32::MIN..=-2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            0 => Wdl::Draw,
+            1 => Wdl::CursedWin,
+            _ => Wdl::Win,
+        }
+    }
+}
+
+impl fmt::Display for Wdl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            Wdl::Loss => "loss",
+            Wdl::BlessedLoss => "blessed loss",
+            Wdl::Draw => "draw",
+            Wdl::CursedWin => "cursed win",
+            Wdl::Win => "win",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// What the tablebases know about a position, see `Game::probe_tablebase`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TablebaseProbe {
+    pub wdl: Wdl,
+    /// Plies until the next capture or pawn move with best play, negative
+    /// when losing and 0 for draws. `None` without the DTZ table.
+    pub dtz: Option<i32>,
+}
+
+/// Anything that knows the result of endgame positions, like the Syzygy
+/// tablebases of the engine.
+pub trait EndgameTables {
+    /// `None` when the position is not in the tables.
+    fn probe(&self, game: &Game) -> Option<TablebaseProbe>;
+}
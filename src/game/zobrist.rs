@@ -0,0 +1,77 @@
+use crate::game::bitboard::{color_index, piece_index};
+use crate::game::random::SplitMix64;
+use crate::game::{Color, Game, Piece, Square};
+
+// Layout of the key table: a key per piece and square, then the castling
+// rights, the en passant files and the side to move.
+const PIECES: usize = 0;
+const CASTLING: usize = 2 * 6 * 64;
+const EN_PASSANT: usize = CASTLING + 4;
+const SIDE: usize = EN_PASSANT + 8;
+
+static KEYS: [u64; SIDE + 1] = keys();
+
+/// Fixed pseudo-random keys from SplitMix64, the same in every build.
+const fn keys() -> [u64; SIDE + 1] {
+    let mut keys = [0; SIDE + 1];
+    let mut random = SplitMix64::new(0x2545_f491_4f6c_dd1d);
+    let mut i = 0;
+    while i < keys.len() {
+        keys[i] = random.next_u64();
+        i += 1;
+    }
+    keys
+}
+
+pub fn piece_key(piece: Piece, square: Square) -> u64 {
+    KEYS[PIECES + (color_index(piece.color) * 6 + piece_index(piece.piece_type)) * 64 + square.index()]
+}
+
+/// The key of one castling right, "K", "Q", "k" or "q".
+pub fn castling_key(right: char) -> u64 {
+    match right {
+        'K' => KEYS[CASTLING],
+        'Q' => KEYS[CASTLING + 1],
+        'k' => KEYS[CASTLING + 2],
+        'q' => KEYS[CASTLING + 3],
+        _ => 0,
+    }
+}
+
+/// The combined key of all castling rights in `rights`, like "KQkq".
+pub fn castling_rights_key(rights: &str) -> u64 {
+    rights.chars().fold(0, |key, right| key ^ castling_key(right))
+}
+
+pub fn en_passant_key(file: char) -> u64 {
+    KEYS[EN_PASSANT + (file as u8 - b'a') as usize]
+}
+
+/// Included when Black is to move.
+pub fn side_key() -> u64 {
+    KEYS[SIDE]
+}
+
+/// Computes the key of the position from scratch. `Game` keeps its key up to
+/// date move by move, see `Game::zobrist_key`.
+///
+/// The en passant file only counts when the capture is possible, so positions
+/// that only differ by an unusable en passant square get the same key.
+pub fn hash(game: &Game) -> u64 {
+    let mut key = 0;
+    for (square, piece) in Square::all().zip(game.board().iter()) {
+        if let Some(piece) = piece {
+            key ^= piece_key(*piece, square);
+        }
+    }
+    key ^= castling_rights_key(&game.white_can_castle);
+    key ^= castling_rights_key(&game.black_can_castle);
+    if let Some(target) = game.en_passant_target()
+        && game.can_capture_en_passant(target) {
+        key ^= en_passant_key(target.file());
+    }
+    if game.current_player == Color::Black {
+        key ^= side_key();
+    }
+    key
+}
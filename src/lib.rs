@@ -0,0 +1,9 @@
+pub mod engine;
+pub mod game;
+pub mod notation;
+pub mod ruleengine;
+pub mod stockfish;
+pub mod uci;
+
+#[cfg(test)]
+mod tests;
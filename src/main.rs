@@ -1,89 +1,297 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
-use crate::game::Game;
+use std::time::{Duration, Instant};
+use chess::engine::book::{BookBuilder, BookChoice, BookPlayer, OpeningBook};
+use chess::engine::eval;
+use chess::engine::player::{self, HumanPlayer, MoveProvider, PlayedMove};
+use chess::engine::search::{SearchLimits, Searcher};
+use chess::engine::syzygy::Tablebase;
+use chess::game::Game;
+use chess::game::errors::{GameErr, GameResult};
+use chess::notation::pgn::{self, PgnTags};
+use chess::notation::san;
+use chess::ruleengine::perft;
+use chess::stockfish::{self, StockfishAPI, StockfishConfig};
+use chess::stockfish::mock::MockChessApi;
+use chess::uci::client::UciEngine;
+use chess::uci::server::UciServer;
 
-
-mod game;
-mod tests;
-mod ruleengine;
-mod stockfish;
-
-#[allow(unused)]
+/// Usage: `chess [play] [--offline] [--white <player>] [--black <player>] [--pgn <file>] [--api-url <url>]`.
+/// A player is `human`, `stockfish`, `builtin` or `uci:<path to engine>`,
+/// `stockfish` uses the chess API at `--api-url` when given.
+/// With `--book <file>` players other than humans play from the Polyglot book
+/// while they can, picking moves by weight or the best one with `--book-best`.
+/// With `--syzygy <dirs>` the built-in engine uses the Syzygy tablebases there.
+/// Without `play` Stockfish plays against itself, or the built-in engine with
+/// `--offline`. With `--pgn` the finished game is saved to the file.
+/// `chess perft <depth> [fen]` counts the move tree below each move.
+/// `chess eval [fen]` prints the evaluation term by term.
+/// `chess uci` runs the built-in engine as a UCI engine on stdin and stdout.
+/// `chess mock-api [port]` serves a local stand-in for the chess API.
+/// `chess book build <pgn> <book> [plies]` builds a Polyglot book from games,
+/// `chess book moves <book> [fen]` lists the book moves of a position.
+/// `chess syzygy <dirs> [fen]` prints the tablebase result of a position and its moves.
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().is_some_and(|a| a == "perft") {
+        run_perft(&args[1..]);
+        return;
+    }
+    if args.first().is_some_and(|a| a == "uci") {
+        UciServer::new(io::stdout()).run(io::stdin().lock());
+        return;
+    }
+    if args.first().is_some_and(|a| a == "mock-api") {
+        run_mock_api(&args[1..]);
+        return;
+    }
+    if args.first().is_some_and(|a| a == "eval") {
+        run_eval(&args[1..]);
+        return;
+    }
+    if args.first().is_some_and(|a| a == "book") {
+        run_book(&args[1..]);
+        return;
+    }
+    if args.first().is_some_and(|a| a == "syzygy") {
+        run_syzygy(&args[1..]);
+        return;
+    }
+    let option = |name: &str| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1));
+    let default_player = if args.iter().any(|a| a == "play") {
+        "human"
+    } else if args.iter().any(|a| a == "--offline") {
+        "builtin"
+    } else {
+        "stockfish"
+    };
+    let white_spec = option("--white").map_or(default_player, |p| p.as_str());
+    let black_spec = option("--black").map_or(default_player, |p| p.as_str());
+    let api_url = option("--api-url").map(|url| url.as_str());
+    let book = match option("--book").map(OpeningBook::open).transpose() {
+        Ok(book) => book,
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+    let tablebase = match option("--syzygy").map(Tablebase::open).transpose() {
+        Ok(tablebase) => tablebase.map(Arc::new),
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+    let choice = if args.iter().any(|a| a == "--book-best") { BookChoice::Best } else { BookChoice::WeightedRandom };
+    let with_book = |spec: &str, player: Box<dyn MoveProvider>| -> Box<dyn MoveProvider> {
+        match &book {
+            Some(book) if spec != "human" => Box::new(BookPlayer::new(book.clone(), player, choice)),
+            _ => player,
+        }
+    };
+    let create = |spec: &str| create_player(spec, api_url, tablebase.as_ref()).map(|player| with_book(spec, player));
+    let players = create(white_spec).and_then(|white| Ok((white, create(black_spec)?)));
+    let (mut white, mut black) = match players {
+        Ok(players) => players,
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+
     println!("Welcome to the chess game!");
     println!("Type 'QUIT' to quit.");
-    let g = Game::new();
-
+    let mut g = Game::new();
     print!("{}", &g);
-    //read_user_input(g);
-    
-    let sf = stockfish::StockfishAPI::new();
-    autoplay(g,sf);
-}
 
-fn autoplay(mut g: Game, sf: stockfish::StockfishAPI) {
-    let break_at = 25;
-    for i in 0..break_at {
-        let result = sf.get(&g.fen).expect("Invalid response from stockfish");
-        let moves = parse_input(vec![&result.from, &result.to]).expect("Invalid input");
-        println!("Stockfish move: from {} to {}",&result.from, &result.to);
-        g.move_piece(moves.0, moves.1).expect("Invalid move");
+    let mut tags = PgnTags { white: white.name(), black: black.name(), ..PgnTags::default() };
+    if white_spec != "human" && black_spec != "human" {
+        tags.event = "Autoplay".into();
+    }
+    // Stockfish against itself is slowed down so the game can be followed.
+    let remote = white_spec == "stockfish" && black_spec == "stockfish";
+    let limits = SearchLimits::movetime(Duration::from_millis(1000));
+    let result = player::play_game(&mut g, white.as_mut(), black.as_mut(), limits, |g: &Game, played: &PlayedMove| {
+        match played.chosen.score {
+            Some(score) => println!("{} move: {} ({})", played.player, played.san, score),
+            None => println!("{} move: {}", played.player, played.san),
+        }
         print!("{}", g);
-        thread::sleep(Duration::from_millis(1000));
+        if remote {
+            thread::sleep(Duration::from_millis(1000));
+        }
+    });
+    match result {
+        Ok(_) => println!("Game over: {}", g.status().display()),
+        Err(GameErr::PlayerQuit) => println!("Goodbye!"),
+        Err(e) => println!("Error: {}", e),
+    }
+
+    if let Some(path) = option("--pgn") {
+        match pgn::write_to_file(path, &g, &tags) {
+            Ok(_) => println!("Game saved to {}", path),
+            Err(e) => println!("Could not save the game: {}", e),
+        }
     }
 }
 
-fn read_user_input(mut g: Game) {
-    let mut input = String::from("");
-    println!(" ");
-    println!("Current move is: {}", &g.current_player.display());
-    println!("Please enter a move in the format 'a1=>a2': ");
-    std::io::stdin().read_line(&mut input).expect("Invalid input");
-    let input_as_vec = input.trim().split("=>").collect::<Vec<&str>>();
+fn create_player(spec: &str, api_url: Option<&str>, tablebase: Option<&Arc<Tablebase>>) -> GameResult<Box<dyn MoveProvider>> {
+    match spec {
+        "human" => Ok(Box::new(HumanPlayer::stdin("Human"))),
+        "stockfish" => {
+            let config = StockfishConfig {
+                base_url: api_url.unwrap_or(stockfish::DEFAULT_BASE_URL).into(),
+                ..StockfishConfig::default()
+            };
+            Ok(Box::new(StockfishAPI::with_config(config)))
+        }
+        "builtin" => {
+            let mut searcher = Searcher::new();
+            searcher.set_tablebase(tablebase.cloned());
+            Ok(Box::new(searcher))
+        }
+        _ => match spec.strip_prefix("uci:") {
+            Some(path) => Ok(Box::new(UciEngine::spawn(path)?)),
+            None => Err(GameErr::Engine(format!("unknown player {}, use human, stockfish, builtin or uci:<path>", spec))),
+        },
+    }
+}
 
-    if (input.trim().to_uppercase() == "QUIT") {
-        println!("Goodbye!");
-        return;
+fn run_mock_api(args: &[String]) {
+    let port = args.first().and_then(|p| p.parse::<u16>().ok()).unwrap_or(8080);
+    match MockChessApi::bind(("127.0.0.1", port), Vec::new()) {
+        Ok(mock) => {
+            println!("Mock chess API listening on {}", mock.url());
+            mock.wait();
+        }
+        Err(e) => println!("Error: {}", e),
     }
-    let moved = match parse_input(input_as_vec) {
-        Ok((from, to)) => {
-            g.move_piece((from.0, from.1 as i32), (to.0, to.1 as i32))
-        },
-        Err (e) => {
-            println!("Error: {}", e);
-            return read_user_input(g)
+}
+
+fn run_eval(args: &[String]) {
+    let g = if args.is_empty() {
+        Game::new()
+    } else {
+        match Game::from_fen(&args.join(" ")) {
+            Ok(g) => g,
+            Err(e) => {
+                println!("Error: {}", e);
+                return;
+            }
         }
     };
-    match moved {
-        Ok(_) => {
-            print!("{}", g);
-            read_user_input(g);
+    print!("{}", &g);
+    println!("{}", eval::evaluate_breakdown(&g));
+    println!("Side to move    {:>6}", eval::evaluate(&g));
+}
+
+fn run_book(args: &[String]) {
+    let usage = "Usage: chess book build <pgn> <book> [plies] | chess book moves <book> [fen]";
+    match args.first().map(|a| a.as_str()) {
+        Some("build") if args.len() >= 3 => {
+            let plies = args.get(3).and_then(|p| p.parse::<usize>().ok());
+            let text = match fs::read_to_string(&args[1]) {
+                Ok(text) => text,
+                Err(e) => {
+                    println!("Error: {}", e);
+                    return;
+                }
+            };
+            let mut builder = BookBuilder::new(plies);
+            let book = builder.add_pgn(&text).map(|_| builder.build());
+            match book.and_then(|book| book.save(&args[2]).map(|_| book.len())) {
+                Ok(entries) => println!("Wrote {} entries to {}", entries, args[2]),
+                Err(e) => println!("Error: {}", e),
+            }
         }
+        Some("moves") if args.len() >= 2 => {
+            let g = match args.len() {
+                2 => Ok(Game::new()),
+                _ => Game::from_fen(&args[2..].join(" ")),
+            };
+            let moves = g.and_then(|g| Ok((OpeningBook::open(&args[1])?.moves(&g), g)));
+            match moves {
+                Ok((moves, g)) => {
+                    let total: u32 = moves.iter().map(|m| m.weight as u32).sum();
+                    for book_move in &moves {
+                        let san = san::to_san(&g, book_move.mv).unwrap_or_else(|_| book_move.mv.to_string());
+                        let share = if total == 0 { 0.0 } else { book_move.weight as f64 * 100.0 / total as f64 };
+                        println!("{:<8} {:>6} {:>6.1}%", san, book_move.weight, share);
+                    }
+                    if moves.is_empty() {
+                        println!("The position is not in the book.");
+                    }
+                }
+                Err(e) => println!("Error: {}", e),
+            }
+        }
+        _ => println!("{}", usage),
+    }
+}
+
+fn run_syzygy(args: &[String]) {
+    let Some(paths) = args.first() else {
+        println!("Usage: chess syzygy <dirs> [fen]");
+        return;
+    };
+    let g = match args.len() {
+        1 => Game::new(),
+        _ => match Game::from_fen(&args[1..].join(" ")) {
+            Ok(g) => g,
+            Err(e) => {
+                println!("Error: {}", e);
+                return;
+            }
+        },
+    };
+    let tablebase = match Tablebase::open(paths) {
+        Ok(tablebase) => tablebase,
         Err(e) => {
             println!("Error: {}", e);
-            read_user_input(g);
-        },
+            return;
+        }
+    };
+    println!("Found {} tables with up to {} pieces", tablebase.len(), tablebase.max_pieces());
+    let Some(probe) = g.probe_tablebase(&tablebase) else {
+        println!("The position is not in the tables.");
+        return;
+    };
+    match probe.dtz {
+        Some(dtz) => println!("Result: {}, {} plies to zeroing", probe.wdl, dtz),
+        None => println!("Result: {}", probe.wdl),
+    }
+    for tb_move in tablebase.root_moves(&g).unwrap_or_default() {
+        let san = san::to_san(&g, tb_move.mv).unwrap_or_else(|_| tb_move.mv.to_string());
+        println!("{:<8} {:<12} {:>5}", san, tb_move.wdl, tb_move.dtz);
     }
 }
 
-fn parse_input(input: Vec<&str>) -> Result<((char, i32),(char, i32)), &str> {
-    if input.len() != 2 {
-        return Err("Invalid input");
-    }
-    let from = input[0];
-    let to = input[1];
+fn run_perft(args: &[String]) {
+    let Some(depth) = args.first().and_then(|d| d.parse::<u32>().ok()) else {
+        println!("Usage: chess perft <depth> [fen]");
+        return;
+    };
+    let g = match args.len() {
+        1 => Game::new(),
+        _ => match Game::from_fen(&args[1..].join(" ")) {
+            Ok(g) => g,
+            Err(e) => {
+                println!("Error: {}", e);
+                return;
+            }
+        },
+    };
 
-    match (
-        from.chars().nth(0),
-        from.chars().nth(1),
-        from.chars().nth(1).unwrap().to_digit(10),
-        to.chars().nth(0),
-        to.chars().nth(1),
-        to.chars().nth(1).unwrap().to_digit(10)
-    ) {
-        (Some(from_col), Some(_), Some(from_row), Some(to_col), Some(_), Some(to_row)) =>
-            Ok(((from_col, from_row as i32), (to_col, to_row as i32)))
-        ,
-        _ => Err("Invalid input"),
+    let start = Instant::now();
+    let divided = perft::divide(&g, depth);
+    for (mv, nodes) in &divided {
+        println!("{}: {}", mv, nodes);
     }
+    let nodes: u64 = if depth == 0 { 1 } else { divided.iter().map(|(_, n)| n).sum() };
+    println!();
+    println!("Nodes searched: {}", nodes);
+    println!("Time: {} ms", start.elapsed().as_millis());
 }
@@ -0,0 +1,424 @@
+use std::fs;
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::Chars;
+use crate::game::errors::{GameErr, GameResult};
+use crate::game::{Color, Game, GameStatus, Move};
+use crate::notation::san;
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+const LINE_WIDTH: usize = 80;
+
+/// The tags written before the moves. Together with the result these are the
+/// Seven Tag Roster, unknown values are written as "?".
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnTags {
+    pub event: String,
+    pub site: String,
+    pub date: String,
+    pub round: String,
+    pub white: String,
+    pub black: String,
+    /// Any other tags, written after the roster.
+    pub extra: Vec<(String, String)>,
+}
+
+impl Default for PgnTags {
+    fn default() -> Self {
+        Self {
+            event: "?".into(),
+            site: "?".into(),
+            date: "????.??.??".into(),
+            round: "?".into(),
+            white: "?".into(),
+            black: "?".into(),
+            extra: Vec::new(),
+        }
+    }
+}
+
+/// The PGN result token for the game, "*" while it is still going on.
+pub fn result_token(game: &Game) -> &'static str {
+    match game.status() {
+        GameStatus::Checkmate => match game.current_player {
+            Color::White => "0-1",
+            Color::Black => "1-0",
+        },
+        GameStatus::Stalemate | GameStatus::Draw(_) => "1/2-1/2",
+        GameStatus::Ongoing | GameStatus::Check => "*",
+    }
+}
+
+/// Exports the game as PGN, with the moves in SAN and the comments added with
+/// `Game::set_comment`. Games not starting from the initial position get the
+/// SetUp and FEN tags.
+pub fn export(game: &Game, tags: &PgnTags) -> GameResult<String> {
+    let result = result_token(game);
+    let mut pgn = String::new();
+    let roster = [
+        ("Event", &tags.event),
+        ("Site", &tags.site),
+        ("Date", &tags.date),
+        ("Round", &tags.round),
+        ("White", &tags.white),
+        ("Black", &tags.black),
+    ];
+    for (name, value) in roster {
+        pgn.push_str(&tag(name, value));
+    }
+    pgn.push_str(&tag("Result", result));
+    if game.start_fen() != START_FEN {
+        pgn.push_str(&tag("SetUp", "1"));
+        pgn.push_str(&tag("FEN", game.start_fen()));
+    }
+    for (name, value) in &tags.extra {
+        pgn.push_str(&tag(name, value));
+    }
+    pgn.push('\n');
+
+    // Replay the game from the start to write each move in SAN.
+    let mut replay = Game::from_fen(game.start_fen())?;
+    let mut tokens: Vec<String> = Vec::new();
+    let mut after_comment = false;
+    for record in game.history() {
+        let move_number = replay.fen.split(' ').nth(5).unwrap_or("1").to_string();
+        match replay.current_player {
+            Color::White => tokens.push(format!("{}.", move_number)),
+            Color::Black if tokens.is_empty() || after_comment => tokens.push(format!("{}...", move_number)),
+            Color::Black => {}
+        }
+        tokens.push(san::to_san(&replay, record.mv)?);
+        replay.make_move(record.mv)?;
+
+        after_comment = false;
+        if let Some(comment) = &record.comment {
+            tokens.push(format!("{{{}}}", comment.replace('}', ")")));
+            after_comment = true;
+        }
+    }
+    tokens.push(result.to_string());
+
+    pgn.push_str(&wrap(&tokens));
+    pgn.push('\n');
+    Ok(pgn)
+}
+
+/// Writes the game as PGN to a file.
+pub fn write_to_file(path: impl AsRef<Path>, game: &Game, tags: &PgnTags) -> std::io::Result<()> {
+    let pgn = export(game, tags).map_err(std::io::Error::other)?;
+    fs::write(path, pgn)
+}
+
+fn tag(name: &str, value: &str) -> String {
+    format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Joins the tokens with spaces, breaking lines before they get too long.
+fn wrap(tokens: &[String]) -> String {
+    let mut text = String::new();
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length > 0 && line_length + 1 + token.len() > LINE_WIDTH {
+            text.push('\n');
+            line_length = 0;
+        } else if line_length > 0 {
+            text.push(' ');
+            line_length += 1;
+        }
+        text.push_str(token);
+        line_length += token.len();
+    }
+    text
+}
+
+/// A game read from a PGN file.
+#[derive(Debug, Clone)]
+pub struct PgnGame {
+    /// The tag pairs in the order they were written.
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<PgnMove>,
+    /// The result token ending the movetext, "*" when it was missing.
+    pub result: String,
+    /// The game with all mainline moves and their comments played.
+    pub game: Game,
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+}
+
+/// A move of the movetext with its annotations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnMove {
+    pub san: String,
+    pub mv: Move,
+    /// Numeric annotation glyphs, "!" and "?" style suffixes are stored as
+    /// their glyph ($1 to $6).
+    pub nags: Vec<u8>,
+    /// Comment written before the move, only found at the start of a game or
+    /// a variation.
+    pub starting_comment: Option<String>,
+    pub comment: Option<String>,
+    /// Alternatives to this move, each starting from the position before it.
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    Open,
+    Close,
+    Result(String),
+    Symbol(String),
+}
+
+/// Parses every game in a PGN text. The mainline and all variations are
+/// replayed, an illegal move is reported with the game and ply it occurs in.
+pub fn parse(text: &str) -> GameResult<Vec<PgnGame>> {
+    let tokens = tokenize(text)?;
+    let mut games = Vec::new();
+    let mut pos = 0;
+    while pos < tokens.len() {
+        games.push(parse_game(&tokens, &mut pos, games.len() + 1)?);
+    }
+    Ok(games)
+}
+
+fn parse_game(tokens: &[Token], pos: &mut usize, number: usize) -> GameResult<PgnGame> {
+    let mut tags = Vec::new();
+    while let Some(Token::Tag(name, value)) = tokens.get(*pos) {
+        tags.push((name.clone(), value.clone()));
+        *pos += 1;
+    }
+    let mut game = match tags.iter().find(|(name, _)| name == "FEN") {
+        Some((_, fen)) => Game::from_fen(fen)?,
+        None => Game::new(),
+    };
+
+    let moves = parse_line(tokens, pos, &mut game, number, 0)?;
+    if let Some(Token::Close) = tokens.get(*pos) {
+        return Err(GameErr::InvalidPgn(format!("Unexpected ')' in game {}", number)));
+    }
+    let result = match tokens.get(*pos) {
+        Some(Token::Result(result)) => {
+            *pos += 1;
+            result.clone()
+        }
+        _ => "*".to_string(),
+    };
+    Ok(PgnGame { tags, moves, result, game })
+}
+
+/// Reads moves until the end of the line and plays them on `game`. A
+/// variation is played on a copy of the position before the move it replaces.
+fn parse_line(tokens: &[Token], pos: &mut usize, game: &mut Game, number: usize, depth: usize) -> GameResult<Vec<PgnMove>> {
+    let mut moves: Vec<PgnMove> = Vec::new();
+    let mut before: Option<Game> = None;
+    let mut pending_comment: Option<String> = None;
+    let ply_offset = game.history().len();
+
+    while let Some(token) = tokens.get(*pos) {
+        match token {
+            Token::Tag(_, _) | Token::Result(_) => break,
+            Token::Close => {
+                if depth > 0 {
+                    *pos += 1;
+                    return Ok(moves);
+                }
+                break;
+            }
+            Token::Open => {
+                *pos += 1;
+                let (Some(last), Some(position)) = (moves.last_mut(), &before) else {
+                    return Err(GameErr::InvalidPgn(format!("Variation without a move in game {}", number)));
+                };
+                let mut variation_game = position.clone();
+                let variation = parse_line(tokens, pos, &mut variation_game, number, depth + 1)?;
+                last.variations.push(variation);
+                continue;
+            }
+            Token::Comment(text) => match moves.last_mut() {
+                Some(last) => {
+                    append_comment(&mut last.comment, text);
+                    if depth == 0 {
+                        game.set_comment(last.comment.as_deref().unwrap_or_default())?;
+                    }
+                }
+                None => append_comment(&mut pending_comment, text),
+            },
+            Token::Nag(nag) => {
+                if let Some(last) = moves.last_mut() {
+                    last.nags.push(*nag);
+                }
+            }
+            Token::Symbol(symbol) => {
+                let Some(text) = strip_move_number(symbol) else {
+                    *pos += 1;
+                    continue;
+                };
+                if let Some(nag) = suffix_nag(text) {
+                    if let Some(last) = moves.last_mut() {
+                        last.nags.push(nag);
+                    }
+                    *pos += 1;
+                    continue;
+                }
+                let (san, nag) = split_suffix(text);
+                let ply = ply_offset + moves.len() + 1;
+                let illegal = || GameErr::IllegalPgnMove { game: number, ply, san: san.to_string() };
+                let mv = san::parse_san(game, san).map_err(|_| illegal())?;
+                before = Some(game.clone());
+                game.make_move(mv).map_err(|_| illegal())?;
+                moves.push(PgnMove {
+                    san: san.to_string(),
+                    mv,
+                    nags: nag.into_iter().collect(),
+                    starting_comment: if moves.is_empty() { pending_comment.take() } else { None },
+                    comment: None,
+                    variations: Vec::new(),
+                });
+            }
+        }
+        *pos += 1;
+    }
+    if depth > 0 {
+        return Err(GameErr::InvalidPgn(format!("Unterminated variation in game {}", number)));
+    }
+    Ok(moves)
+}
+
+fn append_comment(comment: &mut Option<String>, text: &str) {
+    match comment {
+        Some(existing) => {
+            existing.push(' ');
+            existing.push_str(text);
+        }
+        None => *comment = Some(text.to_string()),
+    }
+}
+
+/// Removes a leading move number like "12." or "12...", returns `None` when
+/// nothing is left.
+fn strip_move_number(symbol: &str) -> Option<&str> {
+    let rest = symbol.trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.len() == symbol.len() || !rest.starts_with('.') {
+        return Some(symbol);
+    }
+    let rest = rest.trim_start_matches('.');
+    if rest.is_empty() { None } else { Some(rest) }
+}
+
+fn suffix_nag(text: &str) -> Option<u8> {
+    match text {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    }
+}
+
+/// Splits "e4!?" into the move and the glyph of its suffix annotation.
+fn split_suffix(text: &str) -> (&str, Option<u8>) {
+    let san = text.trim_end_matches(['!', '?']);
+    (san, suffix_nag(&text[san.len()..]))
+}
+
+fn tokenize(text: &str) -> GameResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    let mut line_start = true;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => {
+                line += 1;
+                line_start = true;
+                continue;
+            }
+            '%' if line_start => skip_line(&mut chars),
+            ';' => skip_line(&mut chars),
+            c if c.is_whitespace() => {}
+            '[' => {
+                let tag = read_tag(&mut chars).ok_or_else(|| GameErr::InvalidPgn(format!("Malformed tag pair on line {}", line)))?;
+                tokens.push(tag);
+            }
+            '{' => {
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            comment.push(c);
+                        }
+                        None => return Err(GameErr::InvalidPgn(format!("Unterminated comment on line {}", line))),
+                    }
+                }
+                tokens.push(Token::Comment(comment.split_whitespace().collect::<Vec<_>>().join(" ")));
+            }
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '$' => {
+                let mut digits = String::new();
+                while let Some(d) = chars.next_if(|d| d.is_ascii_digit()) {
+                    digits.push(d);
+                }
+                let nag = digits.parse().map_err(|_| GameErr::InvalidPgn(format!("Invalid NAG on line {}", line)))?;
+                tokens.push(Token::Nag(nag));
+            }
+            _ => {
+                let mut symbol = c.to_string();
+                while let Some(s) = chars.next_if(|s| !s.is_whitespace() && !"{}()[];$".contains(*s)) {
+                    symbol.push(s);
+                }
+                match symbol.as_str() {
+                    "1-0" | "0-1" | "1/2-1/2" | "*" => tokens.push(Token::Result(symbol)),
+                    _ => tokens.push(Token::Symbol(symbol)),
+                }
+            }
+        }
+        line_start = false;
+    }
+    Ok(tokens)
+}
+
+fn skip_line(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| *c != '\n').is_some() {}
+}
+
+/// Reads a tag pair like `Event "Casual game"]`, the '[' is already read.
+fn read_tag(chars: &mut Peekable<Chars>) -> Option<Token> {
+    while chars.next_if(|c| c.is_whitespace() && *c != '\n').is_some() {}
+    let mut name = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+        name.push(c);
+    }
+    while chars.next_if(|c| c.is_whitespace() && *c != '\n').is_some() {}
+    if name.is_empty() || chars.next() != Some('"') {
+        return None;
+    }
+    let mut value = String::new();
+    loop {
+        match chars.next()? {
+            '"' => break,
+            '\\' => value.push(chars.next()?),
+            '\n' => return None,
+            c => value.push(c),
+        }
+    }
+    while chars.next_if(|c| c.is_whitespace() && *c != '\n').is_some() {}
+    if chars.next() != Some(']') {
+        return None;
+    }
+    Some(Token::Tag(name, value))
+}
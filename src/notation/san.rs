@@ -0,0 +1,157 @@
+use crate::game::errors::{GameErr, GameResult};
+use crate::game::{Color, Game, GameStatus, Move, Piece, PieceType, Square};
+use crate::ruleengine;
+
+/// Parses a move in Standard Algebraic Notation, like "Nbd7", "exd6 e.p.",
+/// "O-O-O" or "e8=Q+", against the current position.
+pub fn parse_san(game: &Game, san: &str) -> GameResult<Move> {
+    let invalid = || GameErr::InvalidSan(san.to_string());
+
+    // Check, mate and annotation marks carry no information for finding the move.
+    let mut text = san.trim();
+    text = text.strip_suffix("e.p.").unwrap_or(text).trim_end();
+    text = text.trim_end_matches(['+', '#', '!', '?']);
+
+    let (king, kingside, queenside) = match game.current_player {
+        Color::White => (Square::E1, Square::G1, Square::C1),
+        Color::Black => (Square::E8, Square::G8, Square::C8),
+    };
+    let castling = match text {
+        "O-O" | "0-0" => Some(kingside),
+        "O-O-O" | "0-0-0" => Some(queenside),
+        _ => None,
+    };
+    if let Some(to) = castling {
+        let mv = Move::new(king, to);
+        let is_king = ruleengine::get_piece_at_pos(game.board(), mv.from)
+            .is_some_and(|p| p.piece_type == PieceType::King);
+        if !is_king || ruleengine::check_move(game, mv).is_err() {
+            return Err(GameErr::IllegalSan(san.to_string()));
+        }
+        return Ok(mv);
+    }
+
+    let mut chars: Vec<char> = text.chars().collect();
+
+    // Promotion, "e8=Q" or "e8Q"
+    let mut promotion = None;
+    if let Some(&last) = chars.last()
+        && "QRBN".contains(last)
+        && chars.len() >= 3 {
+        promotion = Some(Piece::from_char_code(last).ok_or_else(invalid)?.piece_type);
+        chars.pop();
+        if chars.last() == Some(&'=') {
+            chars.pop();
+        }
+    }
+
+    // Destination square
+    if chars.len() < 2 {
+        return Err(invalid());
+    }
+    let to_str: String = chars.split_off(chars.len() - 2).into_iter().collect();
+    let to: Square = to_str.parse().map_err(|_| invalid())?;
+
+    // Piece letter, pawns have none
+    let piece_type = match chars.first() {
+        Some(c) if "NBRQK".contains(*c) => {
+            let piece_type = Piece::from_char_code(*c).ok_or_else(invalid)?.piece_type;
+            chars.remove(0);
+            piece_type
+        }
+        _ => PieceType::Pawn,
+    };
+
+    // What is left is disambiguation and the capture mark
+    if chars.last() == Some(&'x') {
+        chars.pop();
+    }
+    let (mut from_file, mut from_rank) = (None, None);
+    for c in chars {
+        match c {
+            'a'..='h' if from_file.is_none() && from_rank.is_none() => from_file = Some(c),
+            '1'..='8' if from_rank.is_none() => from_rank = c.to_digit(10).map(|d| d as i32),
+            _ => return Err(invalid()),
+        }
+    }
+
+    let candidates: Vec<Move> = ruleengine::legal_moves(game).into_iter()
+        .filter(|mv| mv.to == to && mv.promotion == promotion)
+        .filter(|mv| from_file.is_none_or(|f| mv.from.file() == f))
+        .filter(|mv| from_rank.is_none_or(|r| mv.from.rank() == r))
+        .filter(|mv| {
+            ruleengine::get_piece_at_pos(game.board(), mv.from).is_some_and(|p| p.piece_type == piece_type)
+        })
+        .collect();
+
+    match candidates[..] {
+        [mv] => Ok(mv),
+        [] => Err(GameErr::IllegalSan(san.to_string())),
+        _ => Err(GameErr::AmbiguousSan(san.to_string())),
+    }
+}
+
+/// Formats a legal move in Standard Algebraic Notation, with "+" for check
+/// and "#" for mate.
+pub fn to_san(game: &Game, mv: Move) -> GameResult<String> {
+    ruleengine::check_move(game, mv)?;
+    let piece = ruleengine::get_piece_at_pos(game.board(), mv.from).ok_or(GameErr::NoPieceAtPosition)?;
+
+    let mut san = String::new();
+    match ruleengine::is_castling_move(game, mv.from, mv.to, game.current_player) {
+        "K" | "k" => san.push_str("O-O"),
+        "Q" | "q" => san.push_str("O-O-O"),
+        _ => {
+            let is_capture = ruleengine::get_piece_at_pos(game.board(), mv.to).is_some()
+                || (piece.piece_type == PieceType::Pawn && mv.from.file() != mv.to.file());
+
+            if piece.piece_type == PieceType::Pawn {
+                if is_capture {
+                    san.push(mv.from.file());
+                }
+            } else {
+                san.push(piece.get_char_code().to_ascii_uppercase());
+                san.push_str(&disambiguation(game, mv, piece.piece_type));
+            }
+            if is_capture {
+                san.push('x');
+            }
+            san.push_str(&mv.to.to_string());
+            if let Some(piece_type) = mv.promotion {
+                let promoted = Piece { color: Color::White, piece_type };
+                san.push('=');
+                san.push(promoted.get_char_code());
+            }
+        }
+    }
+
+    let mut after = game.clone();
+    after.make_move(mv)?;
+    match after.status() {
+        GameStatus::Checkmate => san.push('#'),
+        _ if after.bitboards().is_in_check(after.current_player) => san.push('+'),
+        _ => {}
+    }
+    Ok(san)
+}
+
+/// File, rank or both of the origin square, when another piece of the same
+/// type can also reach the destination.
+fn disambiguation(game: &Game, mv: Move, piece_type: PieceType) -> String {
+    let others: Vec<Move> = ruleengine::legal_moves(game).into_iter()
+        .filter(|other| other.to == mv.to && other.from != mv.from)
+        .filter(|other| {
+            ruleengine::get_piece_at_pos(game.board(), other.from).is_some_and(|p| p.piece_type == piece_type)
+        })
+        .collect();
+
+    if others.is_empty() {
+        String::new()
+    } else if others.iter().all(|other| other.from.file() != mv.from.file()) {
+        mv.from.file().to_string()
+    } else if others.iter().all(|other| other.from.rank() != mv.from.rank()) {
+        mv.from.rank().to_string()
+    } else {
+        mv.from.to_string()
+    }
+}
@@ -3,33 +3,27 @@ mod ruleset_rook;
 mod ruleset_knight;
 mod ruleset_bishop;
 mod ruleset_king;
+pub mod perft;
 
+use crate::game::bitboard::{self, Bitboard, Bitboards};
 use crate::game::errors::{GameErr, GameResult};
 use crate::game::*;
-use crate::ruleengine;
-pub fn get_piece_at_pos(board: &[Option<Piece>; 64], pos: (char, i32)) -> Option<&Piece> {
-    let board_index = get_index_based_on_pos(pos);
-    let to_return = board.get(board_index).and_then(|f| { f.as_ref() });
-    to_return
-}
 
-pub fn get_piece_at_index(board: &[Option<Piece>; 64], index: usize) -> Option<&Piece> {
-    let to_return = board.get(index).and_then(|f|{f.as_ref()});
-    to_return
+pub const PROMOTION_PIECES: [PieceType; 4] = [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight];
+
+pub fn get_piece_at_pos(board: &[Option<Piece>; 64], pos: Square) -> Option<&Piece> {
+    board.get(pos.index()).and_then(|f| { f.as_ref() })
 }
 
-pub fn get_index_based_on_pos(pos: (char, i32)) -> usize {
-    let chars = vec!['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
-    let char_index = chars.iter().position(|f| f.eq(&pos.0)).expect("Invalid char");
-    let row = (8 * pos.1 - 8) as usize;
-    char_index + row as usize
+pub fn get_piece_at_index(board: &[Option<Piece>; 64], index: usize) -> Option<&Piece> {
+    board.get(index).and_then(|f|{f.as_ref()})
 }
 
-pub fn is_allowed_move(game: &Game, from: (char, i32), to: (char, i32), current_player: Color) -> GameResult<i32> {
+pub fn is_allowed_move(game: &Game, from: Square, to: Square, current_player: Color) -> GameResult<i32> {
 
-    let piece_from = ruleengine::get_piece_at_pos(&game.board, from).expect("No piece at position.");
+    let piece_from = get_piece_at_pos(game.board(), from).ok_or(GameErr::NoPieceAtPosition)?;
 
-    let piece_to = ruleengine::get_piece_at_pos(&game.board, to);
+    let piece_to = get_piece_at_pos(game.board(), to);
 
     // Wrong color is moving
     if piece_from.color != current_player {
@@ -42,29 +36,28 @@ pub fn is_allowed_move(game: &Game, from: (char, i32), to: (char, i32), current_
     }
 
     // Check that the position is empty, or that it is not occupied by the same color.
-    if let Some(piece_to) = piece_to {
-        if (piece_from.color ==  piece_to.color) {
-            return Err(GameErr::PositionOccupied);
-        }
+    if let Some(piece_to) = piece_to
+        && piece_from.color == piece_to.color {
+        return Err(GameErr::PositionOccupied);
     }
-    
+
     match piece_from.piece_type {
         PieceType::Pawn => {
-            ruleset_pawn::check(&game.board, from, to, current_player)
+            ruleset_pawn::check(game.board(), from, to, current_player, game.en_passant_target())
         },
         PieceType::Rook => {
-            ruleset_rook::check(&game.board, from, to, current_player)
+            ruleset_rook::check(game.board(), from, to, current_player)
         },
         PieceType::Knight => {
-            ruleset_knight::check(&game.board, from ,to, current_player)
+            ruleset_knight::check(game.board(), from ,to, current_player)
         },
         PieceType::Bishop => {
-            ruleset_bishop::check(&game.board, from ,to, current_player)
+            ruleset_bishop::check(game.board(), from ,to, current_player)
         }
         PieceType::Queen => {
-            ruleset_rook::check(&game.board, from, to, current_player)
+            ruleset_rook::check(game.board(), from, to, current_player)
                 .or_else(|_| {
-                    ruleset_bishop::check(&game.board, from, to, current_player)
+                    ruleset_bishop::check(game.board(), from, to, current_player)
                         .map_err(|e| match e {
                             GameErr::IllegalBishopMove => GameErr::IllegalQueenMove,
                             _ => e,
@@ -72,25 +65,284 @@ pub fn is_allowed_move(game: &Game, from: (char, i32), to: (char, i32), current_
                 })
         }
         PieceType::King => {
-            ruleset_king::check(&game.board, from, to, current_player)
+            ruleset_king::check(game.board(), from, to, current_player)
         }
     }
 
 }
 
-pub fn is_castling_move(game: &Game, from: (char, i32), to: (char, i32), current_player: Color) -> &str {
-    if (from.0 == 'e' && to.0 == 'g' && (from.1 == 1 && to.1 == from.1) && game.white_can_castle.contains("K") && current_player == Color::White) {
-        return "K".as_ref()
+/// Returns the castling right the move uses, "K", "Q", "k" or "q", or "-" when
+/// the move is not a castling move. The king moves two squares towards the rook.
+pub fn is_castling_move(game: &Game, from: Square, to: Square, current_player: Color) -> &'static str {
+    let rank = if current_player == Color::White { 1 } else { 8 };
+    let is_king = get_piece_at_pos(game.board(), from)
+        .is_some_and(|p| p.piece_type == PieceType::King && p.color == current_player);
+    if !is_king || from.file() != 'e' || from.rank() != rank || to.rank() != rank {
+        return "-"
     }
-    if (from.0 == 'e' && to.0 == 'g' && (from.1 == 1 && to.1 == from.1) && game.white_can_castle.contains("Q") && current_player == Color::White) {
-        return "Q".as_ref()
+    match (to.file(), current_player) {
+        ('g', Color::White) => "K",
+        ('c', Color::White) => "Q",
+        ('g', Color::Black) => "k",
+        ('c', Color::Black) => "q",
+        _ => "-",
     }
-    if (from.0 == 'e' && to.0 == 'g' && (from.1 == 8 && to.1 == from.1) && game.black_can_castle.contains("k") && current_player == Color::Black) {
-        return "k".as_ref()
+}
+
+/// Checks that castling is allowed: the right is still there, the rook is in
+/// place, the squares between king and rook are empty and the king does not
+/// leave, pass through or land on an attacked square.
+pub fn check_castling(game: &Game, castling: &str) -> GameResult<()> {
+    let (rights, color) = match castling {
+        "K" | "Q" => (&game.white_can_castle, Color::White),
+        _ => (&game.black_can_castle, Color::Black),
+    };
+    let opponent = match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    };
+    let (rook, between, king_path): (Square, &[Square], [Square; 2]) = match castling {
+        "K" => (Square::H1, &[Square::F1, Square::G1], [Square::F1, Square::G1]),
+        "Q" => (Square::A1, &[Square::B1, Square::C1, Square::D1], [Square::D1, Square::C1]),
+        "k" => (Square::H8, &[Square::F8, Square::G8], [Square::F8, Square::G8]),
+        "q" => (Square::A8, &[Square::B8, Square::C8, Square::D8], [Square::D8, Square::C8]),
+        _ => return Err(GameErr::CastlingNotAllowed),
+    };
+
+    if !rights.contains(castling) {
+        return Err(GameErr::CastlingNotAllowed);
     }
-    if (from.0 == 'e' && to.0 == 'g' && (from.1 == 8 && to.1 == from.1) && game.black_can_castle.contains("q") && current_player == Color::Black) {
-        return "q".as_ref()
+    let has_rook = get_piece_at_pos(game.board(), rook)
+        .is_some_and(|p| p.piece_type == PieceType::Rook && p.color == color);
+    if !has_rook {
+        return Err(GameErr::CastlingNotAllowed);
     }
+    if between.iter().any(|&square| get_piece_at_pos(game.board(), square).is_some()) {
+        return Err(GameErr::CastlingPathBlocked);
+    }
+    if game.bitboards().is_in_check(color) {
+        return Err(GameErr::CastlingOutOfCheck);
+    }
+    if game.bitboards().is_attacked(king_path[0], opponent) {
+        return Err(GameErr::CastlingThroughCheck);
+    }
+    if game.bitboards().is_attacked(king_path[1], opponent) {
+        return Err(GameErr::CastlingIntoCheck);
+    }
+    Ok(())
+}
+
+/// Where the rook moves from and to for the given castling right.
+pub fn castling_rook_move(castling: &str) -> Option<(Square, Square)> {
+    match castling {
+        "K" => Some((Square::H1, Square::F1)),
+        "Q" => Some((Square::A1, Square::D1)),
+        "k" => Some((Square::H8, Square::F8)),
+        "q" => Some((Square::A8, Square::D8)),
+        _ => None,
+    }
+}
 
-    "-"
-}
\ No newline at end of file
+/// The square of the pawn taken when a pawn moves from `from` to `to` en passant.
+pub fn en_passant_capture_square(from: Square, to: Square) -> Square {
+    to.offset(0, from.rank() - to.rank()).unwrap_or(to)
+}
+
+/// Validates a move for the current player without changing the game.
+/// Returns the points won by the move, captures and promotions. A move is
+/// legal when the move generator lists it, the rule sets only name the
+/// reason a move is rejected.
+pub fn check_move(game: &Game, mv: Move) -> GameResult<i32> {
+    if !legal_moves_from(game, mv.from).contains(&mv) {
+        return Err(illegal_move_reason(game, mv));
+    }
+    Ok(move_points(game, mv))
+}
+
+/// The points won by a legal move, captures and promotions.
+pub fn move_points(game: &Game, mv: Move) -> i32 {
+    let Some(piece) = get_piece_at_pos(game.board(), mv.from) else {
+        return 0;
+    };
+    let captured = match get_piece_at_pos(game.board(), mv.to) {
+        None if piece.piece_type == PieceType::Pawn && mv.from.file() != mv.to.file() => {
+            get_piece_at_pos(game.board(), en_passant_capture_square(mv.from, mv.to))
+        }
+        captured => captured,
+    };
+    let promotion = mv.promotion
+        .map_or(0, |piece_type| Piece { color: piece.color, piece_type }.get_points() - piece.get_points());
+    captured.map_or(0, |p| p.get_points()) + promotion
+}
+
+/// Why the move generator does not list the move.
+fn illegal_move_reason(game: &Game, mv: Move) -> GameErr {
+    let (from, to) = (mv.from, mv.to);
+    let Some(piece) = get_piece_at_pos(game.board(), from) else {
+        return GameErr::NoPieceAtPosition;
+    };
+    if piece.color != game.current_player {
+        return GameErr::IllegalMoveOnOtherPlayer;
+    }
+
+    let castling = is_castling_move(game, from, to, game.current_player);
+    let allowed = match castling {
+        "-" => is_allowed_move(game, from, to, game.current_player).map(|_| ()),
+        _ => check_castling(game, castling),
+    };
+    if let Err(e) = allowed {
+        return e;
+    }
+
+    // A pawn reaching the last rank must be promoted, and nothing else can be.
+    let is_promotion = piece.piece_type == PieceType::Pawn && (to.rank() == 1 || to.rank() == 8);
+    match (is_promotion, mv.promotion) {
+        (true, None) => GameErr::PromotionRequired,
+        (true, Some(PieceType::Pawn | PieceType::King)) | (false, Some(_)) => GameErr::InvalidPromotion,
+        // The move would leave the own king in check.
+        _ => GameErr::KingInCheck,
+    }
+}
+
+/// Moves the piece on the board, including the rook when castling and the
+/// captured pawn when taking en passant.
+pub fn apply_to_board(board: &mut [Option<Piece>; 64], mv: Move, castling: &str) {
+    let (from, to) = (mv.from, mv.to);
+    if let Some((rook_from, rook_to)) = castling_rook_move(castling) {
+        board[rook_to.index()] = board[rook_from.index()];
+        board[rook_from.index()] = None;
+    }
+
+    // A pawn moving diagonally to an empty square captures en passant.
+    let is_pawn = get_piece_at_pos(board, from).is_some_and(|p| p.piece_type == PieceType::Pawn);
+    if is_pawn && from.file() != to.file() && get_piece_at_pos(board, to).is_none() {
+        board[en_passant_capture_square(from, to).index()] = None;
+    }
+
+    let from_idx = from.index();
+    board[to.index()] = match (board[from_idx], mv.promotion) {
+        (Some(piece), Some(piece_type)) => Some(Piece { color: piece.color, piece_type }),
+        (piece, None) => piece,
+        (None, _) => None,
+    };
+    board[from_idx] = None;
+}
+
+/// Every legal move for the player to move.
+pub fn legal_moves(game: &Game) -> Vec<Move> {
+    generate_moves(game, !0)
+}
+
+/// Every legal move for the piece at `from`. Empty if the square holds no piece
+/// of the player to move. A pawn reaching the last rank gives one move per
+/// promotion piece.
+pub fn legal_moves_from(game: &Game, from: Square) -> Vec<Move> {
+    generate_moves(game, bitboard::bit(from))
+}
+
+/// True if the current player has at least one legal move.
+pub fn has_legal_move(game: &Game) -> bool {
+    !legal_moves(game).is_empty()
+}
+
+/// Generates the legal moves of the pieces on `from_squares`. Candidate
+/// squares come from the attack tables, a candidate is kept when it does not
+/// leave the own king in check.
+fn generate_moves(game: &Game, from_squares: Bitboard) -> Vec<Move> {
+    let bitboards = game.bitboards();
+    let player = game.current_player;
+    let own = bitboards.color(player);
+    let mut moves = Vec::new();
+
+    for from in bitboard::squares(own & from_squares) {
+        let Some(piece) = bitboards.piece_at(from) else {
+            continue;
+        };
+        let targets = match piece.piece_type {
+            PieceType::Pawn => pawn_targets(bitboards, from, player, game.en_passant_target()),
+            _ => bitboards.attacks_from(from) & !own,
+        };
+        for to in bitboard::squares(targets) {
+            let mv = Move::new(from, to);
+            let mut after = *bitboards;
+            after.apply(mv, "-");
+            if after.is_in_check(player) {
+                continue;
+            }
+            if piece.piece_type == PieceType::Pawn && bitboard::bit(to) & (bitboard::RANK_1 | bitboard::RANK_8) != 0 {
+                moves.extend(PROMOTION_PIECES.map(|piece_type| Move::with_promotion(from, to, piece_type)));
+            } else {
+                moves.push(mv);
+            }
+        }
+        if piece.piece_type == PieceType::King {
+            for to in [from.offset(2, 0), from.offset(-2, 0)].into_iter().flatten() {
+                let castling = is_castling_move(game, from, to, player);
+                if castling != "-" && check_castling(game, castling).is_ok() {
+                    moves.push(Move::new(from, to));
+                }
+            }
+        }
+    }
+    moves
+}
+
+/// Pushes, double steps from the start rank, captures and the en passant capture.
+fn pawn_targets(bitboards: &Bitboards, from: Square, player: Color, en_passant_target: Option<Square>) -> Bitboard {
+    let empty = !bitboards.occupied();
+    let (forward, start_rank) = match player {
+        Color::White => (1, 2),
+        Color::Black => (-1, 7),
+    };
+    let mut targets = 0;
+    if let Some(single) = from.offset(0, forward)
+        && empty & bitboard::bit(single) != 0 {
+        targets |= bitboard::bit(single);
+        if from.rank() == start_rank
+            && let Some(double) = from.offset(0, 2 * forward)
+            && empty & bitboard::bit(double) != 0 {
+            targets |= bitboard::bit(double);
+        }
+    }
+    let opponent = match player {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    };
+    let mut capturable = bitboards.color(opponent);
+    if let Some(target) = en_passant_target {
+        capturable |= bitboard::bit(target);
+    }
+    targets | (bitboard::pawn_attacks(from, player) & capturable)
+}
+
+/// True if neither side can possibly checkmate: bare kings, a single minor
+/// piece, or only bishops that all stand on squares of the same colour.
+pub fn is_insufficient_material(board: &[Option<Piece>; 64]) -> bool {
+    let pieces: Vec<(usize, &Piece)> = board.iter().enumerate()
+        .filter_map(|(i, p)| p.as_ref().map(|p| (i, p)))
+        .filter(|(_, p)| p.piece_type != PieceType::King)
+        .collect();
+
+    match pieces[..] {
+        [] => true,
+        [(_, p)] => matches!(p.piece_type, PieceType::Bishop | PieceType::Knight),
+        _ => {
+            let square_colour = |i: usize| (i / 8 + i % 8) % 2;
+            pieces.iter().all(|(_, p)| p.piece_type == PieceType::Bishop)
+                && pieces.iter().all(|(i, _)| square_colour(*i) == square_colour(pieces[0].0))
+        }
+    }
+}
+
+pub fn find_king(bitboards: &Bitboards, color: Color) -> Option<Square> {
+    bitboards.king_square(color)
+}
+
+pub fn is_in_check(bitboards: &Bitboards, color: Color) -> bool {
+    bitboards.is_in_check(color)
+}
+
+/// True if any piece of the color `by` attacks the square.
+pub fn is_square_attacked(bitboards: &Bitboards, square: Square, by: Color) -> bool {
+    bitboards.is_attacked(square, by)
+}
@@ -0,0 +1,43 @@
+use crate::game::{Game, Move};
+
+/// Counts the leaf nodes of the move tree `depth` plies deep. The moves are
+/// played and taken back on the game, so this checks the move generator,
+/// the move validation and undo together.
+pub fn perft(game: &Game, depth: u32) -> u64 {
+    let mut game = game.clone();
+    count(&mut game, depth)
+}
+
+/// The perft count below each legal move, in the order the moves are generated.
+pub fn divide(game: &Game, depth: u32) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    let mut game = game.clone();
+    game.legal_moves()
+        .into_iter()
+        .map(|mv| {
+            game.make_move(mv).expect("generated move is legal");
+            let nodes = count(&mut game, depth - 1);
+            game.undo().expect("move was just played");
+            (mv, nodes)
+        })
+        .collect()
+}
+
+fn count(game: &mut Game, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = game.legal_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    let mut nodes = 0;
+    for mv in moves {
+        game.make_move(mv).expect("generated move is legal");
+        nodes += count(game, depth - 1);
+        game.undo().expect("move was just played");
+    }
+    nodes
+}
@@ -1,9 +1,7 @@
-use std::cmp::PartialEq;
-use log::debug;
 use crate::game::errors::{GameErr, GameResult};
-use crate::game::{Color, Game, Piece};
+use crate::game::{Color, Piece, Square};
 use crate::ruleengine;
-use crate::ruleengine::{get_piece_at_index, get_piece_at_pos};
+use crate::ruleengine::get_piece_at_pos;
 
 #[derive(PartialEq, Debug, Clone, Copy, Eq, Hash)]
 enum Direction {
@@ -12,13 +10,13 @@ enum Direction {
     DownRight,
     DownLeft,
 }
-pub fn check(board: &[Option<Piece>; 64], from: (char, i32), to: (char, i32), current_player: Color) -> GameResult<i32> {
-    let index = ruleengine::get_index_based_on_pos(from) as i32;
-    let target_index = ruleengine::get_index_based_on_pos(to) as i32;
+pub fn check(board: &[Option<Piece>; 64], from: Square, to: Square, current_player: Color) -> GameResult<i32> {
+    let index = from.index() as i32;
+    let target_index = to.index() as i32;
 
     let up_right_positions = step(index, Direction::UpRight);
     let found_up_right = up_right_positions.iter().find(|&i| i.eq(&target_index));
-    if found_up_right.is_some() && !is_path_blocked_up(&board, up_right_positions, target_index)? {
+    if found_up_right.is_some() && !is_path_blocked_up(board, up_right_positions, target_index)? {
         return check_score_and_return(board, to, current_player);
     }
 
@@ -44,20 +42,20 @@ pub fn check(board: &[Option<Piece>; 64], from: (char, i32), to: (char, i32), cu
     Err(GameErr::IllegalBishopMove)
 }
 fn is_path_blocked_up(board: &[Option<Piece>; 64], selection: Vec<i32>, target_index: i32) -> Result<bool, GameErr> {
-    if selection.iter().find(|&i| ruleengine::get_piece_at_index(&board, i.clone() as usize).is_some() && i < &target_index).is_some() {
+    if selection.iter().any(|&i| ruleengine::get_piece_at_index(board, i as usize).is_some() && i < target_index) {
         return Err(GameErr::PathIsBlocked);
     }
     Ok(false)
 }
 fn is_path_blocked_down(board: &[Option<Piece>; 64], selection: Vec<i32>, target_index: i32) -> Result<bool, GameErr> {
-    if selection.iter().find(|&i| ruleengine::get_piece_at_index(&board, i.clone() as usize).is_some() && i > &target_index).is_some() {
+    if selection.iter().any(|&i| ruleengine::get_piece_at_index(board, i as usize).is_some() && i > target_index) {
         return Err(GameErr::PathIsBlocked);
     }
     Ok(false)
 }
 
-fn check_score_and_return (board: &[Option<Piece>; 64], to: (char, i32), current_player: Color) -> GameResult<i32>{
-    if let Some(piece_at_pos) = get_piece_at_pos(&board, to) {
+fn check_score_and_return (board: &[Option<Piece>; 64], to: Square, current_player: Color) -> GameResult<i32>{
+    if let Some(piece_at_pos) = get_piece_at_pos(board, to) {
         if piece_at_pos.color == current_player {
             return Err(GameErr::IllegalBishopMove);
         }
@@ -90,7 +88,7 @@ fn step(index: i32, direction: Direction) -> Vec<i32> {
 
         index += add;
         // If we've fallen off the board, stop
-        if index < 0 || index >= 64 {
+        if !(0..64).contains(&index) {
             break;
         }
         moves.push(index);
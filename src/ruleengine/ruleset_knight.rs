@@ -1,10 +1,9 @@
-use log::debug;
 use crate::game::errors::{GameErr, GameResult};
-use crate::game::{Color, Piece};
+use crate::game::{Color, Piece, Square};
 use crate::ruleengine;
 
-pub fn check(board: &[Option<Piece>; 64], from: (char, i32), to: (char, i32), current_player: Color) -> GameResult<i32> {
-    let index = ruleengine::get_index_based_on_pos(from) as i32;
+pub fn check(board: &[Option<Piece>; 64], from: Square, to: Square, current_player: Color) -> GameResult<i32> {
+    let index = from.index() as i32;
     let legal_pos: [i32; 8] = [
         step(index, 2, true, false),
         step(index, 2, true, true),
@@ -15,15 +14,14 @@ pub fn check(board: &[Option<Piece>; 64], from: (char, i32), to: (char, i32), cu
         step(index, 1, false, false),
         step(index, 1, false, true),
     ];
-    let target = ruleengine::get_index_based_on_pos(to) as i32;
+    let target = to.index() as i32;
     let is_valid = legal_pos.contains(&target);
     if !is_valid {
         return Err(GameErr::IllegalKnightMove);
     }
-    if let Some(at_target) = ruleengine::get_piece_at_pos(&board, to) {
-        if at_target.color != current_player {
-            return Ok(at_target.get_points());
-        }
+    if let Some(at_target) = ruleengine::get_piece_at_pos(board, to)
+        && at_target.color != current_player {
+        return Ok(at_target.get_points());
     }
     Ok(0)
 }
@@ -43,7 +41,7 @@ fn step(index: i32, step: i32, direction_up: bool, direction_left: bool) -> i32{
     let end_pos = start_pos + (direction_val) + (last);
     let end_row = (end_pos / 8) + 1;
 
-    if (end_pos < 0) || (end_pos > 63) {
+    if !(0..=63).contains(&end_pos) {
         return -1;
     }
 
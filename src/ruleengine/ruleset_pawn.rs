@@ -1,33 +1,47 @@
 use crate::game::errors::{GameErr, GameResult};
-use crate::game::Color;
+use crate::game::{Color, PieceType, Square};
 use crate::ruleengine;
 use crate::game::Piece;
-pub fn check(board: &[Option<Piece>; 64], from: (char, i32), to: (char, i32), current_player: Color) -> GameResult<i32> {
-    let piece_from = ruleengine::get_piece_at_pos(&board, from);
-    let piece_to = ruleengine::get_piece_at_pos(&board, to);
+pub fn check(board: &[Option<Piece>; 64], from: Square, to: Square, current_player: Color, en_passant_target: Option<Square>) -> GameResult<i32> {
+    let piece_from = ruleengine::get_piece_at_pos(board, from);
+    let piece_to = ruleengine::get_piece_at_pos(board, to);
+    let rank_distance = to.rank() - from.rank();
+    let file_distance = (to.file() as i32 - from.file() as i32).abs();
 
-    if to.1 - from.1 == 1 && current_player == Color::White && piece_to.is_none() {
-        if from.0 == to.0 {
-            return Ok(0)
-        }
+    if rank_distance == 1 && current_player == Color::White && piece_to.is_none() && file_distance == 0 {
+        return Ok(0)
+    }
+    if rank_distance == -1 && current_player == Color::Black && piece_to.is_none() && file_distance == 0 {
+        return Ok(0)
     }
-    if to.1 - from.1 == -1 && current_player == Color::Black && piece_to.is_none() {
-        if from.0 == to.0 {
+    // Double step from the starting rank, both squares in front must be empty.
+    let is_double_step = (current_player == Color::White && from.rank() == 2 && to.rank() == 4)
+        || (current_player == Color::Black && from.rank() == 7 && to.rank() == 5);
+    if is_double_step && file_distance == 0 {
+        let passed = from.offset(0, rank_distance / 2).ok_or(GameErr::IllegalPawnMove)?;
+        if ruleengine::get_piece_at_pos(board, passed).is_some() {
+            return Err(GameErr::PathIsBlocked)
+        }
+        if piece_to.is_none() {
             return Ok(0)
         }
     }
-    if (from.1 == 2 && to.1 == 4) || (from.1 == 7 && to.1 == 5) {
-        return Ok(0)
-    }
+    let forward = if current_player == Color::White { 1 } else { -1 };
     if let Some(target) = piece_to
-        && (to.1 - from.1).abs() == 1
-        && (to.0 as i32 - from.0 as i32 == 1 || to.0 as i32 - from.0 as i32 == -1)
-    {
-        if let Some(piece_from) = piece_from {
-            if (target.color != piece_from.color) {
-                return Ok(target.get_points());
-            }
-        }
+        && rank_distance == forward
+        && file_distance == 1
+        && let Some(piece_from) = piece_from
+        && target.color != piece_from.color {
+        return Ok(target.get_points());
+    }
+    // En passant, the pawn that just made a double step is captured in passing.
+    if en_passant_target == Some(to)
+        && rank_distance == forward
+        && file_distance == 1
+        && let Some(passed) = ruleengine::get_piece_at_pos(board, ruleengine::en_passant_capture_square(from, to))
+        && passed.color != current_player
+        && passed.piece_type == PieceType::Pawn {
+        return Ok(passed.get_points());
     }
     Err(GameErr::IllegalPawnMove)
 }
@@ -1,38 +1,233 @@
+use std::thread;
+use std::time::Duration;
 use reqwest::blocking::Client;
 use serde_json::json;
 use serde::Deserialize;
+use thiserror::Error;
+use crate::engine::player::{MoveProvider, PlayerMove, Score};
+use crate::engine::search::SearchLimits;
+use crate::game::{Color, Game, Move};
+use crate::game::errors::{GameErr, GameResult};
 
-#[derive(Debug, Deserialize)]
+pub mod mock;
+
+pub const DEFAULT_BASE_URL: &str = "https://chess-api.com/v1";
+/// The deepest search the service runs.
+pub const MAX_DEPTH: u32 = 18;
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum StockfishErr {
+    #[error("Could not reach the chess API: {0}")]
+    Connection(String),
+    #[error("The chess API did not answer within {0:?}")]
+    Timeout(Duration),
+    #[error("The chess API answered with status {status}: {body}")]
+    Status { status: u16, body: String },
+    #[error("Invalid response from the chess API: {0}")]
+    InvalidResponse(String),
+    #[error("The chess API reported an error: {0}")]
+    Service(String),
+    #[error("The chess API response contains no valid move")]
+    NoMove,
+}
+
+impl StockfishErr {
+    /// Failures that may go away when the request is sent again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            StockfishErr::Connection(_) | StockfishErr::Timeout(_) => true,
+            StockfishErr::Status { status, .. } => *status >= 500 || *status == 429,
+            _ => false,
+        }
+    }
+}
+
+impl From<StockfishErr> for GameErr {
+    fn from(e: StockfishErr) -> Self {
+        GameErr::Engine(e.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct NextMove {
+    #[serde(default)]
     pub from: String,
+    #[serde(default)]
     pub to: String,
+    /// The move in long algebraic notation, like "e7e8q".
+    #[serde(rename = "move", default)]
+    pub lan: Option<String>,
+    #[serde(default)]
+    pub san: Option<String>,
+    #[serde(default)]
+    pub promotion: Option<String>,
+    /// Evaluation in pawns from White's point of view.
+    #[serde(default)]
+    pub eval: Option<f64>,
+    /// Moves until mate, negative when Black mates.
+    #[serde(default)]
+    pub mate: Option<i32>,
+    #[serde(default)]
+    pub depth: Option<u32>,
+    /// Chance of winning for White in percent.
+    #[serde(default)]
+    pub win_chance: Option<f64>,
+    /// The expected line after the move, in long algebraic notation.
+    #[serde(rename = "continuationArr", default)]
+    pub continuation: Vec<String>,
+    /// A description of the move, or the error message.
+    #[serde(default)]
+    pub text: Option<String>,
+    /// "move", "bestmove" or "info", "error" when the request failed.
+    #[serde(rename = "type", default)]
+    pub kind: Option<String>,
+}
+
+impl NextMove {
+    pub fn get_move(&self) -> Option<Move> {
+        if let Some(lan) = &self.lan {
+            return lan.parse().ok();
+        }
+        let promotion = self.promotion.as_deref().unwrap_or("");
+        format!("{}{}{}", self.from, self.to, promotion).parse().ok()
+    }
+
+    /// The evaluation from the point of view of `to_move`.
+    pub fn score(&self, to_move: Color) -> Option<Score> {
+        let sign = if to_move == Color::White { 1 } else { -1 };
+        if let Some(mate) = self.mate {
+            return Some(Score::Mate(mate * sign));
+        }
+        self.eval.map(|eval| Score::Centipawns((eval * 100.0).round() as i32 * sign))
+    }
+
+    /// The continuation as moves, up to the first one that does not parse.
+    pub fn continuation_moves(&self) -> Vec<Move> {
+        self.continuation.iter().map_while(|mv| mv.parse().ok()).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StockfishConfig {
+    pub base_url: String,
+    /// Per request, including reading the response.
+    pub timeout: Duration,
+    /// Attempts after the first one for failures that can be retried.
+    pub retries: u32,
+    /// Wait before the first retry, doubled for every further one.
+    pub backoff: Duration,
+    /// Search depth, capped at `MAX_DEPTH`. The service picks one when `None`.
+    pub depth: Option<u32>,
+    /// Number of lines the service analyses.
+    pub variants: Option<u32>,
 }
+
+impl Default for StockfishConfig {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.into(),
+            timeout: Duration::from_secs(10),
+            retries: 2,
+            backoff: Duration::from_millis(500),
+            depth: None,
+            variants: None,
+        }
+    }
+}
+
 pub struct StockfishAPI {
     client: Client,
-    base_url: String,
+    config: StockfishConfig,
+}
+
+impl Default for StockfishAPI {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StockfishAPI {
     pub fn new() -> Self {
-        Self {
-            client: Client::new(),
-            base_url: "https://chess-api.com/v1".into(),
-        }
-    }
-
-    pub fn get(&self, fen: &str) -> Result<NextMove, &str>{
-        let json_string = json!({
-            "fen": fen
-        });
-        let response = self.client.post(self.base_url.as_str()).json(&json_string).send();
-        match response {
-            Ok(resp) => {
-                let result: NextMove = resp.json().unwrap();
-                Ok(result)
-            },
-            Err(e) => {
-                return Err("Invalid response from stockfish");
+        Self::with_config(StockfishConfig::default())
+    }
+
+    pub fn with_config(config: StockfishConfig) -> Self {
+        let client = Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .unwrap_or_else(|_| Client::new());
+        Self { client, config }
+    }
+
+    pub fn config(&self) -> &StockfishConfig {
+        &self.config
+    }
+
+    /// Asks for the best move in the position, searched to the configured depth.
+    pub fn get(&self, fen: &str) -> Result<NextMove, StockfishErr> {
+        self.get_with_depth(fen, self.config.depth)
+    }
+
+    /// Like `get`, with the depth given for this request.
+    pub fn get_with_depth(&self, fen: &str, depth: Option<u32>) -> Result<NextMove, StockfishErr> {
+        let mut request = json!({ "fen": fen });
+        if let Some(depth) = depth {
+            request["depth"] = depth.min(MAX_DEPTH).into();
+        }
+        if let Some(variants) = self.config.variants {
+            request["variants"] = variants.into();
+        }
+
+        let mut backoff = self.config.backoff;
+        let mut attempt = 0;
+        loop {
+            match self.post(&request) {
+                Err(e) if e.is_retryable() && attempt < self.config.retries => {
+                    log::warn!("Chess API request failed, retrying: {}", e);
+                    thread::sleep(backoff);
+                    backoff = backoff.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => return result,
             }
         }
     }
-}
\ No newline at end of file
+
+    fn post(&self, request: &serde_json::Value) -> Result<NextMove, StockfishErr> {
+        let response = self.client.post(self.config.base_url.as_str()).json(request).send()
+            .map_err(|e| self.transport_error(e))?;
+        let status = response.status();
+        let body = response.text().map_err(|e| self.transport_error(e))?;
+        if !status.is_success() {
+            return Err(StockfishErr::Status { status: status.as_u16(), body });
+        }
+        let next: NextMove = serde_json::from_str(&body)
+            .map_err(|e| StockfishErr::InvalidResponse(e.to_string()))?;
+        if next.kind.as_deref() == Some("error") {
+            return Err(StockfishErr::Service(next.text.unwrap_or_default()));
+        }
+        Ok(next)
+    }
+
+    fn transport_error(&self, e: reqwest::Error) -> StockfishErr {
+        if e.is_timeout() {
+            StockfishErr::Timeout(self.config.timeout)
+        } else {
+            StockfishErr::Connection(e.to_string())
+        }
+    }
+}
+
+impl MoveProvider for StockfishAPI {
+    fn name(&self) -> String {
+        "Stockfish".into()
+    }
+
+    /// Only the depth limit is passed on, without one the configured depth is used.
+    fn choose_move(&mut self, game: &Game, limits: SearchLimits) -> GameResult<PlayerMove> {
+        let result = self.get_with_depth(&game.fen, limits.depth.or(self.config.depth))?;
+        let mv = result.get_move().ok_or(StockfishErr::NoMove)?;
+        Ok(PlayerMove { mv, score: result.score(game.current_player) })
+    }
+}
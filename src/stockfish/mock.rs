@@ -0,0 +1,193 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use serde_json::{json, Value};
+use crate::engine::search::{self, SearchLimits, Searcher};
+use crate::game::{Color, Game, Move};
+use crate::notation::san;
+
+/// The mock searches at most this deep, whatever depth was asked for.
+const MAX_MOCK_DEPTH: u32 = 3;
+
+/// How the mock answers one request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockResponse {
+    /// The best move of the built-in engine.
+    Engine,
+    /// The given move in long algebraic notation, legal or not.
+    Move(String),
+    /// An error status with the body.
+    Status(u16, String),
+    /// A body that is not valid JSON.
+    Malformed,
+    /// Waits before sending the inner response.
+    Slow(Duration, Box<MockResponse>),
+}
+
+/// A local stand-in for the chess-api.com service. It answers the same
+/// `{"fen": ...}` POST requests, with the scripted responses first and with
+/// `MockResponse::Engine` once the script is used up. The server stops when
+/// the value is dropped.
+pub struct MockChessApi {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<Value>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl MockChessApi {
+    /// Starts the mock on a free local port.
+    pub fn start(script: Vec<MockResponse>) -> io::Result<Self> {
+        Self::bind("127.0.0.1:0", script)
+    }
+
+    pub fn bind(addr: impl ToSocketAddrs, script: Vec<MockResponse>) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let recorded = requests.clone();
+        let stopped = stop.clone();
+        let mut script = VecDeque::from(script);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                // Responses are taken in the order the connections come in,
+                // a slow one does not hold up the next.
+                let response = script.pop_front().unwrap_or(MockResponse::Engine);
+                let recorded = recorded.clone();
+                thread::spawn(move || {
+                    if let Err(e) = handle(stream, response, &recorded) {
+                        log::warn!("Mock chess API could not answer: {}", e);
+                    }
+                });
+            }
+        });
+        Ok(Self { addr, requests, stop })
+    }
+
+    /// The URL to use as `StockfishConfig::base_url`.
+    pub fn url(&self) -> String {
+        format!("http://{}/v1", self.addr)
+    }
+
+    /// The bodies of all requests so far.
+    pub fn requests(&self) -> Vec<Value> {
+        self.requests.lock().map(|r| r.clone()).unwrap_or_default()
+    }
+
+    /// Serves until the process ends.
+    pub fn wait(self) {
+        loop {
+            thread::park();
+        }
+    }
+}
+
+impl Drop for MockChessApi {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Wakes up the accept loop so it sees the flag.
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+fn handle(stream: TcpStream, response: MockResponse, requests: &Mutex<Vec<Value>>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length") {
+            length = value.trim().parse().unwrap_or(0);
+        }
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    let request: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    if let Ok(mut requests) = requests.lock() {
+        requests.push(request.clone());
+    }
+
+    let (status, body) = answer(&request, response);
+    let mut stream = stream;
+    write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+           status, if status < 400 { "OK" } else { "Error" }, body.len(), body)?;
+    stream.flush()
+}
+
+fn answer(request: &Value, response: MockResponse) -> (u16, String) {
+    match response {
+        MockResponse::Slow(delay, response) => {
+            thread::sleep(delay);
+            answer(request, *response)
+        }
+        MockResponse::Status(status, body) => (status, body),
+        MockResponse::Malformed => (200, "{\"move\": \"e2e4\", ".into()),
+        MockResponse::Move(mv) => {
+            let game = request["fen"].as_str().and_then(|fen| Game::from_fen(fen).ok());
+            (200, move_json(game.as_ref(), &mv, None, Vec::new()).to_string())
+        }
+        MockResponse::Engine => {
+            let Some(game) = request["fen"].as_str().and_then(|fen| Game::from_fen(fen).ok()) else {
+                return (200, json!({ "type": "error", "text": "Invalid FEN" }).to_string());
+            };
+            let depth = request["depth"].as_u64().unwrap_or(MAX_MOCK_DEPTH as u64) as u32;
+            let limits = SearchLimits {
+                depth: Some(depth.clamp(1, MAX_MOCK_DEPTH)),
+                movetime: Some(Duration::from_millis(500)),
+                nodes: None,
+            };
+            let result = Searcher::new().search(&game, limits);
+            let Some(best) = result.best_move else {
+                return (200, json!({ "type": "error", "text": "No legal move" }).to_string());
+            };
+            let pv = result.pv.iter().skip(1).map(|mv| mv.to_string()).collect();
+            let mut body = move_json(Some(&game), &best.to_string(), Some(result.score), pv);
+            body["depth"] = result.depth.into();
+            (200, body.to_string())
+        }
+    }
+}
+
+/// A response shaped like the service's, `score` is from the point of view of
+/// the player to move.
+fn move_json(game: Option<&Game>, lan: &str, score: Option<i32>, continuation: Vec<String>) -> Value {
+    let san = game.zip(lan.parse::<Move>().ok()).and_then(|(g, mv)| san::to_san(g, mv).ok());
+    let white_to_move = game.is_none_or(|g| g.current_player == Color::White);
+    let sign = if white_to_move { 1 } else { -1 };
+    let (eval, mate) = match score {
+        Some(score) => match search::mate_in(score) {
+            Some(moves) => (Value::Null, json!(moves * sign)),
+            None => (json!(score as f64 * sign as f64 / 100.0), Value::Null),
+        },
+        None => (Value::Null, Value::Null),
+    };
+    json!({
+        "type": "bestmove",
+        "text": format!("Move {}", lan),
+        "fen": game.map(|g| g.fen.clone()),
+        "move": lan,
+        "lan": lan,
+        "san": san,
+        "from": lan.get(0..2).unwrap_or(""),
+        "to": lan.get(2..4).unwrap_or(""),
+        "promotion": lan.get(4..5),
+        "eval": eval,
+        "mate": mate,
+        "continuationArr": continuation,
+        "turn": if white_to_move { "w" } else { "b" },
+    })
+}
@@ -1,6 +1,5 @@
 use crate::game::errors::GameErr;
-use crate::game::{Color, Game, PieceType};
-use crate::ruleengine;
+use crate::game::Game;
 #[test]
 fn bishop_non_diagonal_illegal_requires_redo_same_turn() {
     let mut g = Game::new();
@@ -0,0 +1,157 @@
+use crate::game::bitboard::{self, Bitboards};
+use crate::game::{Color, Game, Move, Piece, PieceType, Square};
+use crate::ruleengine;
+
+fn set(squares: &[Square]) -> u64 {
+    squares.iter().fold(0, |acc, &s| acc | bitboard::bit(s))
+}
+
+#[test]
+fn knight_and_king_attack_tables() {
+    assert_eq!(bitboard::knight_attacks(Square::A1), set(&[Square::B3, Square::C2]));
+    assert_eq!(bitboard::knight_attacks(Square::D4).count_ones(), 8);
+    assert_eq!(bitboard::king_attacks(Square::H8), set(&[Square::G8, Square::G7, Square::H7]));
+    assert_eq!(bitboard::king_attacks(Square::E4).count_ones(), 8);
+}
+
+#[test]
+fn pawn_attacks_do_not_wrap_around_the_board() {
+    assert_eq!(bitboard::pawn_attacks(Square::A2, Color::White), set(&[Square::B3]));
+    assert_eq!(bitboard::pawn_attacks(Square::H7, Color::Black), set(&[Square::G6]));
+    assert_eq!(bitboard::pawn_attacks(Square::E4, Color::Black), set(&[Square::D3, Square::F3]));
+}
+
+#[test]
+fn sliding_attacks_stop_at_the_first_blocker() {
+    let occupied = set(&[Square::D6, Square::B4, Square::F2]);
+    assert_eq!(
+        bitboard::rook_attacks(Square::D4, occupied),
+        set(&[Square::D5, Square::D6, Square::D3, Square::D2, Square::D1,
+              Square::C4, Square::B4, Square::E4, Square::F4, Square::G4, Square::H4])
+    );
+    assert_eq!(
+        bitboard::bishop_attacks(Square::D4, occupied),
+        set(&[Square::E5, Square::F6, Square::G7, Square::H8, Square::C5, Square::B6, Square::A7,
+              Square::C3, Square::B2, Square::A1, Square::E3, Square::F2])
+    );
+}
+
+#[test]
+fn bitboards_follow_the_game() {
+    let mut g = Game::new();
+    assert_eq!(g.bitboards().occupied().count_ones(), 32);
+    assert_eq!(g.bitboards().pieces(Color::White, PieceType::Pawn), 0xff00);
+
+    g.move_piece(('e', 2), ('e', 4)).unwrap();
+    g.move_piece(('d', 7), ('d', 5)).unwrap();
+    g.move_piece(('e', 4), ('d', 5)).unwrap();
+    assert_eq!(*g.bitboards(), Bitboards::from_board(g.board()));
+    assert_eq!(g.bitboards().occupied().count_ones(), 31);
+    assert_eq!(g.bitboards().piece_at(Square::D5), Some(Piece { color: Color::White, piece_type: PieceType::Pawn }));
+
+    g.undo().unwrap();
+    assert_eq!(*g.bitboards(), Bitboards::from_board(g.board()));
+    assert_eq!(g.bitboards().occupied().count_ones(), 32);
+}
+
+#[test]
+fn placing_pieces_keeps_the_bitboards_in_step() {
+    let mut g = Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let rook = Piece { color: Color::White, piece_type: PieceType::Rook };
+    g.put_piece(Square::A1, rook).unwrap();
+    assert_eq!(*g.bitboards(), Bitboards::from_board(g.board()));
+    assert_eq!(g.fen, "4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+    assert_eq!(g.zobrist_key(), Game::from_fen(&g.fen).unwrap().zobrist_key());
+    assert!(g.legal_moves().contains(&Move::new(Square::A1, Square::A8)));
+
+    assert_eq!(g.remove_piece("a1"), Ok(Some(rook)));
+    assert_eq!(g.remove_piece("a1"), Ok(None));
+    assert_eq!(*g.bitboards(), Bitboards::from_board(g.board()));
+    assert_eq!(g.fen, "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    assert_eq!(g.repetition_count(), 1);
+}
+
+#[test]
+fn undo_restores_the_bitboards() {
+    // Castling, en passant and promotions with and without capture.
+    for fen in [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    ] {
+        let mut g = Game::from_fen(fen).unwrap();
+        let before = *g.bitboards();
+        for mv in g.legal_moves() {
+            g.make_move(mv).unwrap();
+            g.undo().unwrap();
+            assert_eq!(*g.bitboards(), before, "{} {}", fen, mv);
+        }
+    }
+}
+
+#[test]
+fn attackers_of_a_square() {
+    let g = Game::from_fen("4k3/8/8/3r4/8/5N2/1B6/4K3 w - - 0 1").unwrap();
+    assert!(g.bitboards().is_attacked(Square::E5, Color::White));
+    assert!(!g.bitboards().is_attacked(Square::D5, Color::White));
+    assert_eq!(g.bitboards().attackers(Square::D4, Color::White), set(&[Square::B2, Square::F3]));
+    assert!(g.bitboards().is_attacked(Square::D1, Color::Black));
+    assert!(!g.bitboards().is_in_check(Color::White));
+}
+
+/// Whether the rule sets allow the move, with the king left out of check.
+fn allowed_by_rule_sets(game: &Game, mv: Move) -> bool {
+    let Some(piece) = game.board()[mv.from.index()].filter(|p| p.color == game.current_player) else {
+        return false;
+    };
+    let castling = ruleengine::is_castling_move(game, mv.from, mv.to, game.current_player);
+    let allowed = match castling {
+        "-" => ruleengine::is_allowed_move(game, mv.from, mv.to, game.current_player).is_ok(),
+        _ => ruleengine::check_castling(game, castling).is_ok(),
+    };
+    let is_promotion = piece.piece_type == PieceType::Pawn && (mv.to.rank() == 1 || mv.to.rank() == 8);
+    let mut after = *game.bitboards();
+    after.apply(mv, castling);
+    allowed && is_promotion == mv.promotion.is_some() && !after.is_in_check(game.current_player)
+}
+
+/// Every move the rule sets allow, found by trying all from and to squares.
+fn brute_force_moves(game: &Game) -> Vec<Move> {
+    let mut moves = Vec::new();
+    for from in Square::all() {
+        for to in Square::all() {
+            if allowed_by_rule_sets(game, Move::new(from, to)) {
+                moves.push(Move::new(from, to));
+            }
+            for piece_type in ruleengine::PROMOTION_PIECES {
+                let mv = Move::with_promotion(from, to, piece_type);
+                if allowed_by_rule_sets(game, mv) {
+                    moves.push(mv);
+                }
+            }
+        }
+    }
+    moves
+}
+
+#[test]
+fn generator_agrees_with_the_rule_sets() {
+    let positions = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "8/8/8/2k5/3Pp3/8/8/4K2B b - d3 0 1",
+    ];
+    for fen in positions {
+        let g = Game::from_fen(fen).unwrap();
+        let mut generated = ruleengine::legal_moves(&g);
+        let mut expected = brute_force_moves(&g);
+        let key = |m: &Move| (m.from, m.to, m.promotion.map(bitboard::piece_index));
+        generated.sort_by_key(key);
+        expected.sort_by_key(key);
+        assert_eq!(generated, expected, "{}", fen);
+    }
+}
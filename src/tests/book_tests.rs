@@ -0,0 +1,139 @@
+use std::env;
+use std::fs;
+use crate::engine::book::{decode_move, encode_move, polyglot_key, BookBuilder, BookChoice, BookEntry, BookPlayer, OpeningBook};
+use crate::engine::player::{MoveProvider, PlayerMove};
+use crate::engine::search::SearchLimits;
+use crate::game::{Game, Move, PieceType, Square};
+use crate::game::errors::{GameErr, GameResult};
+
+fn play(g: &mut Game, moves: &[&str]) {
+    for m in moves {
+        g.make_move(m.parse().unwrap()).unwrap();
+    }
+}
+
+fn entry(game: &Game, mv: &str, weight: u16) -> BookEntry {
+    BookEntry { key: polyglot_key(game), raw_move: encode_move(game, mv.parse().unwrap()), weight, learn: 0 }
+}
+
+struct Fixed(Move);
+
+impl MoveProvider for Fixed {
+    fn name(&self) -> String {
+        "Fixed".into()
+    }
+
+    fn choose_move(&mut self, _game: &Game, _limits: SearchLimits) -> GameResult<PlayerMove> {
+        Ok(PlayerMove::new(self.0))
+    }
+}
+
+#[test]
+fn keys_match_the_polyglot_reference_positions() {
+    let lines: [(&[&str], u64); 9] = [
+        (&[], 0x463b96181691fc9c),
+        (&["e2e4"], 0x823c9b50fd114196),
+        (&["e2e4", "d7d5"], 0x0756b94461c50fb0),
+        (&["e2e4", "d7d5", "e4e5"], 0x662fafb965db29d4),
+        (&["e2e4", "d7d5", "e4e5", "f7f5"], 0x22a48b5a8e47ff78),
+        (&["e2e4", "d7d5", "e4e5", "f7f5", "e1e2"], 0x652a607ca3f242c1),
+        (&["e2e4", "d7d5", "e4e5", "f7f5", "e1e2", "e8f7"], 0x00fdd303c946bdd9),
+        (&["a2a4", "b7b5", "h2h4", "b5b4", "c2c4"], 0x3c8123ea7b067637),
+        (&["a2a4", "b7b5", "h2h4", "b5b4", "c2c4", "b4c3", "a1a3"], 0x5c3f9b829b279560),
+    ];
+    for (moves, key) in lines {
+        let mut g = Game::new();
+        play(&mut g, moves);
+        assert_eq!(polyglot_key(&g), key, "after {:?}", moves);
+    }
+}
+
+#[test]
+fn castling_is_encoded_as_king_takes_rook() {
+    let g = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let short = Move::new(Square::E1, Square::G1);
+    let long = Move::new(Square::E1, Square::C1);
+    assert_eq!(encode_move(&g, short), (Square::E1.index() << 6 | Square::H1.index()) as u16);
+    assert_eq!(encode_move(&g, long), (Square::E1.index() << 6 | Square::A1.index()) as u16);
+    assert_eq!(decode_move(&g, encode_move(&g, short)), Some(short));
+    assert_eq!(decode_move(&g, encode_move(&g, long)), Some(long));
+}
+
+#[test]
+fn promotions_round_trip_and_illegal_moves_are_rejected() {
+    let g = Game::from_fen("8/P6k/8/8/8/8/8/K7 w - - 0 1").unwrap();
+    let mv = Move::with_promotion(Square::A7, Square::A8, PieceType::Knight);
+    assert_eq!(encode_move(&g, mv) >> 12, 1);
+    assert_eq!(decode_move(&g, encode_move(&g, mv)), Some(mv));
+    assert_eq!(decode_move(&g, encode_move(&g, Move::new(Square::A1, Square::A3))), None);
+}
+
+#[test]
+fn books_round_trip_through_the_file_format() {
+    let mut g = Game::new();
+    let mut entries = vec![entry(&g, "e2e4", 10), entry(&g, "d2d4", 30)];
+    play(&mut g, &["e2e4"]);
+    entries.push(entry(&g, "c7c5", 5));
+    let book = OpeningBook::from_entries(entries);
+
+    let path = env::temp_dir().join(format!("chess-book-test-{}.bin", std::process::id()));
+    book.save(&path).unwrap();
+    assert_eq!(fs::metadata(&path).unwrap().len(), 3 * 16);
+    let read = OpeningBook::open(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(read, book);
+    assert!(read.entries().is_sorted_by_key(|e| e.key));
+    assert_eq!(read.lookup(polyglot_key(&Game::new())).len(), 2);
+
+    assert!(matches!(OpeningBook::from_bytes(&[0; 17]), Err(GameErr::InvalidBook(_))));
+}
+
+#[test]
+fn choices_follow_the_weights() {
+    let g = Game::new();
+    let book = OpeningBook::from_entries(vec![entry(&g, "e2e4", 10), entry(&g, "d2d4", 30)]);
+    let d4 = Move::new(Square::D2, Square::D4);
+    let e4 = Move::new(Square::E2, Square::E4);
+    let moves: Vec<Move> = book.moves(&g).iter().map(|m| m.mv).collect();
+    assert_eq!(moves, vec![d4, e4]);
+
+    assert_eq!(book.choose(&g, BookChoice::Best, 39).unwrap().mv, d4);
+    assert_eq!(book.choose(&g, BookChoice::WeightedRandom, 29).unwrap().mv, d4);
+    assert_eq!(book.choose(&g, BookChoice::WeightedRandom, 30).unwrap().mv, e4);
+    assert_eq!(book.choose(&g, BookChoice::WeightedRandom, 69).unwrap().mv, d4);
+    assert_eq!(book.choose(&Game::from_fen("8/8/8/8/8/8/8/K6k w - - 0 1").unwrap(), BookChoice::Best, 0), None);
+}
+
+#[test]
+fn builder_weights_moves_by_result() {
+    let pgn = "[Result \"1-0\"]\n\n1. e4 e5 2. Nf3 1-0\n\n\
+               [Result \"1/2-1/2\"]\n\n1. e4 c5 1/2-1/2\n\n\
+               [Result \"0-1\"]\n\n1. d4 d5 0-1\n";
+    let mut builder = BookBuilder::new(Some(2));
+    builder.add_pgn(pgn).unwrap();
+    let book = builder.build();
+
+    let mut g = Game::new();
+    let weights: Vec<(Move, u16)> = book.moves(&g).iter().map(|m| (m.mv, m.weight)).collect();
+    assert_eq!(weights, vec![(Move::new(Square::E2, Square::E4), 3), (Move::new(Square::D2, Square::D4), 0)]);
+    play(&mut g, &["e2e4"]);
+    let weights: Vec<(Move, u16)> = book.moves(&g).iter().map(|m| (m.mv, m.weight)).collect();
+    assert_eq!(weights, vec![(Move::new(Square::C7, Square::C5), 1), (Move::new(Square::E7, Square::E5), 0)]);
+    // Only two plies of each game are used.
+    play(&mut g, &["e7e5"]);
+    assert!(book.moves(&g).is_empty());
+}
+
+#[test]
+fn book_player_leaves_the_book_to_the_inner_player() {
+    let mut g = Game::new();
+    let book = OpeningBook::from_entries(vec![entry(&g, "e2e4", 1)]);
+    let fallback = Move::new(Square::G8, Square::F6);
+    let mut player = BookPlayer::with_seed(book, Box::new(Fixed(fallback)), BookChoice::WeightedRandom, 7);
+    assert_eq!(player.name(), "Fixed");
+
+    let chosen = player.choose_move(&g, SearchLimits::default()).unwrap();
+    assert_eq!(chosen, PlayerMove::new(Move::new(Square::E2, Square::E4)));
+    play(&mut g, &["e2e4"]);
+    assert_eq!(player.choose_move(&g, SearchLimits::default()).unwrap().mv, fallback);
+}
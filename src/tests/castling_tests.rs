@@ -1,4 +1,6 @@
-use crate::game::Game;
+use crate::game::errors::GameErr;
+use crate::game::{Game, Move, Square};
+use crate::ruleengine;
 
 /// At start, both sides can castle both sides.
 #[test]
@@ -110,3 +112,98 @@ fn rights_do_not_return_if_rook_moves_back() {
     assert_eq!(g.white_can_castle, "K"); // still no 'Q'
     assert_eq!(g.black_can_castle, "kq");
 }
+
+/// White castles kingside, the rook jumps from h1 to f1.
+#[test]
+fn white_castles_kingside() {
+    let mut g = Game::from_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1").unwrap();
+    g.move_piece(('e', 1), ('g', 1)).unwrap();
+
+    assert_eq!(g.fen, "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R4RK1 b kq - 1 1");
+    assert_eq!(g.score_white, 0);
+}
+
+/// White castles queenside, the rook jumps from a1 to d1.
+#[test]
+fn white_castles_queenside() {
+    let mut g = Game::from_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1").unwrap();
+    g.move_piece(('e', 1), ('c', 1)).unwrap();
+
+    assert_eq!(g.fen, "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/2KR3R b kq - 1 1");
+}
+
+/// Black can castle on both wings as well.
+#[test]
+fn black_castles_on_both_wings() {
+    let mut g = Game::from_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R b KQkq - 0 1").unwrap();
+    g.move_piece(('e', 8), ('g', 8)).unwrap();
+    assert_eq!(g.fen, "r4rk1/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQ - 1 2");
+
+    let mut g = Game::from_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R b KQkq - 0 1").unwrap();
+    g.move_piece(('e', 8), ('c', 8)).unwrap();
+    assert_eq!(g.fen, "2kr3r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQ - 1 2");
+}
+
+/// Every square between king and rook must be empty, also b1 on the queenside.
+#[test]
+fn castling_path_must_be_empty() {
+    let mut g = Game::new();
+    assert_eq!(g.move_piece(('e', 1), ('g', 1)), Err(GameErr::CastlingPathBlocked));
+
+    let mut g = Game::from_fen("r3k2r/8/8/8/8/8/8/RN2K2R w KQkq - 0 1").unwrap();
+    assert_eq!(g.move_piece(('e', 1), ('c', 1)), Err(GameErr::CastlingPathBlocked));
+}
+
+/// Castling needs the right, and the rook must still be on its corner.
+#[test]
+fn castling_needs_the_right_and_the_rook() {
+    let mut g = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Qkq - 0 1").unwrap();
+    assert_eq!(g.move_piece(('e', 1), ('g', 1)), Err(GameErr::CastlingNotAllowed));
+
+    let mut g = Game::from_fen("r3k2r/8/8/8/8/8/8/4K2R w KQkq - 0 1").unwrap();
+    assert_eq!(g.move_piece(('e', 1), ('c', 1)), Err(GameErr::CastlingNotAllowed));
+}
+
+/// The king may not castle out of, through or into check.
+#[test]
+fn castling_and_attacked_squares() {
+    // Rook on e8 gives check.
+    let mut g = Game::from_fen("4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert_eq!(g.move_piece(('e', 1), ('g', 1)), Err(GameErr::CastlingOutOfCheck));
+
+    // Rook on f8 covers f1.
+    let mut g = Game::from_fen("5rk1/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert_eq!(g.move_piece(('e', 1), ('g', 1)), Err(GameErr::CastlingThroughCheck));
+    g.move_piece(('e', 1), ('c', 1)).unwrap();
+
+    // Rook on c8 covers c1.
+    let mut g = Game::from_fen("2r3k1/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert_eq!(g.move_piece(('e', 1), ('c', 1)), Err(GameErr::CastlingIntoCheck));
+    g.move_piece(('e', 1), ('g', 1)).unwrap();
+}
+
+/// On the queenside the rook may pass an attacked b1.
+#[test]
+fn queenside_castling_allows_attacked_b_square() {
+    let mut g = Game::from_fen("1r4k1/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+    g.move_piece(('e', 1), ('c', 1)).unwrap();
+}
+
+/// Capturing a rook on its corner removes the opponent's right on that side.
+#[test]
+fn capturing_a_rook_removes_the_right() {
+    let mut g = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    g.move_piece(('h', 1), ('h', 8)).unwrap();
+
+    assert_eq!(g.white_can_castle, "Q");
+    assert_eq!(g.black_can_castle, "q");
+}
+
+/// Castling moves are part of the legal moves.
+#[test]
+fn castling_moves_are_legal_moves() {
+    let g = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let moves = ruleengine::legal_moves_from(&g, Square::E1);
+    assert!(moves.contains(&Move::new(Square::E1, Square::G1)));
+    assert!(moves.contains(&Move::new(Square::E1, Square::C1)));
+}
@@ -0,0 +1,97 @@
+use crate::game::errors::GameErr;
+use crate::game::{Game, GameStatus};
+
+#[test]
+fn new_game_is_ongoing() {
+    let g = Game::new();
+    assert_eq!(g.status(), GameStatus::Ongoing);
+}
+
+#[test]
+fn check_must_be_resolved() {
+    let mut g = Game::new();
+    g.move_piece(('e', 2), ('e', 4)).unwrap(); // W
+    g.move_piece(('d', 7), ('d', 5)).unwrap(); // B
+    g.move_piece(('f', 1), ('b', 5)).unwrap(); // W Bb5+
+    assert_eq!(g.status(), GameStatus::Check);
+
+    // a6 does nothing about the check.
+    assert_eq!(
+        g.move_piece(('a', 7), ('a', 6)), // B
+        Err(GameErr::KingInCheck)
+    );
+
+    // Still Black's turn, c6 blocks the check.
+    g.move_piece(('c', 7), ('c', 6)).unwrap(); // B
+    assert_eq!(g.status(), GameStatus::Ongoing);
+}
+
+#[test]
+fn king_cannot_move_into_check() {
+    let mut g = Game::new();
+    g.move_piece(('e', 2), ('e', 4)).unwrap(); // W
+    g.move_piece(('f', 7), ('f', 5)).unwrap(); // B
+    g.move_piece(('d', 1), ('h', 5)).unwrap(); // W Qh5+
+
+    // f7 is still covered by the queen on h5.
+    assert_eq!(
+        g.move_piece(('e', 8), ('f', 7)), // B
+        Err(GameErr::KingInCheck)
+    );
+    g.move_piece(('g', 7), ('g', 6)).unwrap(); // B
+}
+
+#[test]
+fn pinned_piece_cannot_move() {
+    let mut g = Game::new();
+    g.move_piece(('e', 2), ('e', 4)).unwrap(); // W
+    g.move_piece(('d', 7), ('d', 6)).unwrap(); // B
+    g.move_piece(('d', 2), ('d', 4)).unwrap(); // W
+    g.move_piece(('e', 8), ('d', 7)).unwrap(); // B
+    g.move_piece(('d', 4), ('d', 5)).unwrap(); // W
+    g.move_piece(('g', 8), ('f', 6)).unwrap(); // B
+    g.move_piece(('f', 1), ('b', 5)).unwrap(); // W Bb5+
+    g.move_piece(('c', 7), ('c', 6)).unwrap(); // B
+    g.move_piece(('g', 1), ('f', 3)).unwrap(); // W
+
+    // c6 is pinned by the bishop on b5.
+    assert_eq!(
+        g.move_piece(('c', 6), ('c', 5)), // B
+        Err(GameErr::KingInCheck)
+    );
+}
+
+#[test]
+fn fools_mate_is_checkmate() {
+    let mut g = Game::new();
+    g.move_piece(('f', 2), ('f', 3)).unwrap(); // W
+    g.move_piece(('e', 7), ('e', 5)).unwrap(); // B
+    g.move_piece(('g', 2), ('g', 4)).unwrap(); // W
+    g.move_piece(('d', 8), ('h', 4)).unwrap(); // B Qh4#
+
+    assert_eq!(g.status(), GameStatus::Checkmate);
+    assert!(g.status().is_over());
+}
+
+#[test]
+fn loyds_ten_move_stalemate() {
+    let mut g = Game::new();
+    let moves = [
+        (('e', 2), ('e', 3)), (('a', 7), ('a', 5)),
+        (('d', 1), ('h', 5)), (('a', 8), ('a', 6)),
+        (('h', 5), ('a', 5)), (('h', 7), ('h', 5)),
+        (('h', 2), ('h', 4)), (('a', 6), ('h', 6)),
+        (('a', 5), ('c', 7)), (('f', 7), ('f', 6)),
+        (('c', 7), ('d', 7)), (('e', 8), ('f', 7)),
+        (('d', 7), ('b', 7)), (('d', 8), ('d', 3)),
+        (('b', 7), ('b', 8)), (('d', 3), ('h', 7)),
+        (('b', 8), ('c', 8)), (('f', 7), ('g', 6)),
+        (('c', 8), ('e', 6)),
+    ];
+    for (from, to) in moves {
+        g.move_piece(from, to).unwrap();
+    }
+
+    assert_eq!(g.status(), GameStatus::Stalemate);
+    assert!(g.status().is_over());
+}
@@ -0,0 +1,104 @@
+use crate::game::{DrawReason, Game, GameStatus};
+
+fn shuffle_knights(g: &mut Game) {
+    g.move_piece(('g', 1), ('f', 3)).unwrap(); // W
+    g.move_piece(('g', 8), ('f', 6)).unwrap(); // B
+    g.move_piece(('f', 3), ('g', 1)).unwrap(); // W
+    g.move_piece(('f', 6), ('g', 8)).unwrap(); // B
+}
+
+#[test]
+fn threefold_repetition_is_a_draw() {
+    let mut g = Game::new();
+    shuffle_knights(&mut g);
+    assert_eq!(g.repetition_count(), 2);
+    assert_eq!(g.status(), GameStatus::Ongoing);
+
+    shuffle_knights(&mut g);
+    assert_eq!(g.repetition_count(), 3);
+    assert_eq!(g.status(), GameStatus::Draw(DrawReason::ThreefoldRepetition));
+    assert!(DrawReason::ThreefoldRepetition.is_claimable());
+}
+
+#[test]
+fn fivefold_repetition_is_a_draw() {
+    let mut g = Game::new();
+    for _ in 0..4 {
+        shuffle_knights(&mut g);
+    }
+    assert_eq!(g.repetition_count(), 5);
+    assert_eq!(g.status(), GameStatus::Draw(DrawReason::FivefoldRepetition));
+    assert!(!DrawReason::FivefoldRepetition.is_claimable());
+}
+
+#[test]
+fn en_passant_square_only_counts_when_capture_is_possible() {
+    // After e4 no black pawn can take on e3, so the position equals the one
+    // after Kd8 Nf3 Ke8 Ng1 even though the FEN showed e3 the first time.
+    let mut g = Game::from_fen("4k3/8/8/8/8/8/4P3/4K1N1 w - - 0 1").unwrap();
+    g.move_piece(('e', 2), ('e', 4)).unwrap();
+    g.move_piece(('e', 8), ('d', 8)).unwrap();
+    g.move_piece(('g', 1), ('f', 3)).unwrap();
+    g.move_piece(('d', 8), ('e', 8)).unwrap();
+    g.move_piece(('f', 3), ('g', 1)).unwrap();
+    assert_eq!(g.repetition_count(), 2);
+
+    // Here d4 could take on e3, so the positions differ.
+    let mut g = Game::from_fen("4k3/8/8/8/3p4/8/4P3/4K1N1 w - - 0 1").unwrap();
+    g.move_piece(('e', 2), ('e', 4)).unwrap();
+    g.move_piece(('e', 8), ('d', 8)).unwrap();
+    g.move_piece(('g', 1), ('f', 3)).unwrap();
+    g.move_piece(('d', 8), ('e', 8)).unwrap();
+    g.move_piece(('f', 3), ('g', 1)).unwrap();
+    assert_eq!(g.repetition_count(), 1);
+}
+
+#[test]
+fn fifty_move_rule() {
+    let mut g = Game::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 99 80").unwrap();
+    assert_eq!(g.status(), GameStatus::Ongoing);
+    g.move_piece(('a', 1), ('a', 2)).unwrap();
+    assert_eq!(g.half_time_moves, 100);
+    assert_eq!(g.status(), GameStatus::Draw(DrawReason::FiftyMoveRule));
+}
+
+#[test]
+fn seventy_five_move_rule() {
+    let mut g = Game::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 149 80").unwrap();
+    g.move_piece(('a', 1), ('a', 2)).unwrap();
+    assert_eq!(g.status(), GameStatus::Draw(DrawReason::SeventyFiveMoveRule));
+}
+
+#[test]
+fn checkmate_beats_the_move_rules() {
+    let mut g = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 149 80").unwrap();
+    g.move_piece(('a', 1), ('a', 8)).unwrap();
+    assert_eq!(g.status(), GameStatus::Checkmate);
+}
+
+#[test]
+fn insufficient_material() {
+    let drawn = [
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1N2K3 b - - 0 1",
+        "2b1k3/8/8/8/8/8/8/3BK3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/B1B1K3 w - - 0 1",
+    ];
+    for fen in drawn {
+        let g = Game::from_fen(fen).unwrap();
+        assert_eq!(g.status(), GameStatus::Draw(DrawReason::InsufficientMaterial), "{}", fen);
+    }
+
+    let playable = [
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+        "1b2k3/8/8/8/8/8/8/3BK3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1",
+    ];
+    for fen in playable {
+        let g = Game::from_fen(fen).unwrap();
+        assert_eq!(g.status(), GameStatus::Ongoing, "{}", fen);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::game::errors::GameErr;
+use crate::game::{Game, Move, Square};
+use crate::ruleengine;
+
+#[test]
+fn white_captures_en_passant() {
+    let mut g = Game::new();
+    g.move_piece(('e', 2), ('e', 4)).unwrap(); // W
+    g.move_piece(('a', 7), ('a', 6)).unwrap(); // B
+    g.move_piece(('e', 4), ('e', 5)).unwrap(); // W
+    g.move_piece(('d', 7), ('d', 5)).unwrap(); // B
+    assert_eq!(g.en_passant_target(), Some(Square::D6));
+
+    g.move_piece(('e', 5), ('d', 6)).unwrap(); // W exd6 e.p.
+    assert!(ruleengine::get_piece_at_pos(g.board(), Square::D5).is_none());
+    assert_eq!(g.score_white, 1);
+    assert_eq!(g.half_time_moves, 0);
+    assert_eq!(g.fen, "rnbqkbnr/1pp1pppp/p2P4/8/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3");
+}
+
+#[test]
+fn black_captures_en_passant() {
+    let mut g = Game::new();
+    g.move_piece(('a', 2), ('a', 3)).unwrap(); // W
+    g.move_piece(('d', 7), ('d', 5)).unwrap(); // B
+    g.move_piece(('a', 3), ('a', 4)).unwrap(); // W
+    g.move_piece(('d', 5), ('d', 4)).unwrap(); // B
+    g.move_piece(('c', 2), ('c', 4)).unwrap(); // W
+    assert_eq!(g.fen, "rnbqkbnr/ppp1pppp/8/8/P1Pp4/8/1P1PPPPP/RNBQKBNR b KQkq c3 0 3");
+
+    g.move_piece(('d', 4), ('c', 3)).unwrap(); // B dxc3 e.p.
+    assert!(ruleengine::get_piece_at_pos(g.board(), Square::C4).is_none());
+    assert_eq!(g.score_black, 1);
+}
+
+#[test]
+fn en_passant_expires_after_one_move() {
+    let mut g = Game::new();
+    g.move_piece(('e', 2), ('e', 4)).unwrap(); // W
+    g.move_piece(('a', 7), ('a', 6)).unwrap(); // B
+    g.move_piece(('e', 4), ('e', 5)).unwrap(); // W
+    g.move_piece(('d', 7), ('d', 5)).unwrap(); // B
+    g.move_piece(('g', 1), ('f', 3)).unwrap(); // W
+    g.move_piece(('a', 6), ('a', 5)).unwrap(); // B
+    assert_eq!(g.en_passant_target(), None);
+
+    assert_eq!(
+        g.move_piece(('e', 5), ('d', 6)), // W
+        Err(GameErr::IllegalPawnMove)
+    );
+}
+
+#[test]
+fn en_passant_is_a_legal_move() {
+    let g = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    let moves = ruleengine::legal_moves_from(&g, Square::E5);
+    assert!(moves.contains(&Move::new(Square::E5, Square::D6)));
+}
+
+#[test]
+fn en_passant_cannot_expose_the_king() {
+    // Both pawns leave the fifth rank, opening it for the rook on h5.
+    let mut g = Game::from_fen("8/8/8/K2pP2r/8/8/8/4k3 w - d6 0 1").unwrap();
+    assert_eq!(
+        g.move_piece(('e', 5), ('d', 6)),
+        Err(GameErr::KingInCheck)
+    );
+}
@@ -0,0 +1,63 @@
+use crate::engine::eval::{evaluate, evaluate_breakdown, MAX_PHASE};
+use crate::game::Game;
+
+#[test]
+fn test_start_position_is_balanced() {
+    let g = Game::new();
+    let e = evaluate_breakdown(&g);
+    assert_eq!(e.phase, MAX_PHASE);
+    assert_eq!(e.total(), 0);
+    assert_eq!(evaluate(&g), 0);
+}
+
+#[test]
+fn test_score_is_from_side_to_move() {
+    let white = Game::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+    let black = Game::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+    assert!(evaluate(&white) > 800);
+    assert_eq!(evaluate(&black), -evaluate(&white));
+}
+
+#[test]
+fn test_mirrored_position_scores_the_same() {
+    let g = Game::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4").unwrap();
+    let mirrored = Game::from_fen("rnbqk2r/pppp1ppp/5n2/2b1p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R b KQkq - 4 4").unwrap();
+    assert_eq!(evaluate(&g), evaluate(&mirrored));
+}
+
+#[test]
+fn test_phase_drops_to_zero_without_pieces() {
+    let g = Game::from_fen("4k3/pppp4/8/8/8/8/4PPPP/4K3 w - - 0 1").unwrap();
+    assert_eq!(evaluate_breakdown(&g).phase, 0);
+}
+
+#[test]
+fn test_pawn_structure() {
+    // White has doubled, isolated pawns on the c-file, Black a healthy chain.
+    let g = Game::from_fen("4k3/5ppp/8/8/8/2P5/2P5/4K3 w - - 0 1").unwrap();
+    assert!(evaluate_breakdown(&g).pawn_structure < 0);
+
+    // A passed pawn on the seventh rank is worth more than one on the third.
+    let far = Game::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let near = Game::from_fen("4k3/8/8/8/8/P7/8/4K3 w - - 0 1").unwrap();
+    assert!(evaluate_breakdown(&far).pawn_structure > evaluate_breakdown(&near).pawn_structure);
+}
+
+#[test]
+fn test_bishop_pair() {
+    let g = Game::from_fen("2b1k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap();
+    assert!(evaluate_breakdown(&g).bishop_pair > 0);
+}
+
+#[test]
+fn test_king_safety_and_mobility() {
+    // Castled king behind its pawns against a king that has walked out.
+    let g = Game::from_fen("rnbq1rk1/ppppbppp/5n2/4p3/4P3/3P1N2/PPP2PPP/RNBQ1RK1 w - - 0 1").unwrap();
+    let exposed = Game::from_fen("rnbq1rk1/ppppbppp/5n2/4p3/4P3/3PKN2/PPP2PPP/RNBQ1R2 w - - 0 1").unwrap();
+    assert!(evaluate_breakdown(&g).king_safety > evaluate_breakdown(&exposed).king_safety);
+
+    // A knight in the centre reaches more squares than one in the corner.
+    let centre = Game::from_fen("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1").unwrap();
+    let corner = Game::from_fen("4k3/8/8/8/8/8/8/N3K3 w - - 0 1").unwrap();
+    assert!(evaluate_breakdown(&centre).mobility > evaluate_breakdown(&corner).mobility);
+}
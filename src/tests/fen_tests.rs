@@ -0,0 +1,99 @@
+use crate::game::errors::GameErr;
+use crate::game::{Color, Game, GameStatus, PieceType, Square};
+use crate::ruleengine;
+
+#[test]
+fn start_position_fen_matches_new_game() {
+    let g = Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    let new = Game::new();
+    assert_eq!(g.board(), new.board());
+    assert_eq!(g.fen, new.fen);
+    assert_eq!(g.white_can_castle, "KQ");
+    assert_eq!(g.black_can_castle, "kq");
+}
+
+#[test]
+fn fen_fields_are_parsed() {
+    let g = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b Qk - 12 40").unwrap();
+    assert_eq!(g.current_player, Color::Black);
+    assert_eq!(g.white_can_castle, "Q");
+    assert_eq!(g.black_can_castle, "k");
+    assert_eq!(g.half_time_moves, 12);
+    assert_eq!(g.fen, "r3k2r/8/8/8/8/8/8/R3K2R b Qk - 12 40");
+
+    let rook = ruleengine::get_piece_at_pos(g.board(), Square::H8).unwrap();
+    assert_eq!(rook.color, Color::Black);
+    assert_eq!(rook.piece_type, PieceType::Rook);
+}
+
+#[test]
+fn castling_rights_are_normalised() {
+    let g = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w qQkK - 0 1").unwrap();
+    assert_eq!(g.white_can_castle, "KQ");
+    assert_eq!(g.black_can_castle, "kq");
+}
+
+#[test]
+fn move_counters_can_be_left_out() {
+    let g = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - -").unwrap();
+    assert_eq!(g.half_time_moves, 0);
+    assert_eq!(g.fen, "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+}
+
+#[test]
+fn fullmove_number_increases_after_black_moves() {
+    let mut g = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 7").unwrap();
+    g.move_piece(('a', 1), ('a', 2)).unwrap();
+    assert_eq!(g.fen, "4k3/8/8/8/8/8/R7/4K3 b - - 1 7");
+    g.move_piece(('e', 8), ('d', 8)).unwrap();
+    assert_eq!(g.fen, "3k4/8/8/8/8/8/R7/4K3 w - - 2 8");
+}
+
+#[test]
+fn game_can_be_continued_from_fen() {
+    // Black to move and mate with the queen.
+    let mut g = Game::from_fen("6k1/8/8/8/8/8/5PPP/3q2K1 w - - 0 1").unwrap();
+    assert_eq!(g.status(), GameStatus::Checkmate);
+
+    g = Game::from_fen("6k1/8/8/8/8/8/5PPP/q5K1 b - - 0 1").unwrap();
+    g.move_piece(('a', 1), ('e', 1)).unwrap();
+    assert_eq!(g.status(), GameStatus::Checkmate);
+}
+
+#[test]
+fn invalid_fens_are_rejected() {
+    let invalid = [
+        "",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/ppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KKkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - -1 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0",
+        "rnbqqbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "Pnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    ];
+    for fen in invalid {
+        assert!(
+            matches!(Game::from_fen(fen), Err(GameErr::InvalidFen(_))),
+            "expected '{}' to be rejected",
+            fen
+        );
+    }
+}
+
+#[test]
+fn parse_errors_describe_the_problem() {
+    assert_eq!(
+        Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 extra").unwrap_err(),
+        GameErr::InvalidFen("expected 4 to 6 fields, found 7".into())
+    );
+    assert_eq!(
+        Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e9 0 1").unwrap_err(),
+        GameErr::InvalidFen("invalid en passant target 'e9'".into())
+    );
+}
@@ -0,0 +1,46 @@
+#!/bin/sh
+# A scripted UCI engine for the client tests. It always plays the move set
+# with `setoption name Move value <move>` and reports the last position it
+# was sent. With `setoption name Hang value true` it stops answering `go`,
+# `stop` and `quit`, with `setoption name Delay value <seconds>` it answers
+# `stop` late.
+move=e2e4
+position=none
+hang=false
+delay=0
+while read -r line; do
+    case "$line" in
+        uci)
+            echo "id name Fake Engine 1.0"
+            echo "id author The Tests"
+            echo "option name Move type string default e2e4"
+            echo "uciok"
+            ;;
+        isready) echo "readyok" ;;
+        "setoption name Move value "*) move=${line#setoption name Move value } ;;
+        "setoption name Hang value "*) hang=${line#setoption name Hang value } ;;
+        "setoption name Delay value "*) delay=${line#setoption name Delay value } ;;
+        position*) position=$line ;;
+        "go infinite")
+            [ "$hang" = true ] && continue
+            echo "info string $position"
+            echo "info depth 1 score cp 5 nodes 20 pv $move"
+            ;;
+        go*)
+            [ "$hang" = true ] && continue
+            echo "info string $position"
+            echo "info depth 1 seldepth 2 score cp 12 nodes 20 nps 2000 time 10 pv $move"
+            echo "info depth 2 score mate 3 lowerbound nodes 400 time 100 pv $move e7e5"
+            echo "bestmove $move ponder e7e5"
+            ;;
+        stop)
+            [ "$hang" = true ] && continue
+            sleep "$delay"
+            echo "bestmove $move"
+            ;;
+        quit)
+            [ "$hang" = true ] && continue
+            exit 0
+            ;;
+    esac
+done
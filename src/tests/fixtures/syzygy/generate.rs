@@ -0,0 +1,792 @@
+// Writes the Syzygy tables of this directory: KQvK, KRvK, KBvK, KNvK and
+// KPvK. The endings are solved by iterating over all positions, and the
+// tables are written in the file format of the Syzygy probing code: the
+// position index without board symmetries, recursive pairing, canonical
+// Huffman codes, sparse indexes and DTZ value maps.
+//
+// It is not part of the crate, run it from this directory with
+//     rustc --edition 2024 -O generate.rs -o /tmp/generate && /tmp/generate
+use std::collections::HashMap;
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Piece {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+}
+
+impl Piece {
+    fn letter(self) -> char {
+        match self {
+            Piece::Pawn => 'P',
+            Piece::Knight => 'N',
+            Piece::Bishop => 'B',
+            Piece::Rook => 'R',
+            Piece::Queen => 'Q',
+        }
+    }
+
+    /// The piece code of the file format, for white.
+    fn code(self) -> u8 {
+        match self {
+            Piece::Pawn => 1,
+            Piece::Knight => 2,
+            Piece::Bishop => 3,
+            Piece::Rook => 4,
+            Piece::Queen => 5,
+        }
+    }
+}
+
+const WHITE_KING: u8 = 6;
+const BLACK_KING: u8 = 14;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+const FLAG_STM: u8 = 1;
+const FLAG_MAPPED: u8 = 2;
+const FLAG_WIN_PLIES: u8 = 4;
+const FLAG_LOSS_PLIES: u8 = 8;
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+fn file(square: usize) -> usize {
+    square & 7
+}
+
+fn rank(square: usize) -> usize {
+    square >> 3
+}
+
+fn adjacent(a: usize, b: usize) -> bool {
+    a != b && file(a).abs_diff(file(b)) <= 1 && rank(a).abs_diff(rank(b)) <= 1
+}
+
+fn step(square: usize, df: i32, dr: i32) -> Option<usize> {
+    let (f, r) = (file(square) as i32 + df, rank(square) as i32 + dr);
+    ((0..8).contains(&f) && (0..8).contains(&r)).then(|| (r * 8 + f) as usize)
+}
+
+const KING_STEPS: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+const KNIGHT_STEPS: [(i32, i32); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const ROOK_STEPS: [(i32, i32); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+const BISHOP_STEPS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+fn king_targets(square: usize) -> Vec<usize> {
+    KING_STEPS.iter().filter_map(|&(df, dr)| step(square, df, dr)).collect()
+}
+
+/// Squares attacked by a white piece, rays end on the first blocker.
+fn attacks(piece: Piece, square: usize, blockers: &[usize]) -> Vec<usize> {
+    let slide = |steps: &[(i32, i32)]| {
+        let mut targets = Vec::new();
+        for &(df, dr) in steps {
+            let mut at = square;
+            while let Some(next) = step(at, df, dr) {
+                targets.push(next);
+                if blockers.contains(&next) {
+                    break;
+                }
+                at = next;
+            }
+        }
+        targets
+    };
+    match piece {
+        Piece::Pawn => [(-1, 1), (1, 1)].iter().filter_map(|&(df, dr)| step(square, df, dr)).collect(),
+        Piece::Knight => KNIGHT_STEPS.iter().filter_map(|&(df, dr)| step(square, df, dr)).collect(),
+        Piece::Bishop => slide(&BISHOP_STEPS),
+        Piece::Rook => slide(&ROOK_STEPS),
+        Piece::Queen => {
+            let mut targets = slide(&ROOK_STEPS);
+            targets.extend(slide(&BISHOP_STEPS));
+            targets
+        }
+    }
+}
+
+/// A position of the white king, the white piece and the black king, 0 for
+/// white to move and 1 for black.
+fn key(stm: usize, wk: usize, x: usize, bk: usize) -> usize {
+    (((stm * 64) + wk) * 64 + x) * 64 + bk
+}
+
+fn unkey(key: usize) -> (usize, usize, usize, usize) {
+    (key >> 18, (key >> 12) & 63, (key >> 6) & 63, key & 63)
+}
+
+const POSITIONS: usize = 2 * 64 * 64 * 64;
+
+fn black_in_check(piece: Piece, wk: usize, x: usize, bk: usize) -> bool {
+    attacks(piece, x, &[wk, bk]).contains(&bk)
+}
+
+fn legal(piece: Piece, stm: usize, wk: usize, x: usize, bk: usize) -> bool {
+    let distinct = wk != x && wk != bk && x != bk;
+    let pawn_rank = piece != Piece::Pawn || (1..7).contains(&rank(x));
+    distinct && pawn_rank && !adjacent(wk, bk) && (stm == 1 || !black_in_check(piece, wk, x, bk))
+}
+
+/// Result for the side to move, 2 for a win, 0 for a draw and -2 for a loss,
+/// and the plies to the next capture, pawn move or mate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Outcome {
+    wdl: i8,
+    dtz: u16,
+}
+
+enum Move {
+    /// A king or piece move inside the table.
+    Quiet(usize),
+    /// A capture or pawn move, with the result for the side to move after it.
+    Zeroing(i8),
+}
+
+struct Solved {
+    values: Vec<Option<Outcome>>,
+}
+
+fn moves(piece: Piece, key: usize, values: &[Option<Outcome>], promoted: &HashMap<Piece, Solved>) -> Vec<Move> {
+    let (stm, wk, x, bk) = unkey(key);
+    let mut moves = Vec::new();
+    if stm == 0 {
+        for to in king_targets(wk) {
+            if to != x && !adjacent(to, bk) {
+                moves.push(Move::Quiet(self::key(1, to, x, bk)));
+            }
+        }
+        if piece == Piece::Pawn {
+            let one = x + 8;
+            if one != wk && one != bk {
+                if rank(one) == 7 {
+                    for promotion in [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight] {
+                        let child = promoted[&promotion].values[self::key(1, wk, one, bk)].expect("promotion not solved");
+                        moves.push(Move::Zeroing(child.wdl));
+                    }
+                } else {
+                    moves.push(Move::Zeroing(values[self::key(1, wk, one, bk)].expect("push not solved").wdl));
+                }
+                let two = x + 16;
+                if rank(x) == 1 && two != wk && two != bk {
+                    moves.push(Move::Zeroing(values[self::key(1, wk, two, bk)].expect("push not solved").wdl));
+                }
+            }
+        } else {
+            for to in attacks(piece, x, &[wk, bk]) {
+                if to != wk && to != bk {
+                    moves.push(Move::Quiet(self::key(1, wk, to, bk)));
+                }
+            }
+        }
+    } else {
+        for to in king_targets(bk) {
+            if adjacent(to, wk) {
+                continue;
+            }
+            if to == x {
+                // Bare kings.
+                moves.push(Move::Zeroing(0));
+            } else if !attacks(piece, x, &[wk]).contains(&to) {
+                moves.push(Move::Quiet(self::key(0, wk, x, to)));
+            }
+        }
+    }
+    moves
+}
+
+fn solve(piece: Piece, promoted: &HashMap<Piece, Solved>) -> Solved {
+    let mut values: Vec<Option<Outcome>> = vec![None; POSITIONS];
+    // With a pawn the positions are solved rank by rank from the seventh,
+    // pawn moves lead to positions solved before.
+    let slices: Vec<Vec<usize>> = if piece == Piece::Pawn {
+        (1..7).rev().map(|r| (0..POSITIONS).filter(|&k| rank(unkey(k).2) == r).collect()).collect()
+    } else {
+        vec![(0..POSITIONS).collect()]
+    };
+    for slice in slices {
+        let slice: Vec<usize> = slice.into_iter().filter(|&k| {
+            let (stm, wk, x, bk) = unkey(k);
+            legal(piece, stm, wk, x, bk)
+        }).collect();
+        for &k in &slice {
+            if moves(piece, k, &values, promoted).is_empty() {
+                let (stm, wk, x, bk) = unkey(k);
+                let mate = stm == 1 && black_in_check(piece, wk, x, bk);
+                values[k] = Some(Outcome { wdl: if mate { -2 } else { 0 }, dtz: 0 });
+            }
+        }
+        // Wins in k plies follow a loss in k - 1 plies, a loss in k plies has
+        // only moves to wins and the longest one in k - 1 plies.
+        for k in 1.. {
+            let mut changed = false;
+            for &pos in &slice {
+                if values[pos].is_some() {
+                    continue;
+                }
+                let win = moves(piece, pos, &values, promoted).iter().any(|mv| match *mv {
+                    Move::Zeroing(wdl) => k == 1 && wdl == -2,
+                    Move::Quiet(child) => values[child].is_some_and(|o| o.wdl == -2 && o.dtz as usize + 1 == k),
+                });
+                if win {
+                    values[pos] = Some(Outcome { wdl: 2, dtz: k as u16 });
+                    changed = true;
+                }
+            }
+            for &pos in &slice {
+                if values[pos].is_some() {
+                    continue;
+                }
+                let longest = moves(piece, pos, &values, promoted).iter().try_fold(0, |longest, mv| match *mv {
+                    Move::Zeroing(2) => Some(longest.max(1)),
+                    Move::Quiet(child) => match values[child] {
+                        Some(o) if o.wdl == 2 => Some(longest.max(o.dtz as usize + 1)),
+                        _ => None,
+                    },
+                    Move::Zeroing(_) => None,
+                });
+                if longest == Some(k) {
+                    values[pos] = Some(Outcome { wdl: -2, dtz: k as u16 });
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        for &pos in &slice {
+            values[pos].get_or_insert(Outcome { wdl: 0, dtz: 0 });
+        }
+    }
+    Solved { values }
+}
+
+/// Tables with only draws, the piece cannot mate.
+fn drawn() -> Solved {
+    Solved { values: vec![Some(Outcome { wdl: 0, dtz: 0 }); POSITIONS] }
+}
+
+struct Indexes {
+    a1d1d4: [usize; 64],
+    b1h1h7: [usize; 64],
+}
+
+impl Indexes {
+    fn new() -> Self {
+        let mut ix = Indexes { a1d1d4: [0; 64], b1h1h7: [0; 64] };
+        let off = |s: usize| rank(s) as i32 - file(s) as i32;
+        let mut code = 0;
+        for s in 0..64 {
+            if off(s) < 0 {
+                ix.b1h1h7[s] = code;
+                code += 1;
+            }
+        }
+        let mut code = 0;
+        let mut diagonal = Vec::new();
+        for r in 0..4 {
+            for f in 0..4 {
+                let s = r * 8 + f;
+                if off(s) < 0 {
+                    ix.a1d1d4[s] = code;
+                    code += 1;
+                } else if off(s) == 0 {
+                    diagonal.push(s);
+                }
+            }
+        }
+        for s in diagonal {
+            ix.a1d1d4[s] = code;
+            code += 1;
+        }
+        ix
+    }
+}
+
+fn off_diagonal(square: usize) -> i32 {
+    rank(square) as i32 - file(square) as i32
+}
+
+/// The index of three unique pieces without pawns, in table order.
+fn unique_index(ix: &Indexes, mut sq: [usize; 3]) -> usize {
+    if file(sq[0]) > 3 {
+        sq.iter_mut().for_each(|s| *s ^= 7);
+    }
+    if rank(sq[0]) > 3 {
+        sq.iter_mut().for_each(|s| *s ^= 56);
+    }
+    if let Some(i) = (0..3).find(|&i| off_diagonal(sq[i]) != 0) && off_diagonal(sq[i]) > 0 {
+        sq.iter_mut().for_each(|s| *s = ((*s >> 3) | (*s << 3)) & 63);
+    }
+    let adjust1 = usize::from(sq[1] > sq[0]);
+    let adjust2 = usize::from(sq[2] > sq[0]) + usize::from(sq[2] > sq[1]);
+    if off_diagonal(sq[0]) != 0 {
+        (ix.a1d1d4[sq[0]] * 63 + (sq[1] - adjust1)) * 62 + sq[2] - adjust2
+    } else if off_diagonal(sq[1]) != 0 {
+        (6 * 63 + rank(sq[0]) * 28 + ix.b1h1h7[sq[1]]) * 62 + sq[2] - adjust2
+    } else if off_diagonal(sq[2]) != 0 {
+        6 * 63 * 62 + 4 * 28 * 62 + rank(sq[0]) * 7 * 28 + (rank(sq[1]) - adjust1) * 28 + ix.b1h1h7[sq[2]]
+    } else {
+        6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + rank(sq[0]) * 7 * 6 + (rank(sq[1]) - adjust1) * 6 + (rank(sq[2]) - adjust2)
+    }
+}
+
+/// The index of a pawn and two kings, the pawn first. `order` is where the
+/// pawn is in the order of multiplication, and the index size is returned too.
+fn pawn_index(mut sq: [usize; 3], order: usize) -> (usize, usize) {
+    if file(sq[0]) > 3 {
+        sq.iter_mut().for_each(|s| *s ^= 7);
+    }
+    let lead = rank(sq[0]) - 1;
+    let free1 = sq[1] - usize::from(sq[1] > sq[0]);
+    let free2 = sq[2] - usize::from(sq[2] > sq[0]) - usize::from(sq[2] > sq[1]);
+    let (mut idx, mut factor, mut next, mut free_squares) = (0, 1, 1, 63);
+    let others = [free1, free2];
+    for k in 0..3 {
+        if k == order {
+            idx += lead * factor;
+            factor *= 6;
+        } else {
+            idx += others[next - 1] * factor;
+            factor *= free_squares;
+            free_squares -= 1;
+            next += 1;
+        }
+    }
+    (idx, factor)
+}
+
+/// How a table part lists its pieces, see `Part::index`.
+#[derive(Clone)]
+struct Layout {
+    /// Piece codes in table order.
+    pieces: [u8; 3],
+    order: usize,
+}
+
+impl Layout {
+    /// The index of the position and the size of the part.
+    fn index(&self, ix: &Indexes, piece: Piece, wk: usize, x: usize, bk: usize) -> (usize, usize) {
+        let square = |code: u8| if code == WHITE_KING { wk } else if code == BLACK_KING { bk } else { x };
+        let sq = self.pieces.map(square);
+        if piece == Piece::Pawn {
+            pawn_index(sq, self.order)
+        } else {
+            (unique_index(ix, sq), 31_332)
+        }
+    }
+}
+
+/// A compressed table part.
+struct Part {
+    flags: u8,
+    single: Option<u8>,
+    block_bits: u8,
+    span_bits: u8,
+    max_len: u8,
+    min_len: u8,
+    lowest: Vec<u16>,
+    /// Left and right of every symbol, a value and 0xfff for leaves.
+    btree: Vec<(u16, u16)>,
+    sparse: Vec<(u32, u16)>,
+    block_lengths: Vec<u16>,
+    data: Vec<u8>,
+}
+
+fn single(flags: u8, value: u8) -> Part {
+    Part {
+        flags: flags | FLAG_SINGLE_VALUE,
+        single: Some(value),
+        block_bits: 0,
+        span_bits: 0,
+        max_len: 0,
+        min_len: 0,
+        lowest: Vec::new(),
+        btree: Vec::new(),
+        sparse: Vec::new(),
+        block_lengths: Vec::new(),
+        data: Vec::new(),
+    }
+}
+
+/// Code lengths of a Huffman code for the frequencies, at most 32 bits.
+fn code_lengths(freq: &[u64]) -> Vec<u8> {
+    let used: Vec<usize> = (0..freq.len()).filter(|&s| freq[s] > 0).collect();
+    let mut lengths = vec![0u8; freq.len()];
+    if used.len() == 1 {
+        lengths[used[0]] = 1;
+        return lengths;
+    }
+    let mut weights: Vec<u64> = freq.to_vec();
+    loop {
+        // Nodes are (weight, symbols below).
+        let mut nodes: Vec<(u64, Vec<usize>)> = used.iter().map(|&s| (weights[s], vec![s])).collect();
+        let mut depth = vec![0u8; freq.len()];
+        while nodes.len() > 1 {
+            nodes.sort_by(|a, b| b.0.cmp(&a.0));
+            let (w1, s1) = nodes.pop().unwrap();
+            let (w2, s2) = nodes.pop().unwrap();
+            for &s in s1.iter().chain(&s2) {
+                depth[s] += 1;
+            }
+            nodes.push((w1 + w2, [s1, s2].concat()));
+        }
+        if used.iter().all(|&s| depth[s] <= 32) {
+            for &s in &used {
+                lengths[s] = depth[s];
+            }
+            return lengths;
+        }
+        weights.iter_mut().for_each(|w| *w = w.div_ceil(2));
+    }
+}
+
+/// Compresses the values of a part, `None` are positions that are never probed.
+fn compress(flags: u8, values: &[Option<u16>], block_bits: u8, span_bits: u8) -> Part {
+    let first = values.iter().flatten().next().copied().unwrap_or(0);
+    let mut last = first;
+    let filled: Vec<u16> = values.iter().map(|v| {
+        last = v.unwrap_or(last);
+        last
+    }).collect();
+    if filled.iter().all(|&v| v == first) {
+        return single(flags, first as u8);
+    }
+
+    // Leaves first, then pairs of symbols that follow each other often.
+    let mut leaves: Vec<u16> = filled.clone();
+    leaves.sort_unstable();
+    leaves.dedup();
+    let mut btree: Vec<(u16, u16)> = leaves.iter().map(|&v| (v, 0xfff)).collect();
+    let mut counts: Vec<usize> = vec![1; btree.len()];
+    let mut seq: Vec<usize> = filled.iter().map(|v| leaves.binary_search(v).unwrap()).collect();
+    while btree.len() < 400 {
+        let mut pairs: HashMap<(usize, usize), usize> = HashMap::new();
+        for w in seq.windows(2) {
+            *pairs.entry((w[0], w[1])).or_default() += 1;
+        }
+        let best = pairs.into_iter()
+            .filter(|&((a, b), _)| counts[a] + counts[b] <= 256)
+            .max_by_key(|&((a, b), n)| (n, std::cmp::Reverse((a, b))));
+        let Some(((a, b), n)) = best else {
+            break;
+        };
+        if n < 8 {
+            break;
+        }
+        let symbol = btree.len();
+        btree.push((a as u16, b as u16));
+        counts.push(counts[a] + counts[b]);
+        let mut next = Vec::with_capacity(seq.len());
+        let mut i = 0;
+        while i < seq.len() {
+            if i + 1 < seq.len() && seq[i] == a && seq[i + 1] == b {
+                next.push(symbol);
+                i += 2;
+            } else {
+                next.push(seq[i]);
+                i += 1;
+            }
+        }
+        seq = next;
+    }
+
+    // Canonical Huffman codes, the symbols are renumbered with the longest
+    // codes first and the symbols without a code last.
+    let mut freq = vec![0u64; btree.len()];
+    for &s in &seq {
+        freq[s] += 1;
+    }
+    let lengths = code_lengths(&freq);
+    let max_len = *lengths.iter().max().unwrap();
+    let min_len = *lengths.iter().filter(|&&l| l > 0).min().unwrap();
+    let mut renumbered: Vec<usize> = (0..btree.len()).collect();
+    renumbered.sort_by_key(|&s| (lengths[s] == 0, std::cmp::Reverse(lengths[s]), s));
+    let mut new_id = vec![0; btree.len()];
+    for (id, &s) in renumbered.iter().enumerate() {
+        new_id[s] = id;
+    }
+    let btree: Vec<(u16, u16)> = renumbered.iter().map(|&s| match btree[s] {
+        (value, 0xfff) => (value, 0xfff),
+        (l, r) => (new_id[l as usize] as u16, new_id[r as usize] as u16),
+    }).collect();
+    let counts: Vec<usize> = renumbered.iter().map(|&s| counts[s]).collect();
+    let lengths: Vec<u8> = renumbered.iter().map(|&s| lengths[s]).collect();
+    let seq: Vec<usize> = seq.iter().map(|&s| new_id[s]).collect();
+
+    let count_of = |len: u8| lengths.iter().filter(|&&l| l == len).count();
+    let span = (max_len - min_len + 1) as usize;
+    let mut lowest = vec![0u16; span];
+    let mut base = vec![0u64; span];
+    for i in (0..span - 1).rev() {
+        let longer = count_of(min_len + i as u8 + 1);
+        lowest[i] = lowest[i + 1] + longer as u16;
+        assert!((base[i + 1] + longer as u64) % 2 == 0, "incomplete code");
+        base[i] = (base[i + 1] + longer as u64) / 2;
+    }
+    let code = |s: usize| {
+        let i = (lengths[s] - min_len) as usize;
+        (base[i] + (s - lowest[i] as usize) as u64, lengths[s] as u32)
+    };
+
+    // Blocks of whole symbols, and a sparse index entry every span values.
+    let block_size = 1usize << block_bits;
+    let mut blocks: Vec<Vec<usize>> = vec![Vec::new()];
+    let (mut bits, mut block_values) = (0, 0);
+    for &s in &seq {
+        let (len, values) = (lengths[s] as usize, counts[s]);
+        if bits + len > block_size * 8 || block_values + values > 60_000 {
+            blocks.push(Vec::new());
+            bits = 0;
+            block_values = 0;
+        }
+        blocks.last_mut().unwrap().push(s);
+        bits += len;
+        block_values += values;
+    }
+    let mut data = Vec::new();
+    let mut block_lengths = Vec::new();
+    let mut block_starts = Vec::new();
+    let mut start = 0;
+    for block in &blocks {
+        let mut bytes = vec![0u8; block_size];
+        let mut at = 0;
+        for &s in block {
+            let (value, len) = code(s);
+            for bit in (0..len).rev() {
+                if value >> bit & 1 != 0 {
+                    bytes[at / 8] |= 0x80 >> (at % 8);
+                }
+                at += 1;
+            }
+        }
+        data.extend(bytes);
+        let values: usize = block.iter().map(|&s| counts[s]).sum();
+        block_lengths.push((values - 1) as u16);
+        block_starts.push(start);
+        start += values;
+    }
+    let size = filled.len();
+    let locate = |idx: usize| {
+        let block = block_starts.partition_point(|&s| s <= idx) - 1;
+        (block, idx - block_starts[block])
+    };
+    let span_size = 1usize << span_bits;
+    let sparse = (0..size.div_ceil(span_size)).map(|k| {
+        let mid = k * span_size + span_size / 2;
+        let (block, offset) = locate(mid.min(size - 1));
+        (block as u32, (offset + mid.saturating_sub(size - 1)) as u16)
+    }).collect();
+
+    Part {
+        flags,
+        single: None,
+        block_bits,
+        span_bits,
+        max_len,
+        min_len,
+        lowest,
+        btree,
+        sparse,
+        block_lengths,
+        data,
+    }
+}
+
+fn u16_le(out: &mut Vec<u8>, value: u16) {
+    out.extend(value.to_le_bytes());
+}
+
+/// Writes a table file: `parts` by file of the pawn, then by side to move.
+fn write(kind_magic: [u8; 4], header: u8, orders: &[u8], layouts: &[[u8; 3]], parts: &[Vec<Part>], maps: &[Option<[Vec<u8>; 4]>]) -> Vec<u8> {
+    let mut out = kind_magic.to_vec();
+    out.push(header);
+    for (file, &order) in orders.iter().enumerate() {
+        out.push(order);
+        out.extend(layouts[file]);
+    }
+    if out.len() % 2 == 1 {
+        out.push(0);
+    }
+    for file_parts in parts {
+        for part in file_parts {
+            out.push(part.flags);
+            if let Some(value) = part.single {
+                out.push(value);
+                continue;
+            }
+            out.extend([part.block_bits, part.span_bits, 0]);
+            out.extend((part.block_lengths.len() as u32).to_le_bytes());
+            out.extend([part.max_len, part.min_len]);
+            for &lowest in &part.lowest {
+                u16_le(&mut out, lowest);
+            }
+            u16_le(&mut out, part.btree.len() as u16);
+            for &(left, right) in &part.btree {
+                out.extend([left as u8, ((left >> 8) & 0xf) as u8 | ((right & 0xf) << 4) as u8, (right >> 4) as u8]);
+            }
+            if part.btree.len() % 2 == 1 {
+                out.push(0);
+            }
+        }
+    }
+    if !maps.is_empty() {
+        for map in maps.iter().flatten() {
+            for values in map {
+                out.push(values.len() as u8);
+                out.extend(values);
+            }
+        }
+        if out.len() % 2 == 1 {
+            out.push(0);
+        }
+    }
+    for part in parts.iter().flatten() {
+        for &(block, offset) in &part.sparse {
+            out.extend(block.to_le_bytes());
+            u16_le(&mut out, offset);
+        }
+    }
+    for part in parts.iter().flatten() {
+        for &length in &part.block_lengths {
+            u16_le(&mut out, length);
+        }
+    }
+    for part in parts.iter().flatten() {
+        out.resize(out.len().next_multiple_of(64), 0);
+        out.extend(&part.data);
+    }
+    out.resize(out.len().next_multiple_of(64) + 16, 0);
+    out
+}
+
+/// The stored values of a part, `None` for positions that are never probed.
+fn collect(ix: &Indexes, piece: Piece, solved: &Solved, stm: usize, layout: &Layout, file: Option<usize>,
+           value: impl Fn(Outcome) -> Option<u16>) -> Vec<Option<u16>> {
+    let mut stored: Vec<Option<u16>> = Vec::new();
+    for k in 0..POSITIONS {
+        let (s, wk, x, bk) = unkey(k);
+        if s != stm || file.is_some_and(|f| file_of_pawn(x) != f) {
+            continue;
+        }
+        let Some(outcome) = solved.values[k] else {
+            continue;
+        };
+        if !legal(piece, s, wk, x, bk) {
+            continue;
+        }
+        let (idx, size) = layout.index(ix, piece, wk, x, bk);
+        stored.resize(size, None);
+        if let Some(v) = value(outcome) {
+            assert!(stored[idx].is_none_or(|old| old == v), "{}: two values at {}", piece.letter(), idx);
+            stored[idx] = Some(v);
+        }
+    }
+    stored
+}
+
+fn file_of_pawn(square: usize) -> usize {
+    file(square).min(7 - file(square))
+}
+
+/// A DTZ value map by frequency, and the stored values turned into map entries.
+fn map_values(stored: &[Option<u16>]) -> (Vec<u8>, HashMap<u16, u16>) {
+    let mut freq: HashMap<u16, usize> = HashMap::new();
+    for v in stored.iter().flatten() {
+        *freq.entry(*v).or_default() += 1;
+    }
+    let mut values: Vec<(u16, usize)> = freq.into_iter().collect();
+    values.sort_by_key(|&(v, n)| (std::cmp::Reverse(n), v));
+    let map = values.iter().map(|&(v, _)| u8::try_from(v).unwrap()).collect();
+    let entries = values.iter().enumerate().map(|(i, &(v, _))| (v, i as u16)).collect();
+    (map, entries)
+}
+
+fn write_tables(piece: Piece, solved: &Solved, ix: &Indexes) {
+    let name = format!("K{}vK", piece.letter());
+    let pawn = piece == Piece::Pawn;
+    let code = piece.code();
+    // White and black to move list the pieces in different orders.
+    let (white_layout, black_layout, dtz_layout) = if pawn {
+        (Layout { pieces: [code, WHITE_KING, BLACK_KING], order: 0 },
+         Layout { pieces: [code, BLACK_KING, WHITE_KING], order: 2 },
+         Layout { pieces: [code, WHITE_KING, BLACK_KING], order: 1 })
+    } else if piece == Piece::Rook {
+        (Layout { pieces: [code, WHITE_KING, BLACK_KING], order: 0 },
+         Layout { pieces: [WHITE_KING, BLACK_KING, code], order: 0 },
+         Layout { pieces: [BLACK_KING, code, WHITE_KING], order: 0 })
+    } else {
+        (Layout { pieces: [WHITE_KING, code, BLACK_KING], order: 0 },
+         Layout { pieces: [BLACK_KING, code, WHITE_KING], order: 0 },
+         Layout { pieces: [WHITE_KING, code, BLACK_KING], order: 0 })
+    };
+    let files: Vec<Option<usize>> = if pawn { (0..4).map(Some).collect() } else { vec![None] };
+    let pieces_byte = |a: &Layout, b: &Layout| [0, 1, 2].map(|i| a.pieces[i] | (b.pieces[i] << 4));
+    let header = if pawn { 2 } else { 0 };
+
+    let wdl_parts: Vec<Vec<Part>> = files.iter().map(|&file| {
+        [(0, &white_layout), (1, &black_layout)].map(|(stm, layout)| {
+            let stored = collect(ix, piece, solved, stm, layout, file, |o| Some((o.wdl + 2) as u16));
+            compress(0, &stored, 6, 8)
+        }).into_iter().collect()
+    }).collect();
+    let orders = vec![(white_layout.order | (black_layout.order << 4)) as u8; files.len()];
+    let layouts = vec![pieces_byte(&white_layout, &black_layout); files.len()];
+    let wdl = write(WDL_MAGIC, header | 1, &orders, &layouts, &wdl_parts, &[]);
+    fs::write(format!("{}.rtbw", name), wdl).unwrap();
+
+    // KQvK stores white to move with wins in full moves, KRvK black to move
+    // with losses in plies and KPvK white to move with wins in plies.
+    let (stm, flags) = match piece {
+        Piece::Queen => (0, FLAG_MAPPED | FLAG_LOSS_PLIES),
+        Piece::Rook => (1, FLAG_STM | FLAG_MAPPED | FLAG_LOSS_PLIES),
+        _ => (0, FLAG_MAPPED | FLAG_WIN_PLIES | FLAG_LOSS_PLIES),
+    };
+    let mut dtz_parts = Vec::new();
+    let mut maps = Vec::new();
+    for &file in &files {
+        let drawn = (0..POSITIONS).filter(|&k| unkey(k).0 == stm).all(|k| solved.values[k].is_none_or(|o| o.wdl == 0));
+        if drawn {
+            dtz_parts.push(vec![single(0, 0)]);
+            continue;
+        }
+        let in_moves = flags & FLAG_WIN_PLIES == 0;
+        let win = collect(ix, piece, solved, stm, &dtz_layout, file, |o| (o.wdl == 2).then(|| {
+            assert!(!in_moves || o.dtz % 2 == 1, "even distance stored in moves");
+            if in_moves { (o.dtz - 1) / 2 } else { o.dtz - 1 }
+        }));
+        let loss = collect(ix, piece, solved, stm, &dtz_layout, file, |o| (o.wdl == -2 && o.dtz > 0).then(|| o.dtz - 1));
+        let (win_map, win_entries) = map_values(&win);
+        let (loss_map, loss_entries) = map_values(&loss);
+        let stored: Vec<Option<u16>> = win.iter().zip(&loss).map(|(w, l)| {
+            w.map(|v| win_entries[&v]).or(l.map(|v| loss_entries[&v]))
+        }).collect();
+        dtz_parts.push(vec![compress(flags, &stored, 7, 10)]);
+        maps.push(Some([win_map, loss_map, Vec::new(), Vec::new()]));
+    }
+    let orders = vec![dtz_layout.order as u8 | (dtz_layout.order << 4) as u8; files.len()];
+    let layouts = vec![pieces_byte(&dtz_layout, &dtz_layout); files.len()];
+    let dtz = write(DTZ_MAGIC, header, &orders, &layouts, &dtz_parts, &maps);
+    fs::write(format!("{}.rtbz", name), dtz).unwrap();
+}
+
+fn main() {
+    let ix = Indexes::new();
+    let mut solved = HashMap::new();
+    for piece in [Piece::Queen, Piece::Rook] {
+        solved.insert(piece, solve(piece, &HashMap::new()));
+    }
+    solved.insert(Piece::Bishop, drawn());
+    solved.insert(Piece::Knight, drawn());
+    let pawn = solve(Piece::Pawn, &solved);
+    solved.insert(Piece::Pawn, pawn);
+    for piece in [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight, Piece::Pawn] {
+        let values = &solved[&piece].values;
+        let longest = values.iter().flatten().map(|o| o.dtz).max().unwrap();
+        let wins = values.iter().flatten().filter(|o| o.wdl == 2).count();
+        println!("K{}vK: {} wins, longest distance {} plies", piece.letter(), wins, longest);
+        write_tables(piece, &solved[&piece], &ix);
+    }
+}
@@ -10,5 +10,5 @@ fn test_fen_starts_with_initial_position() {
 fn test_fen_is_correct() {
     let mut g = Game::new();
     g.move_piece(('d', 2), ('d', 4)).unwrap();
-    //assert_eq!(g.fen, "rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 1");
+    assert_eq!(g.fen, "rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 1");
 }
\ No newline at end of file
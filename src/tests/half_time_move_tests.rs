@@ -1,4 +1,4 @@
-use crate::{Game};
+use crate::game::Game;
 use crate::game::errors::GameErr;
 
 #[test]
@@ -1,11 +1,10 @@
 
-use crate::game::{Color, Game, PieceType};
+use crate::game::{Color, Game, PieceType, Square};
 use crate::ruleengine;
-use super::*;
 #[test]
 fn get_pos_a_1_is_white_rook() {
     let g = Game::new();
-    let piece_at_pos = ruleengine::get_piece_at_pos(&g.board, ('a', 1));
+    let piece_at_pos = ruleengine::get_piece_at_pos(g.board(), Square::A1);
     assert_eq!(piece_at_pos.unwrap().color, Color::White);
     assert_eq!(piece_at_pos.unwrap().piece_type, PieceType::Rook);
 }
@@ -13,7 +12,7 @@ fn get_pos_a_1_is_white_rook() {
 #[test]
 fn get_pos_a_2_is_white_pawn() {
     let g = Game::new();
-    let piece_at_pos = ruleengine::get_piece_at_pos(&g.board, ('a', 2));
+    let piece_at_pos = ruleengine::get_piece_at_pos(g.board(), Square::A2);
     assert_eq!(piece_at_pos.unwrap().color, Color::White);
     assert_eq!(piece_at_pos.unwrap().piece_type, PieceType::Pawn);
 }
@@ -21,7 +20,7 @@ fn get_pos_a_2_is_white_pawn() {
 #[test]
 fn get_pos_a_7_is_black_pawn() {
     let g = Game::new();
-    let piece_at_pos = ruleengine::get_piece_at_pos(&g.board, ('a', 7));
+    let piece_at_pos = ruleengine::get_piece_at_pos(g.board(), Square::A7);
     assert_eq!(piece_at_pos.unwrap().color, Color::Black);
     assert_eq!(piece_at_pos.unwrap().piece_type, PieceType::Pawn);
 }
@@ -29,14 +28,14 @@ fn get_pos_a_7_is_black_pawn() {
 #[test]
 fn get_pos_a_6_is_empty() {
     let g = Game::new();
-    let piece_at_pos = ruleengine::get_piece_at_pos(&g.board, ('a', 6));
+    let piece_at_pos = ruleengine::get_piece_at_pos(g.board(), Square::A6);
     assert!(piece_at_pos.is_none());
 }
 
 #[test]
 fn get_pos_b_3_is_empty() {
     let g = Game::new();
-    let piece_at_pos = ruleengine::get_piece_at_pos(&g.board, ('b', 3));
+    let piece_at_pos = ruleengine::get_piece_at_pos(g.board(), Square::B3);
     assert!(piece_at_pos.is_none());
 }
 
@@ -0,0 +1,68 @@
+use crate::game::{Game, Move, Square};
+use crate::ruleengine;
+
+#[test]
+fn start_position_has_twenty_moves() {
+    let g = Game::new();
+    assert_eq!(ruleengine::legal_moves(&g).len(), 20);
+}
+
+#[test]
+fn knight_on_b1_has_two_moves() {
+    let g = Game::new();
+    let moves = ruleengine::legal_moves_from(&g, Square::B1);
+    assert_eq!(moves, vec![Move::new(Square::B1, Square::A3), Move::new(Square::B1, Square::C3)]);
+}
+
+#[test]
+fn no_moves_for_empty_square_or_opponent_piece() {
+    let g = Game::new();
+    assert!(ruleengine::legal_moves_from(&g, Square::E4).is_empty());
+    assert!(ruleengine::legal_moves_from(&g, Square::E7).is_empty());
+}
+
+#[test]
+fn castling_is_listed_for_the_king() {
+    let mut g = Game::new();
+    g.move_piece(('e', 2), ('e', 4)).unwrap(); // W
+    g.move_piece(('e', 7), ('e', 5)).unwrap(); // B
+    g.move_piece(('g', 1), ('f', 3)).unwrap(); // W
+    g.move_piece(('b', 8), ('c', 6)).unwrap(); // B
+    g.move_piece(('f', 1), ('c', 4)).unwrap(); // W
+    g.move_piece(('g', 8), ('f', 6)).unwrap(); // B
+
+    let moves = ruleengine::legal_moves_from(&g, Square::E1);
+    assert!(moves.contains(&Move::new(Square::E1, Square::G1)));
+    assert!(moves.contains(&Move::new(Square::E1, Square::F1)));
+    assert!(moves.contains(&Move::new(Square::E1, Square::E2)));
+}
+
+#[test]
+fn only_check_evasions_are_listed() {
+    let mut g = Game::new();
+    g.move_piece(('e', 2), ('e', 4)).unwrap(); // W
+    g.move_piece(('d', 7), ('d', 5)).unwrap(); // B
+    g.move_piece(('f', 1), ('b', 5)).unwrap(); // W Bb5+
+
+    let moves = g.legal_moves();
+    assert_eq!(moves.len(), 5);
+    for mv in [
+        Move::new(Square::C7, Square::C6),
+        Move::new(Square::B8, Square::C6),
+        Move::new(Square::B8, Square::D7),
+        Move::new(Square::C8, Square::D7),
+        Move::new(Square::D8, Square::D7),
+    ] {
+        assert!(moves.contains(&mv), "missing {}", mv);
+    }
+}
+
+#[test]
+fn checkmate_has_no_legal_moves() {
+    let mut g = Game::new();
+    g.move_piece(('f', 2), ('f', 3)).unwrap(); // W
+    g.move_piece(('e', 7), ('e', 5)).unwrap(); // B
+    g.move_piece(('g', 2), ('g', 4)).unwrap(); // W
+    g.move_piece(('d', 8), ('h', 4)).unwrap(); // B Qh4#
+    assert!(g.legal_moves().is_empty());
+}
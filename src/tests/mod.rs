@@ -8,4 +8,27 @@ mod game_tests;
 mod castling_tests;
 mod queen_tests;
 mod king_tests;
-mod half_time_move_tests;
\ No newline at end of file
+mod half_time_move_tests;
+mod check_tests;
+mod legal_moves_tests;
+mod fen_tests;
+mod en_passant_tests;
+mod promotion_tests;
+mod draw_tests;
+mod undo_tests;
+mod san_tests;
+mod pgn_tests;
+mod square_tests;
+mod bitboard_tests;
+mod perft_tests;
+mod search_tests;
+mod eval_tests;
+mod player_tests;
+mod uci_server_tests;
+#[cfg(unix)]
+mod uci_client_tests;
+mod stockfish_tests;
+mod zobrist_tests;
+mod tt_tests;
+mod book_tests;
+mod syzygy_tests;
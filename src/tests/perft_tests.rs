@@ -0,0 +1,86 @@
+use crate::game::{Game, Move, Square};
+use crate::ruleengine::perft::{divide, perft};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+const POSITION_6: &str = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+fn assert_perft(fen: &str, expected: &[u64]) {
+    let g = Game::from_fen(fen).unwrap();
+    for (depth, nodes) in expected.iter().enumerate() {
+        assert_eq!(perft(&g, depth as u32 + 1), *nodes, "{} at depth {}", fen, depth + 1);
+    }
+    // Playing and taking back every move leaves the game untouched.
+    assert_eq!(g.fen, Game::from_fen(fen).unwrap().fen);
+}
+
+#[test]
+fn perft_start_position() {
+    assert_perft("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[20, 400, 8902]);
+}
+
+#[test]
+fn perft_kiwipete() {
+    assert_perft(KIWIPETE, &[48, 2039, 97862]);
+}
+
+#[test]
+fn perft_position_3() {
+    assert_perft(POSITION_3, &[14, 191, 2812, 43238]);
+}
+
+#[test]
+fn perft_position_4() {
+    assert_perft(POSITION_4, &[6, 264, 9467]);
+}
+
+#[test]
+fn perft_position_5() {
+    assert_perft(POSITION_5, &[44, 1486, 62379]);
+}
+
+#[test]
+fn perft_position_6() {
+    assert_perft(POSITION_6, &[46, 2079, 89890]);
+}
+
+#[test]
+fn perft_zero_is_one_node() {
+    assert_eq!(perft(&Game::new(), 0), 1);
+    assert!(divide(&Game::new(), 0).is_empty());
+}
+
+#[test]
+fn divide_lists_every_move() {
+    let g = Game::from_fen(KIWIPETE).unwrap();
+    let divided = divide(&g, 2);
+    assert_eq!(divided.len(), 48);
+    assert_eq!(divided.iter().map(|(_, n)| n).sum::<u64>(), 2039);
+    assert!(divided.contains(&(Move::new(Square::E1, Square::G1), 43)));
+    assert!(divided.contains(&(Move::new(Square::D5, Square::E6), 46)));
+}
+
+// Deeper runs take a while in debug builds, run them with `cargo test --release -- --ignored`.
+
+#[test]
+#[ignore]
+fn perft_deep_start_position() {
+    assert_perft("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[20, 400, 8902, 197281, 4865609]);
+}
+
+#[test]
+#[ignore]
+fn perft_deep_kiwipete() {
+    assert_perft(KIWIPETE, &[48, 2039, 97862, 4085603]);
+}
+
+#[test]
+#[ignore]
+fn perft_deep_positions() {
+    assert_perft(POSITION_3, &[14, 191, 2812, 43238, 674624]);
+    assert_perft(POSITION_4, &[6, 264, 9467, 422333]);
+    assert_perft(POSITION_5, &[44, 1486, 62379, 2103487]);
+    assert_perft(POSITION_6, &[46, 2079, 89890, 3894594]);
+}
@@ -0,0 +1,175 @@
+use crate::game::{Game, Move, Square};
+use crate::game::errors::GameErr;
+use crate::notation::pgn::{export, parse, result_token, PgnTags};
+use crate::notation::san::parse_san;
+
+fn play(g: &mut Game, moves: &[&str]) {
+    for m in moves {
+        g.make_move(m.parse().unwrap()).unwrap();
+    }
+}
+
+#[test]
+fn empty_game_has_seven_tag_roster_and_open_result() {
+    let g = Game::new();
+    let pgn = export(&g, &PgnTags::default()).unwrap();
+    assert_eq!(
+        pgn,
+        "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n\
+         [White \"?\"]\n[Black \"?\"]\n[Result \"*\"]\n\n*\n"
+    );
+}
+
+#[test]
+fn fools_mate_is_exported_with_move_numbers_and_result() {
+    let mut g = Game::new();
+    play(&mut g, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+    let tags = PgnTags { white: "Fool".into(), black: "Mate \"the\" Fool".into(), ..PgnTags::default() };
+    let pgn = export(&g, &tags).unwrap();
+
+    assert_eq!(result_token(&g), "0-1");
+    assert!(pgn.contains("[Black \"Mate \\\"the\\\" Fool\"]\n"));
+    assert!(pgn.contains("[Result \"0-1\"]\n"));
+    assert!(!pgn.contains("[FEN"));
+    assert!(pgn.ends_with("\n\n1. f3 e5 2. g4 Qh4# 0-1\n"));
+}
+
+#[test]
+fn comments_are_written_after_their_move() {
+    let mut g = Game::new();
+    play(&mut g, &["e2e4"]);
+    g.set_comment("best by test").unwrap();
+    play(&mut g, &["e7e5", "g1f3"]);
+    g.set_comment("a {nested} comment").unwrap();
+
+    let pgn = export(&g, &PgnTags::default()).unwrap();
+    assert!(pgn.ends_with("\n1. e4 {best by test} 1... e5 2. Nf3 {a {nested) comment} *\n"));
+}
+
+#[test]
+fn setup_position_gets_fen_tag_and_black_move_number() {
+    let fen = "4k3/P7/8/8/8/8/8/4K3 b - - 0 40";
+    let mut g = Game::from_fen(fen).unwrap();
+    play(&mut g, &["e8d7"]);
+    g.make_move(Move::with_promotion(Square::A7, Square::A8, crate::game::PieceType::Queen)).unwrap();
+
+    let pgn = export(&g, &PgnTags::default()).unwrap();
+    assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/P7/8/8/8/8/8/4K3 b - - 0 40\"]\n"));
+    assert!(pgn.ends_with("\n40... Kd7 41. a8=Q *\n"));
+}
+
+#[test]
+fn long_games_are_wrapped_at_eighty_columns() {
+    let movetext = "1. e4 e5 2. Nf3 d6 3. d4 Bg4 4. dxe5 Bxf3 5. Qxf3 dxe5 6. Bc4 Nf6 7. Qb3 Qe7 \
+                    8. Nc3 c6 9. Bg5 b5 10. Nxb5 cxb5 11. Bxb5+ Nbd7 12. O-O-O Rd8 13. Rxd7 Rxd7 \
+                    14. Rd1 Qe6 15. Bxd7+ Nxd7 16. Qb8+ Nxb8 17. Rd8# 1-0";
+    let mut g = Game::new();
+    for token in movetext.split_whitespace().filter(|t| !t.ends_with('.') && *t != "1-0") {
+        let mv = parse_san(&g, token).unwrap();
+        g.make_move(mv).unwrap();
+    }
+
+    let pgn = export(&g, &PgnTags::default()).unwrap();
+    let exported = pgn.split("\n\n").nth(1).unwrap();
+    assert!(exported.lines().count() > 1);
+    assert!(exported.lines().all(|l| l.len() <= 80));
+    assert_eq!(exported.split_whitespace().collect::<Vec<_>>(), movetext.split_whitespace().collect::<Vec<_>>());
+}
+
+const TWO_GAMES: &str = r#"[Event "Team match"]
+[Site "Office"]
+[Date "2024.03.01"]
+[Round "1"]
+[White "Anna"]
+[Black "Ben \"the rook\""]
+[Result "1-0"]
+
+{Opening comment} 1. e4 e5 2. Nf3 $1 Nc6 (2... d6 3. d4 (3. Bc4 {Also fine}) 3... exd4)
+3. Bb5 a6?! ; The Morphy defence
+4. Ba4 {Main line} Nf6 1-0
+
+% An escaped line that is ignored
+[Event "Blitz"]
+[Result "1/2-1/2"]
+
+1.d4 d5 2.c4!? dxc4 1/2-1/2
+"#;
+
+#[test]
+fn multiple_games_with_tags_are_read() {
+    let games = parse(TWO_GAMES).unwrap();
+    assert_eq!(games.len(), 2);
+
+    assert_eq!(games[0].tag("Black"), Some("Ben \"the rook\""));
+    assert_eq!(games[0].tag("Date"), Some("2024.03.01"));
+    assert_eq!(games[0].result, "1-0");
+    assert_eq!(games[0].moves.len(), 8);
+    assert_eq!(games[0].game.history().len(), 8);
+
+    assert_eq!(games[1].tag("Event"), Some("Blitz"));
+    assert_eq!(games[1].result, "1/2-1/2");
+    let sans: Vec<&str> = games[1].moves.iter().map(|m| m.san.as_str()).collect();
+    assert_eq!(sans, ["d4", "d5", "c4", "dxc4"]);
+    assert_eq!(games[1].moves[2].nags, [5]);
+}
+
+#[test]
+fn comments_nags_and_variations_are_kept() {
+    let games = parse(TWO_GAMES).unwrap();
+    let moves = &games[0].moves;
+
+    assert_eq!(moves[0].starting_comment.as_deref(), Some("Opening comment"));
+    assert_eq!(moves[2].nags, [1]);
+    assert_eq!(moves[5].nags, [6]);
+    assert_eq!(moves[6].comment.as_deref(), Some("Main line"));
+    assert_eq!(moves[2].mv, Move::new(Square::G1, Square::F3));
+
+    // 2... d6 3. d4 (3. Bc4) 3... exd4 replaces 2... Nc6.
+    assert_eq!(moves[3].variations.len(), 1);
+    let variation = &moves[3].variations[0];
+    let sans: Vec<&str> = variation.iter().map(|m| m.san.as_str()).collect();
+    assert_eq!(sans, ["d6", "d4", "exd4"]);
+    assert_eq!(variation[1].variations[0][0].san, "Bc4");
+    assert_eq!(variation[1].variations[0][0].comment.as_deref(), Some("Also fine"));
+
+    // Comments are copied to the replayed game, variations are not played.
+    assert_eq!(games[0].game.history()[6].comment.as_deref(), Some("Main line"));
+    assert_eq!(games[0].game.fen, "r1bqkb1r/1ppp1ppp/p1n2n2/4p3/B3P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 2 5");
+}
+
+#[test]
+fn illegal_move_reports_game_and_ply() {
+    let text = "1. e4 e5 *\n\n1. e4 e5 2. Nf3 Nc6 3. Bb5 Nf3 *\n";
+    assert_eq!(
+        parse(text).unwrap_err(),
+        GameErr::IllegalPgnMove { game: 2, ply: 6, san: "Nf3".into() }
+    );
+
+    let text = "1. e4 e5 (1... e5 2. Ke3) *";
+    assert_eq!(
+        parse(text).unwrap_err(),
+        GameErr::IllegalPgnMove { game: 1, ply: 3, san: "Ke3".into() }
+    );
+}
+
+#[test]
+fn malformed_pgn_is_rejected() {
+    assert!(matches!(parse("1. e4 {open comment"), Err(GameErr::InvalidPgn(_))));
+    assert!(matches!(parse("1. e4 (1. d4 *"), Err(GameErr::InvalidPgn(_))));
+    assert!(matches!(parse("1. e4 ) e5 *"), Err(GameErr::InvalidPgn(_))));
+    assert!(matches!(parse("[Event Casual]\n1. e4 *"), Err(GameErr::InvalidPgn(_))));
+}
+
+#[test]
+fn exported_games_can_be_read_back() {
+    let mut g = Game::from_fen("4k3/P7/8/8/8/8/8/4K3 b - - 0 40").unwrap();
+    play(&mut g, &["e8d7"]);
+    g.set_comment("the king is too late").unwrap();
+    g.make_move(Move::with_promotion(Square::A7, Square::A8, crate::game::PieceType::Queen)).unwrap();
+
+    let games = parse(&export(&g, &PgnTags::default()).unwrap()).unwrap();
+    assert_eq!(games.len(), 1);
+    assert_eq!(games[0].game.fen, g.fen);
+    assert_eq!(games[0].moves[0].comment.as_deref(), Some("the king is too late"));
+    assert_eq!(games[0].moves[1].san, "a8=Q");
+}
@@ -0,0 +1,107 @@
+use std::io::Cursor;
+use crate::engine::player::{play_game, HumanPlayer, MoveProvider, PlayerMove, Score};
+use crate::engine::search::{SearchLimits, Searcher, MATE};
+use crate::game::errors::{GameErr, GameResult};
+use crate::game::{Game, GameStatus, Move, Square};
+use crate::stockfish::NextMove;
+use crate::uci::client::position_command;
+
+fn human(input: &str) -> HumanPlayer {
+    HumanPlayer::new("Human", Box::new(Cursor::new(input.to_string())))
+}
+
+/// Plays the given moves in order.
+struct Scripted(Vec<Move>);
+
+impl MoveProvider for Scripted {
+    fn name(&self) -> String {
+        "Scripted".into()
+    }
+
+    fn choose_move(&mut self, _game: &Game, _limits: SearchLimits) -> GameResult<PlayerMove> {
+        if self.0.is_empty() {
+            return Err(GameErr::Engine("out of moves".into()));
+        }
+        Ok(PlayerMove::new(self.0.remove(0)))
+    }
+}
+
+#[test]
+fn human_enters_san_and_coordinates() {
+    let g = Game::new();
+    let mut player = human("Nf3\n");
+    assert_eq!(player.choose_move(&g, SearchLimits::default()).unwrap().mv, Move::new(Square::G1, Square::F3));
+    let mut player = human("e2=>e4\n");
+    assert_eq!(player.choose_move(&g, SearchLimits::default()).unwrap().mv, Move::new(Square::E2, Square::E4));
+}
+
+#[test]
+fn human_is_asked_again_after_an_illegal_move() {
+    let g = Game::new();
+    let mut player = human("e2=>e5\nQh5\nd4\n");
+    assert_eq!(player.choose_move(&g, SearchLimits::default()).unwrap().mv, Move::new(Square::D2, Square::D4));
+}
+
+#[test]
+fn human_quits() {
+    let g = Game::new();
+    assert_eq!(human("quit\n").choose_move(&g, SearchLimits::default()), Err(GameErr::PlayerQuit));
+    assert_eq!(human("").choose_move(&g, SearchLimits::default()), Err(GameErr::PlayerQuit));
+}
+
+#[test]
+fn play_game_runs_to_mate() {
+    // Fool's mate.
+    let mut white = Scripted(vec![Move::new(Square::F2, Square::F3), Move::new(Square::G2, Square::G4)]);
+    let mut black = Scripted(vec![Move::new(Square::E7, Square::E5), Move::new(Square::D8, Square::H4)]);
+    let mut g = Game::new();
+    let mut played = Vec::new();
+    play_game(&mut g, &mut white, &mut black, SearchLimits::default(), |_, m| played.push(m.san.clone())).unwrap();
+    assert_eq!(g.status(), GameStatus::Checkmate);
+    assert_eq!(played, vec!["f3", "e5", "g4", "Qh4#"]);
+}
+
+#[test]
+fn play_game_stops_on_an_illegal_move() {
+    let mut white = Scripted(vec![Move::new(Square::E2, Square::E5)]);
+    let mut black = Scripted(vec![]);
+    let mut g = Game::new();
+    let result = play_game(&mut g, &mut white, &mut black, SearchLimits::default(), |_, _| {});
+    assert!(matches!(result, Err(GameErr::Engine(_))));
+    assert!(g.history().is_empty());
+}
+
+#[test]
+fn builtin_engine_against_scripted_player() {
+    let mut g = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    let mut white = Searcher::new();
+    let mut black = Scripted(vec![]);
+    play_game(&mut g, &mut white, &mut black, SearchLimits::depth(3), |_, m| {
+        assert_eq!(m.chosen.score, Some(Score::Mate(1)));
+    }).unwrap();
+    assert_eq!(g.status(), GameStatus::Checkmate);
+}
+
+#[test]
+fn score_conversions() {
+    assert_eq!(Score::from_search(MATE - 3), Score::Mate(2));
+    assert_eq!(Score::from_search(-35), Score::Centipawns(-35));
+    assert_eq!(Score::Centipawns(-35).to_string(), "-0.35");
+    assert_eq!(Score::Mate(-2).to_string(), "#-2");
+
+    let next: NextMove = serde_json::from_str(r#"{"from":"e2","to":"e4","move":"e2e4","eval":0.3}"#).unwrap();
+    assert_eq!(next.score(crate::game::Color::White), Some(Score::Centipawns(30)));
+    assert_eq!(next.score(crate::game::Color::Black), Some(Score::Centipawns(-30)));
+}
+
+#[test]
+fn position_command_lists_the_moves() {
+    let mut g = Game::new();
+    assert_eq!(position_command(&g), "position startpos");
+    g.make_move(Move::new(Square::E2, Square::E4)).unwrap();
+    g.make_move(Move::new(Square::E7, Square::E5)).unwrap();
+    assert_eq!(position_command(&g), "position startpos moves e2e4 e7e5");
+
+    let g = Game::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    assert_eq!(position_command(&g), "position fen 4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+}
@@ -0,0 +1,106 @@
+use crate::game::errors::GameErr;
+use crate::game::{Color, Game, Move, PieceType, Square};
+use crate::ruleengine;
+use crate::stockfish::NextMove;
+
+#[test]
+fn pawn_promotes_to_queen() {
+    let mut g = Game::from_fen("8/4P3/8/8/8/8/k7/4K3 w - - 3 40").unwrap();
+    g.make_move(Move::with_promotion(Square::E7, Square::E8, PieceType::Queen)).unwrap();
+
+    let queen = ruleengine::get_piece_at_pos(g.board(), Square::E8).unwrap();
+    assert_eq!(queen.piece_type, PieceType::Queen);
+    assert_eq!(queen.color, Color::White);
+    assert_eq!(g.score_white, 8);
+    assert_eq!(g.half_time_moves, 0);
+    assert_eq!(g.fen, "4Q3/8/8/8/8/8/k7/4K3 b - - 0 40");
+}
+
+#[test]
+fn pawn_can_underpromote_while_capturing() {
+    let mut g = Game::from_fen("3r4/4P3/8/8/8/8/k7/4K3 w - - 0 1").unwrap();
+    g.make_move(Move::with_promotion(Square::E7, Square::D8, PieceType::Knight)).unwrap();
+
+    let knight = ruleengine::get_piece_at_pos(g.board(), Square::D8).unwrap();
+    assert_eq!(knight.piece_type, PieceType::Knight);
+    assert_eq!(g.score_white, 5 + 2);
+}
+
+#[test]
+fn black_pawn_promotes_on_first_rank() {
+    let mut g = Game::from_fen("4k3/8/8/8/8/8/3p4/K7 b - - 0 1").unwrap();
+    g.make_move(Move::with_promotion(Square::D2, Square::D1, PieceType::Rook)).unwrap();
+
+    let rook = ruleengine::get_piece_at_pos(g.board(), Square::D1).unwrap();
+    assert_eq!(rook.piece_type, PieceType::Rook);
+    assert_eq!(rook.color, Color::Black);
+}
+
+#[test]
+fn promotion_piece_is_required() {
+    let mut g = Game::from_fen("8/4P3/8/8/8/8/k7/4K3 w - - 0 1").unwrap();
+    assert_eq!(g.move_piece(('e', 7), ('e', 8)), Err(GameErr::PromotionRequired));
+
+    // The pawn is still on e7 and it is still White's turn.
+    let pawn = ruleengine::get_piece_at_pos(g.board(), Square::E7).unwrap();
+    assert_eq!(pawn.piece_type, PieceType::Pawn);
+    assert_eq!(g.current_player, Color::White);
+}
+
+#[test]
+fn invalid_promotion_choices_are_rejected() {
+    let mut g = Game::from_fen("8/4P3/8/8/8/8/k3P3/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        g.make_move(Move::with_promotion(Square::E7, Square::E8, PieceType::King)),
+        Err(GameErr::InvalidPromotion)
+    );
+    assert_eq!(
+        g.make_move(Move::with_promotion(Square::E7, Square::E8, PieceType::Pawn)),
+        Err(GameErr::InvalidPromotion)
+    );
+    assert_eq!(
+        g.make_move(Move::with_promotion(Square::E2, Square::E3, PieceType::Queen)),
+        Err(GameErr::InvalidPromotion)
+    );
+}
+
+#[test]
+fn legal_moves_list_every_promotion_piece() {
+    let g = Game::from_fen("8/4P3/8/8/8/8/k7/4K3 w - - 0 1").unwrap();
+    let moves = ruleengine::legal_moves_from(&g, Square::E7);
+    assert_eq!(moves.len(), 4);
+    for piece_type in [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight] {
+        assert!(moves.contains(&Move::with_promotion(Square::E7, Square::E8, piece_type)));
+    }
+}
+
+#[test]
+fn moves_with_promotion_suffix_are_parsed() {
+    assert_eq!(
+        "e7e8q".parse::<Move>(),
+        Ok(Move::with_promotion(Square::E7, Square::E8, PieceType::Queen))
+    );
+    assert_eq!("e2e4".parse::<Move>(), Ok(Move::new(Square::E2, Square::E4)));
+    assert_eq!("e7e8x".parse::<Move>(), Err(GameErr::InvalidUciMove("e7e8x".into())));
+    assert_eq!("e7e9".parse::<Move>(), Err(GameErr::InvalidUciMove("e7e9".into())));
+    assert_eq!("e7".parse::<Move>(), Err(GameErr::InvalidUciMove("e7".into())));
+    assert_eq!(Move::with_promotion(Square::B2, Square::B1, PieceType::Knight).to_string(), "b2b1n");
+}
+
+#[test]
+fn stockfish_promotion_is_parsed() {
+    let with_lan: NextMove = serde_json::from_str(r#"{"from":"e7","to":"e8","move":"e7e8n"}"#).unwrap();
+    assert_eq!(
+        with_lan.get_move(),
+        Some(Move::with_promotion(Square::E7, Square::E8, PieceType::Knight))
+    );
+
+    let with_field: NextMove = serde_json::from_str(r#"{"from":"e7","to":"e8","promotion":"q"}"#).unwrap();
+    assert_eq!(
+        with_field.get_move(),
+        Some(Move::with_promotion(Square::E7, Square::E8, PieceType::Queen))
+    );
+
+    let plain: NextMove = serde_json::from_str(r#"{"from":"e2","to":"e4"}"#).unwrap();
+    assert_eq!(plain.get_move(), Some(Move::new(Square::E2, Square::E4)));
+}
@@ -1,6 +1,5 @@
-use std::cmp::PartialEq;
 use crate::game::errors::GameErr;
-use crate::game::{Game, Piece, PieceType};
+use crate::game::Game;
 
 
 
@@ -0,0 +1,106 @@
+use crate::game::errors::GameErr;
+use crate::game::{Game, Move, PieceType, Square};
+use crate::notation::san::{parse_san, to_san};
+use crate::ruleengine;
+
+#[test]
+fn simple_moves_are_parsed() {
+    let g = Game::new();
+    assert_eq!(parse_san(&g, "e4"), Ok(Move::new(Square::E2, Square::E4)));
+    assert_eq!(parse_san(&g, "Nf3"), Ok(Move::new(Square::G1, Square::F3)));
+    assert_eq!(parse_san(&g, "Nc3!?"), Ok(Move::new(Square::B1, Square::C3)));
+}
+
+#[test]
+fn illegal_and_invalid_san_is_rejected() {
+    let g = Game::new();
+    assert_eq!(parse_san(&g, "Ne4"), Err(GameErr::IllegalSan("Ne4".into())));
+    assert_eq!(parse_san(&g, "Qxd7"), Err(GameErr::IllegalSan("Qxd7".into())));
+    assert_eq!(parse_san(&g, "O-O"), Err(GameErr::IllegalSan("O-O".into())));
+    assert_eq!(parse_san(&g, "Zf3"), Err(GameErr::InvalidSan("Zf3".into())));
+    assert_eq!(parse_san(&g, "e9"), Err(GameErr::InvalidSan("e9".into())));
+    assert_eq!(parse_san(&g, ""), Err(GameErr::InvalidSan("".into())));
+}
+
+#[test]
+fn knights_are_disambiguated_by_file() {
+    let g = Game::from_fen("r1bqkb1r/pppppppp/5n2/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    // b8 is empty here, so there is only one knight that reaches d5.
+    assert_eq!(parse_san(&g, "Nd5"), Ok(Move::new(Square::F6, Square::D5)));
+
+    let g = Game::from_fen("rnbqkb1r/pp1ppppp/5n2/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    assert_eq!(parse_san(&g, "Nbd7"), Err(GameErr::IllegalSan("Nbd7".into())));
+
+    let g = Game::from_fen("rnbqkb1r/ppp1pppp/5n2/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    assert_eq!(parse_san(&g, "Nd7"), Err(GameErr::AmbiguousSan("Nd7".into())));
+    assert_eq!(parse_san(&g, "Nbd7"), Ok(Move::new(Square::B8, Square::D7)));
+    assert_eq!(parse_san(&g, "Nfd7"), Ok(Move::new(Square::F6, Square::D7)));
+    assert_eq!(to_san(&g, Move::new(Square::B8, Square::D7)), Ok("Nbd7".into()));
+}
+
+#[test]
+fn rooks_are_disambiguated_by_rank() {
+    let g = Game::from_fen("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1").unwrap();
+    assert_eq!(parse_san(&g, "R1a3"), Ok(Move::new(Square::A1, Square::A3)));
+    assert_eq!(to_san(&g, Move::new(Square::A5, Square::A3)), Ok("R5a3".into()));
+}
+
+#[test]
+fn queens_are_disambiguated_by_square() {
+    let g = Game::from_fen("1k6/8/8/8/4Q2Q/8/8/K6Q w - - 0 1").unwrap();
+    assert_eq!(parse_san(&g, "Qh4e1"), Ok(Move::new(Square::H4, Square::E1)));
+    assert_eq!(to_san(&g, Move::new(Square::H4, Square::E1)), Ok("Qh4e1".into()));
+    assert_eq!(parse_san(&g, "Qhe1"), Err(GameErr::AmbiguousSan("Qhe1".into())));
+}
+
+#[test]
+fn en_passant_castling_and_promotion() {
+    let g = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    assert_eq!(parse_san(&g, "exd6 e.p."), Ok(Move::new(Square::E5, Square::D6)));
+    assert_eq!(to_san(&g, Move::new(Square::E5, Square::D6)), Ok("exd6".into()));
+
+    let g = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+    assert_eq!(parse_san(&g, "O-O-O"), Ok(Move::new(Square::E8, Square::C8)));
+    assert_eq!(parse_san(&g, "0-0"), Ok(Move::new(Square::E8, Square::G8)));
+    assert_eq!(to_san(&g, Move::new(Square::E8, Square::C8)), Ok("O-O-O".into()));
+
+    let g = Game::from_fen("3k4/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let queen = Move::with_promotion(Square::E7, Square::E8, PieceType::Queen);
+    assert_eq!(parse_san(&g, "e8=Q+"), Ok(queen));
+    assert_eq!(parse_san(&g, "e8Q"), Ok(queen));
+    assert_eq!(to_san(&g, queen), Ok("e8=Q+".into()));
+    assert_eq!(
+        to_san(&g, Move::with_promotion(Square::E7, Square::E8, PieceType::Knight)),
+        Ok("e8=N".into())
+    );
+}
+
+#[test]
+fn check_and_mate_suffixes() {
+    let mut g = Game::new();
+    for san in ["f3", "e5", "g4"] {
+        let mv = parse_san(&g, san).unwrap();
+        g.make_move(mv).unwrap();
+    }
+    assert_eq!(to_san(&g, Move::new(Square::D8, Square::H4)), Ok("Qh4#".into()));
+
+    let g = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    assert_eq!(to_san(&g, Move::new(Square::A1, Square::A8)), Ok("Ra8+".into()));
+}
+
+#[test]
+fn every_legal_move_round_trips() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+    ];
+    for fen in fens {
+        let g = Game::from_fen(fen).unwrap();
+        for mv in ruleengine::legal_moves(&g) {
+            let san = to_san(&g, mv).unwrap();
+            assert_eq!(parse_san(&g, &san), Ok(mv), "{} in {}", san, fen);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+use crate::engine::search::{SearchLimits, Searcher, MATE};
+use crate::game::{Game, GameStatus, Move, Square};
+
+fn search(fen: &str, depth: u32) -> (Game, crate::engine::search::SearchResult) {
+    let g = Game::from_fen(fen).unwrap();
+    let result = Searcher::new().search(&g, SearchLimits::depth(depth));
+    (g, result)
+}
+
+#[test]
+fn finds_back_rank_mate_in_one() {
+    let (mut g, result) = search("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 3);
+    assert_eq!(result.best_move, Some(Move::new(Square::A1, Square::A8)));
+    assert_eq!(result.mate_in(), Some(1));
+    g.make_move(result.best_move.unwrap()).unwrap();
+    assert_eq!(g.status(), GameStatus::Checkmate);
+}
+
+#[test]
+fn mates_instead_of_stalemating() {
+    let (mut g, result) = search("k7/8/1K6/8/8/8/8/2Q5 w - - 0 1", 3);
+    assert_eq!(result.mate_in(), Some(1));
+    g.make_move(result.best_move.unwrap()).unwrap();
+    assert_eq!(g.status(), GameStatus::Checkmate);
+}
+
+#[test]
+fn mate_beats_the_fifty_move_rule() {
+    let (_, result) = search("7k/8/6K1/8/8/8/8/R7 w - - 99 80", 3);
+    assert_eq!(result.best_move, Some(Move::new(Square::A1, Square::A8)));
+    assert_eq!(result.mate_in(), Some(1));
+}
+
+#[test]
+fn finds_mate_in_two() {
+    // The rook can not mate at once, the king has to take away h7 or g8 first.
+    let (mut g, result) = search("7k/8/5K2/8/8/8/8/R7 w - - 0 1", 4);
+    assert_eq!(result.mate_in(), Some(2));
+    assert_eq!(result.pv.len(), 3);
+    for mv in result.pv {
+        g.make_move(mv).unwrap();
+    }
+    assert_eq!(g.status(), GameStatus::Checkmate);
+}
+
+#[test]
+fn takes_a_hanging_queen() {
+    let (_, result) = search("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", 2);
+    assert_eq!(result.best_move, Some(Move::new(Square::D1, Square::D5)));
+    assert!(result.score >= 500);
+}
+
+#[test]
+fn quiescence_sees_the_recapture() {
+    // Qxd5 wins a pawn but loses the queen to cxd5.
+    let (_, result) = search("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1", 1);
+    assert_ne!(result.best_move, Some(Move::new(Square::D1, Square::D5)));
+}
+
+#[test]
+fn checkmated_side_has_no_move() {
+    let (_, result) = search("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", 3);
+    assert_eq!(result.best_move, None);
+    assert_eq!(result.score, -MATE);
+}
+
+#[test]
+fn movetime_limits_the_search() {
+    let g = Game::new();
+    let start = Instant::now();
+    let result = Searcher::new().search(&g, SearchLimits::movetime(Duration::from_millis(200)));
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(g.legal_moves().contains(&result.best_move.unwrap()));
+    assert!(result.depth >= 1);
+}
+
+#[test]
+fn stop_flag_ends_the_search_with_a_legal_move() {
+    let g = Game::new();
+    let mut searcher = Searcher::new();
+    searcher.stop_flag().store(true, Ordering::Relaxed);
+    let result = searcher.search(&g, SearchLimits::default());
+    assert!(g.legal_moves().contains(&result.best_move.unwrap()));
+    assert!(!searcher.stop_flag().load(Ordering::Relaxed));
+}
+
+#[test]
+fn node_limit_is_respected() {
+    let g = Game::new();
+    let result = Searcher::new().search(&g, SearchLimits { nodes: Some(500), ..SearchLimits::default() });
+    assert!(result.nodes <= 501);
+    assert!(result.best_move.is_some());
+}
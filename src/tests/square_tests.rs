@@ -0,0 +1,55 @@
+use crate::game::errors::GameErr;
+use crate::game::{Game, Move, PieceType, Square};
+
+#[test]
+fn squares_convert_to_and_from_indices() {
+    assert_eq!(Square::A1.index(), 0);
+    assert_eq!(Square::H8.index(), 63);
+    assert_eq!(Square::from_index(12), Some(Square::E2));
+    assert_eq!(Square::from_index(64), None);
+    assert_eq!(Square::new('e', 4), Ok(Square::E4));
+    assert_eq!((Square::E4.file(), Square::E4.rank()), ('e', 4));
+    assert_eq!(Square::all().count(), 64);
+}
+
+#[test]
+fn invalid_squares_are_errors() {
+    assert_eq!(Square::new('i', 1), Err(GameErr::InvalidSquare("i1".into())));
+    assert_eq!(Square::new('a', 0), Err(GameErr::InvalidSquare("a0".into())));
+    assert_eq!(Square::try_from(('a', 9)), Err(GameErr::InvalidSquare("a9".into())));
+    assert_eq!("e".parse::<Square>(), Err(GameErr::InvalidSquare("e".into())));
+    assert_eq!("E4".parse::<Square>(), Err(GameErr::InvalidSquare("E4".into())));
+    assert_eq!("e44".parse::<Square>(), Err(GameErr::InvalidSquare("e44".into())));
+}
+
+#[test]
+fn squares_are_parsed_and_displayed() {
+    assert_eq!("c6".parse::<Square>(), Ok(Square::C6));
+    assert_eq!(Square::C6.to_string(), "c6");
+    assert_eq!(Square::B1.offset(1, 2), Some(Square::C3));
+    assert_eq!(Square::H1.offset(1, 0), None);
+}
+
+#[test]
+fn uci_moves_round_trip() {
+    for uci in ["e2e4", "g8f6", "e7e8q", "a2a1n", "h7h8r", "b7b8b"] {
+        let mv: Move = uci.parse().unwrap();
+        assert_eq!(mv.to_string(), uci);
+    }
+    assert_eq!("e7e8q".parse::<Move>().unwrap().promotion, Some(PieceType::Queen));
+    assert_eq!("e7e8k".parse::<Move>(), Err(GameErr::InvalidUciMove("e7e8k".into())));
+    assert_eq!("z2e4".parse::<Move>(), Err(GameErr::InvalidUciMove("z2e4".into())));
+    assert_eq!("e2e4 ".parse::<Move>(), Err(GameErr::InvalidUciMove("e2e4 ".into())));
+}
+
+#[test]
+fn move_piece_rejects_squares_off_the_board() {
+    let mut g = Game::new();
+    assert_eq!(g.move_piece(('x', 2), ('e', 4)), Err(GameErr::InvalidSquare("x2".into())));
+    assert_eq!(g.move_piece(('e', 2), ('e', 9)), Err(GameErr::InvalidSquare("e9".into())));
+
+    // Squares can also be given as `Square` or as strings.
+    g.move_piece(Square::E2, Square::E4).unwrap();
+    g.move_piece("e7", "e5").unwrap();
+    assert_eq!(g.history().len(), 2);
+}
@@ -0,0 +1,146 @@
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+use crate::engine::player::{play_game, MoveProvider, Score};
+use crate::engine::search::SearchLimits;
+use crate::game::{Game, GameStatus, Move, Square};
+use crate::stockfish::{NextMove, StockfishAPI, StockfishConfig, StockfishErr};
+use crate::stockfish::mock::{MockChessApi, MockResponse};
+
+fn mock(script: Vec<MockResponse>) -> MockChessApi {
+    MockChessApi::start(script).unwrap()
+}
+
+fn status(status: u16, body: &str) -> MockResponse {
+    MockResponse::Status(status, body.into())
+}
+
+fn client(url: String) -> StockfishAPI {
+    StockfishAPI::with_config(StockfishConfig {
+        base_url: url,
+        timeout: Duration::from_millis(500),
+        retries: 2,
+        backoff: Duration::from_millis(10),
+        ..StockfishConfig::default()
+    })
+}
+
+const RESPONSE: &str = r#"{"text":"Move e2 → e4 (e4): [0.3]. Depth 12.","eval":0.3,"move":"e2e4","fen":"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1","depth":12,"winChance":52.7,"continuationArr":["e7e5","g1f3"],"mate":null,"centipawns":"30","san":"e4","lan":"e2e4","turn":"w","color":"w","piece":"p","flags":"b","isCapture":false,"isCastling":false,"isPromotion":false,"from":"e2","to":"e4","fromNumeric":"52","toNumeric":"54","taken":null,"type":"bestmove"}"#;
+
+#[test]
+fn parses_the_full_response() {
+    let next: NextMove = serde_json::from_str(RESPONSE).unwrap();
+    assert_eq!(next.get_move(), Some(Move::new(Square::E2, Square::E4)));
+    assert_eq!(next.san.as_deref(), Some("e4"));
+    assert_eq!(next.depth, Some(12));
+    assert_eq!(next.win_chance, Some(52.7));
+    assert_eq!(next.mate, None);
+    assert_eq!(next.continuation_moves(), vec![Move::new(Square::E7, Square::E5), Move::new(Square::G1, Square::F3)]);
+    assert_eq!(next.kind.as_deref(), Some("bestmove"));
+}
+
+#[test]
+fn sends_fen_depth_and_variants() {
+    let mock = mock(vec![MockResponse::Move("e2e4".into())]);
+    let api = StockfishAPI::with_config(StockfishConfig { depth: Some(30), variants: Some(3), ..client(mock.url()).config().clone() });
+    let next = api.get(&Game::new().fen).unwrap();
+    assert_eq!(next.get_move(), Some(Move::new(Square::E2, Square::E4)));
+    assert_eq!(next.san.as_deref(), Some("e4"));
+
+    let request = &mock.requests()[0];
+    assert_eq!(request["fen"], Game::new().fen.as_str());
+    assert_eq!(request["depth"], 18);
+    assert_eq!(request["variants"], 3);
+}
+
+#[test]
+fn retries_server_errors() {
+    let mock = mock(vec![status(503, "busy"), status(500, "oops"), status(200, RESPONSE)]);
+    let mut api = client(mock.url());
+    let chosen = api.choose_move(&Game::new(), SearchLimits::default()).unwrap();
+    assert_eq!(chosen.mv, Move::new(Square::E2, Square::E4));
+    assert_eq!(chosen.score, Some(Score::Centipawns(30)));
+    assert_eq!(mock.requests().len(), 3);
+}
+
+#[test]
+fn gives_up_after_the_retries() {
+    let mock = mock(vec![status(502, "a"), status(502, "b"), status(502, "c")]);
+    let result = client(mock.url()).get(&Game::new().fen);
+    assert_eq!(result, Err(StockfishErr::Status { status: 502, body: "c".into() }));
+    assert_eq!(mock.requests().len(), 3);
+}
+
+#[test]
+fn does_not_retry_client_errors() {
+    let mock = mock(vec![status(400, "bad fen")]);
+    let result = client(mock.url()).get("not a fen");
+    assert_eq!(result, Err(StockfishErr::Status { status: 400, body: "bad fen".into() }));
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[test]
+fn reports_malformed_json_and_service_errors() {
+    let mock = mock(vec![MockResponse::Malformed]);
+    assert!(matches!(client(mock.url()).get(&Game::new().fen), Err(StockfishErr::InvalidResponse(_))));
+
+    // The engine behind the mock rejects the position.
+    assert_eq!(client(mock.url()).get("x"), Err(StockfishErr::Service("Invalid FEN".into())));
+}
+
+#[test]
+fn times_out_and_retries_slow_responses() {
+    let slow = MockResponse::Slow(Duration::from_secs(2), Box::new(MockResponse::Move("e2e4".into())));
+    let mock = mock(vec![slow.clone(), slow, MockResponse::Move("d2d4".into())]);
+    let api = StockfishAPI::with_config(StockfishConfig { retries: 0, ..client(mock.url()).config().clone() });
+    assert_eq!(api.get(&Game::new().fen), Err(StockfishErr::Timeout(Duration::from_millis(500))));
+
+    let start = Instant::now();
+    let next = client(mock.url()).get(&Game::new().fen).unwrap();
+    assert_eq!(next.get_move(), Some(Move::new(Square::D2, Square::D4)));
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn reports_an_unreachable_service() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/v1", listener.local_addr().unwrap());
+    drop(listener);
+    let api = StockfishAPI::with_config(StockfishConfig { retries: 0, ..client(url).config().clone() });
+    assert!(matches!(api.get(&Game::new().fen), Err(StockfishErr::Connection(_))));
+}
+
+#[test]
+fn mock_answers_with_the_builtin_engine() {
+    let mock = mock(Vec::new());
+    let g = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    let next = client(mock.url()).get(&g.fen).unwrap();
+    assert_eq!(next.get_move(), Some(Move::new(Square::A1, Square::A8)));
+    assert_eq!(next.san.as_deref(), Some("Ra8#"));
+    assert_eq!(next.score(g.current_player), Some(Score::Mate(1)));
+
+    // Black to move and winning, the service reports the evaluation from White's side.
+    let g = Game::from_fen("3qk3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+    let next = client(mock.url()).get(&g.fen).unwrap();
+    assert!(next.eval.unwrap() < -5.0);
+    assert!(matches!(next.score(g.current_player), Some(Score::Centipawns(cp)) if cp > 500));
+}
+
+#[test]
+fn autoplay_against_the_mock() {
+    let mock = mock(["f2f3", "e7e5", "g2g4", "d8h4"].map(|mv| MockResponse::Move(mv.into())).to_vec());
+    let mut white = client(mock.url());
+    let mut black = client(mock.url());
+    let mut g = Game::new();
+    play_game(&mut g, &mut white, &mut black, SearchLimits::default(), |_, _| {}).unwrap();
+    assert_eq!(g.status(), GameStatus::Checkmate);
+    assert_eq!(mock.requests()[3]["fen"], "rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq g3 0 2");
+}
+
+#[test]
+fn an_illegal_scripted_move_ends_the_game() {
+    let mock = mock(vec![MockResponse::Move("e2e5".into())]);
+    let mut white = client(mock.url());
+    let mut black = client(mock.url());
+    let result = play_game(&mut Game::new(), &mut white, &mut black, SearchLimits::default(), |_, _| {});
+    assert!(matches!(result, Err(crate::game::errors::GameErr::Engine(_))));
+}
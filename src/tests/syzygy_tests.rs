@@ -0,0 +1,289 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use crate::engine::search::{SearchLimits, Searcher, TB_WIN};
+use crate::engine::syzygy::{Tablebase, TablebaseProbe, Wdl};
+use crate::game::{Color, Game, Move, Square};
+use crate::game::errors::GameErr;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+/// Positions of KQvK with the first king in the a1-d1-d4 triangle.
+const KQVK_SIZE: u16 = 31_332;
+
+/// A KQvK WDL table: every position with white to move is a win, Huffman
+/// coded with one bit per position, and every position with black to move a
+/// loss. Captures and stalemates are found by searching, not in the table.
+fn kqvk_wdl() -> Vec<u8> {
+    let mut data = WDL_MAGIC.to_vec();
+    data.push(1);
+    data.push(0x00);
+    data.extend([0x66, 0x55, 0xee]);
+    data.push(0);
+    // White to move: 4096 byte blocks, a sparse index entry per 2^15 values,
+    // symbols of one bit, symbol 0 a draw and symbol 1 a win.
+    data.extend([0, 12, 15, 0]);
+    data.extend(1u32.to_le_bytes());
+    data.extend([1, 1]);
+    data.extend(0u16.to_le_bytes());
+    data.extend(2u16.to_le_bytes());
+    data.extend([2, 0xf0, 0xff, 4, 0xf0, 0xff]);
+    // Black to move: a single value, the loss.
+    data.extend([0x80, 0]);
+    data.extend(0u32.to_le_bytes());
+    data.extend((1u16 << 14).to_le_bytes());
+    data.extend((KQVK_SIZE - 1).to_le_bytes());
+    data.resize(64, 0);
+    data.resize(64 + 4096, 0xff);
+    data.resize(data.len() + 16, 0);
+    data
+}
+
+/// A KQvK DTZ table with white to move, 11 plies to zeroing everywhere.
+fn kqvk_dtz() -> Vec<u8> {
+    let mut data = DTZ_MAGIC.to_vec();
+    data.extend([0, 0x00, 0x06, 0x05, 0x0e, 0, 0x80, 5]);
+    data.resize(64 + 16, 0);
+    data
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("chess-syzygy-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn kqvk_tablebase(name: &str) -> (Tablebase, PathBuf) {
+    let dir = temp_dir(name);
+    fs::write(dir.join("KQvK.rtbw"), kqvk_wdl()).unwrap();
+    fs::write(dir.join("KQvK.rtbz"), kqvk_dtz()).unwrap();
+    (Tablebase::open(&dir).unwrap(), dir)
+}
+
+fn probe(tablebase: &Tablebase, fen: &str) -> Option<TablebaseProbe> {
+    tablebase.probe(&Game::from_fen(fen).unwrap())
+}
+
+#[test]
+fn directories_are_scanned_for_tables() {
+    let missing = env::temp_dir().join("chess-syzygy-does-not-exist");
+    assert!(matches!(Tablebase::open(&missing), Err(GameErr::InvalidTablebase(_))));
+
+    let dir = temp_dir("scan");
+    for name in ["KRvK.rtbw", "KRvK.rtbz", "KQPvKRR.rtbw", "KvK.rtbw", "KQvKQQQQQQ.rtbw", "notes.txt"] {
+        fs::write(dir.join(name), b"").unwrap();
+    }
+    let tablebase = Tablebase::open(&dir).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(tablebase.len(), 4);
+    assert_eq!(tablebase.max_pieces(), 6);
+
+    // Empty files are no tables, probing gives up without panicking.
+    assert_eq!(probe(&tablebase, "8/8/8/8/8/2k5/8/R3K3 w - - 0 1"), None);
+}
+
+#[test]
+fn positions_outside_the_tables_are_not_probed() {
+    let (tablebase, dir) = kqvk_tablebase("outside");
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(probe(&tablebase, "4k3/8/8/8/8/8/8/R3K3 w Q - 0 1"), None);
+    assert_eq!(probe(&tablebase, "4k3/8/8/8/8/8/8/RQ2K3 w - - 0 1"), None);
+    assert_eq!(probe(&tablebase, "4k3/8/8/8/8/8/8/R3K3 w - - 0 1"), None);
+    assert_eq!(tablebase.probe(&Game::new()), None);
+}
+
+#[test]
+fn wdl_and_dtz_of_kqvk() {
+    let (tablebase, dir) = kqvk_tablebase("kqvk");
+    let win = probe(&tablebase, "8/8/4k3/8/8/8/8/3QK3 w - - 0 1");
+    let loss = probe(&tablebase, "8/8/4k3/8/8/8/8/3QK3 b - - 0 1");
+    let mirrored = probe(&tablebase, "3qk3/8/8/8/8/4K3/8/8 b - - 0 1");
+    let capture = probe(&tablebase, "8/8/8/8/8/4k3/3Q4/7K b - - 0 1");
+    let stalemate = probe(&tablebase, "k7/2Q5/1K6/8/8/8/8/8 b - - 0 1");
+    let bare_kings = probe(&tablebase, "8/8/8/8/8/4k3/8/7K w - - 0 1");
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(win, Some(TablebaseProbe { wdl: Wdl::Win, dtz: Some(11) }));
+    assert_eq!(loss, Some(TablebaseProbe { wdl: Wdl::Loss, dtz: Some(-12) }));
+    assert_eq!(mirrored, Some(TablebaseProbe { wdl: Wdl::Win, dtz: Some(11) }));
+    assert_eq!(capture.map(|p| p.wdl), Some(Wdl::Draw));
+    assert_eq!(stalemate.map(|p| p.wdl), Some(Wdl::Draw));
+    assert_eq!(bare_kings, Some(TablebaseProbe { wdl: Wdl::Draw, dtz: Some(0) }));
+}
+
+#[test]
+fn damaged_tables_are_not_used() {
+    let dir = temp_dir("damaged");
+    let mut wdl = kqvk_wdl();
+    wdl.truncate(64 + 16);
+    fs::write(dir.join("KQvK.rtbw"), wdl).unwrap();
+    let tablebase = Tablebase::open(&dir).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(probe(&tablebase, "8/8/4k3/8/8/8/8/3QK3 w - - 0 1"), None);
+}
+
+#[test]
+fn root_moves_put_mates_first_and_stalemates_last() {
+    let (tablebase, dir) = kqvk_tablebase("root");
+    let g = Game::from_fen("7k/3Q4/6K1/8/8/8/8/8 w - - 0 1").unwrap();
+    let moves = tablebase.root_moves(&g).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(moves.len(), g.legal_moves().len());
+    let mut after = g.clone();
+    after.make_move(moves[0].mv).unwrap();
+    assert!(after.legal_moves().is_empty());
+    assert_eq!((moves[0].wdl, moves[0].dtz), (Wdl::Win, 1));
+    // Qd5, Qe6 and Qf7 stalemate.
+    let draws: Vec<Move> = moves.iter().skip_while(|m| m.wdl == Wdl::Win).map(|m| m.mv).collect();
+    assert_eq!(draws.len(), 3);
+    for to in [Square::D5, Square::E6, Square::F7] {
+        assert!(draws.contains(&Move::new(Square::D7, to)));
+    }
+    assert!(moves.iter().filter(|m| m.wdl == Wdl::Win && m.dtz > 1).all(|m| m.dtz == 13));
+}
+
+#[test]
+fn search_plays_by_the_tables() {
+    let (tablebase, dir) = kqvk_tablebase("search");
+    let mut searcher = Searcher::new();
+    searcher.set_tablebase(Some(Arc::new(tablebase)));
+    let far = searcher.search(&Game::from_fen("8/8/8/3k4/8/8/8/Q6K w - - 0 1").unwrap(), SearchLimits::depth(2));
+    let mate = searcher.search(&Game::from_fen("7k/3Q4/6K1/8/8/8/8/8 w - - 0 1").unwrap(), SearchLimits::depth(3));
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(far.score, TB_WIN);
+    assert_eq!(mate.mate_in(), Some(1));
+
+    let mut plain = Searcher::new();
+    let result = plain.search(&Game::from_fen("8/8/8/3k4/8/8/8/Q6K w - - 0 1").unwrap(), SearchLimits::depth(2));
+    assert!(result.score < TB_WIN - 1000);
+}
+
+/// Position with the pieces on the given squares, `None` when the side not
+/// to move is in check.
+fn placed(pieces: &[(char, usize)], side: char) -> Option<Game> {
+    let mut board = ['1'; 64];
+    for &(piece, square) in pieces {
+        if board[square] != '1' {
+            return None;
+        }
+        board[square] = piece;
+    }
+    let ranks: Vec<String> = (0..8).rev().map(|rank| board[rank * 8..rank * 8 + 8].iter().collect()).collect();
+    let game = Game::from_fen(&format!("{} {} - - 0 1", ranks.join("/"), side)).ok()?;
+    let other = if side == 'w' { Color::Black } else { Color::White };
+    (!game.bitboards().is_in_check(other)).then_some(game)
+}
+
+/// The tables under fixtures/syzygy, written by generate.rs there. Set
+/// `SYZYGY_PATH` to check the official tables instead.
+fn fixture_tablebase() -> Tablebase {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/fixtures/syzygy");
+    Tablebase::open(env::var("SYZYGY_PATH").unwrap_or(fixtures.to_string())).unwrap()
+}
+
+#[test]
+fn wdl_and_dtz_of_three_piece_tables() {
+    let tablebase = fixture_tablebase();
+    let result = |fen: &str| probe(&tablebase, fen).map(|p| p.wdl);
+    let dtz = |fen: &str| probe(&tablebase, fen).and_then(|p| p.dtz);
+    assert_eq!(result("8/8/4k3/8/8/8/8/3QK3 w - - 0 1"), Some(Wdl::Win));
+    assert_eq!(result("8/8/4k3/8/8/8/8/3QK3 b - - 0 1"), Some(Wdl::Loss));
+    assert_eq!(result("8/8/8/4k3/8/8/8/R3K3 b - - 0 1"), Some(Wdl::Loss));
+    // The king takes the undefended rook.
+    assert_eq!(result("8/8/8/8/8/8/1k6/R6K b - - 0 1"), Some(Wdl::Draw));
+    assert_eq!(probe(&tablebase, "7k/8/6K1/8/8/8/8/R7 w - - 0 1"), Some(TablebaseProbe { wdl: Wdl::Win, dtz: Some(1) }));
+    assert_eq!(dtz("7k/8/6K1/8/8/8/8/R7 b - - 0 1"), Some(-2));
+    assert_eq!(dtz("6k1/8/6K1/8/8/8/8/Q7 w - - 0 1"), Some(1));
+    assert_eq!(result("8/8/8/8/8/8/1k6/B6K w - - 0 1"), Some(Wdl::Draw));
+    assert_eq!(result("8/8/8/8/8/8/1k6/N6K b - - 0 1"), Some(Wdl::Draw));
+
+    // The king in front of the pawn with the opposition wins, without it
+    // the defender holds. A rook pawn is a draw with the king in the corner.
+    assert_eq!(result("4k3/8/8/4K3/4P3/8/8/8 b - - 0 1"), Some(Wdl::Draw));
+    assert_eq!(result("4k3/8/8/4K3/4P3/8/8/8 w - - 0 1"), Some(Wdl::Win));
+    assert_eq!(result("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1"), Some(Wdl::Draw));
+    assert_eq!(result("8/4k3/8/4K3/4P3/8/8/8 b - - 0 1"), Some(Wdl::Loss));
+    assert_eq!(result("k7/8/K7/P7/8/8/8/8 w - - 0 1"), Some(Wdl::Draw));
+    assert_eq!(result("8/8/8/8/8/8/kP6/7K b - - 0 1"), Some(Wdl::Draw));
+    // Mirrored, black has the pawn.
+    assert_eq!(result("8/8/8/8/4p3/4k3/8/4K3 w - - 0 1"), Some(Wdl::Loss));
+    // A promotion is a pawn move, the distance counts from it.
+    assert_eq!(probe(&tablebase, "8/4P3/8/8/8/k7/8/K7 w - - 0 1"), Some(TablebaseProbe { wdl: Wdl::Win, dtz: Some(1) }));
+    assert_eq!(dtz("8/8/8/8/8/8/1P6/K1k5 w - - 0 1"), Some(1));
+}
+
+#[test]
+fn games_are_probed_through_the_tables() {
+    let tablebase = fixture_tablebase();
+    let g = Game::from_fen("7k/8/6K1/8/8/8/8/R7 w - - 0 1").unwrap();
+    assert_eq!(g.probe_tablebase(&tablebase), Some(TablebaseProbe { wdl: Wdl::Win, dtz: Some(1) }));
+    assert_eq!(Game::new().probe_tablebase(&tablebase), None);
+}
+
+/// Every stored result has to agree with the best result one move later,
+/// which reads the table of the other side to move. Every eleventh placement
+/// is checked, all of them take minutes.
+#[test]
+fn three_piece_tables_agree_with_one_move_later() {
+    let tablebase = fixture_tablebase();
+    for (piece, longest, white_king) in [('Q', 20, 18), ('R', 32, 0), ('P', 20, 35)] {
+        for (extra, black_king, side) in (0..64).flat_map(|e| (0..64).flat_map(move |k| [(e, k, 'w'), (e, k, 'b')])) {
+            if (extra * 64 + black_king) % 11 != 0 || (piece == 'P' && !(8..56).contains(&extra)) {
+                continue;
+            }
+            let Some(game) = placed(&[('K', white_king), (piece, extra), ('k', black_king)], side) else {
+                continue;
+            };
+            let Some(parent) = tablebase.probe(&game) else {
+                panic!("no result for {}", game.fen);
+            };
+            let dtz = parent.dtz.unwrap();
+            // The best result after a move, and the fewest plies to zeroing
+            // or mate among the winning moves.
+            let mut best = None;
+            let mut fastest = i32::MAX;
+            let mut after = game.clone();
+            for mv in game.legal_moves() {
+                let zeroing = piece == 'P' && mv.from.index() == extra;
+                after.make_move(mv).unwrap();
+                let (wdl, plies) = if !after.legal_moves().is_empty() {
+                    let child = tablebase.probe(&after).unwrap();
+                    (flip(child.wdl), if zeroing { 1 } else { 1 - child.dtz.unwrap() })
+                } else if after.bitboards().is_in_check(after.current_player) {
+                    (Wdl::Win, 1)
+                } else {
+                    (Wdl::Draw, 0)
+                };
+                after.undo().unwrap();
+                best = best.max(Some(wdl));
+                if wdl == Wdl::Win {
+                    fastest = fastest.min(plies);
+                }
+            }
+            let Some(best) = best else {
+                continue;
+            };
+            assert_eq!(parent.wdl, best, "{}", game.fen);
+            match parent.wdl {
+                // Stored distances may be rounded to full moves.
+                Wdl::Win => assert!((1..=longest).contains(&dtz) && (dtz - fastest).abs() <= 1, "{} {}", game.fen, dtz),
+                Wdl::Loss => assert!((-longest - 1..=-1).contains(&dtz), "{} {}", game.fen, dtz),
+                _ => assert_eq!(dtz, 0, "{}", game.fen),
+            }
+        }
+    }
+}
+
+fn flip(wdl: Wdl) -> Wdl {
+    match wdl {
+        Wdl::Loss => Wdl::Win,
+        Wdl::BlessedLoss => Wdl::CursedWin,
+        Wdl::Draw => Wdl::Draw,
+        Wdl::CursedWin => Wdl::BlessedLoss,
+        Wdl::Win => Wdl::Loss,
+    }
+}
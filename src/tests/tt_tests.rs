@@ -0,0 +1,92 @@
+use std::mem;
+use crate::engine::search::{SearchLimits, Searcher, MATE};
+use crate::engine::tt::{score_from_tt, score_to_tt, Bound, TranspositionTable, TtEntry};
+use crate::game::{Game, Move, Square};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/P1N2Q1p/1PPBBPPP/R3K2R w KQkq - 0 1";
+
+#[test]
+fn size_follows_the_memory_limit() {
+    let tt = TranspositionTable::new(1);
+    assert!(tt.len().is_power_of_two());
+    assert!(tt.len() * mem::size_of::<TtEntry>() <= 1024 * 1024);
+    assert!(tt.len() * 2 * mem::size_of::<TtEntry>() > 1024 * 1024);
+}
+
+#[test]
+fn stores_and_probes() {
+    let mut tt = TranspositionTable::new(1);
+    let mv = Move::new(Square::E2, Square::E4);
+    tt.store(42, 5, 31, Bound::Lower, Some(mv), 3);
+    let entry = tt.probe(42).unwrap();
+    assert_eq!((entry.depth, entry.score, entry.bound, entry.best_move), (5, 31, Bound::Lower, Some(mv)));
+    // Same slot, other position.
+    assert_eq!(tt.probe(42 + tt.len() as u64), None);
+
+    tt.clear();
+    assert_eq!(tt.probe(42), None);
+}
+
+#[test]
+fn mate_scores_are_stored_relative_to_the_position() {
+    // Mate in 3 plies from the root, found 2 plies deep: mate in 1 ply from there.
+    assert_eq!(score_to_tt(MATE - 3, 2), MATE - 1);
+    assert_eq!(score_from_tt(MATE - 1, 4), MATE - 5);
+    assert_eq!(score_to_tt(-MATE + 3, 2), -MATE + 1);
+    assert_eq!(score_from_tt(score_to_tt(-MATE + 7, 3), 3), -MATE + 7);
+    assert_eq!(score_to_tt(250, 9), 250);
+
+    let mut tt = TranspositionTable::new(1);
+    tt.store(7, 3, MATE - 3, Bound::Exact, None, 2);
+    assert_eq!(score_from_tt(tt.probe(7).unwrap().score, 6), MATE - 7);
+}
+
+#[test]
+fn deeper_entries_of_the_current_search_are_kept() {
+    let mut tt = TranspositionTable::new(1);
+    let other = 5 + tt.len() as u64;
+    tt.store(5, 8, 10, Bound::Exact, None, 0);
+    tt.store(other, 2, 20, Bound::Exact, None, 0);
+    assert_eq!(tt.probe(5).unwrap().depth, 8);
+    assert_eq!(tt.probe(other), None);
+
+    // The same position is always updated, and older searches make room.
+    tt.store(5, 1, 15, Bound::Upper, None, 0);
+    assert_eq!(tt.probe(5).unwrap().score, 15);
+    tt.store(5, 8, 10, Bound::Exact, None, 0);
+    tt.new_search();
+    tt.store(other, 2, 20, Bound::Exact, None, 0);
+    assert_eq!(tt.probe(other).unwrap().score, 20);
+}
+
+#[test]
+fn keeps_the_best_move_without_a_new_one() {
+    let mut tt = TranspositionTable::new(1);
+    let mv = Move::new(Square::G1, Square::F3);
+    tt.store(9, 4, 0, Bound::Exact, Some(mv), 0);
+    tt.store(9, 5, -20, Bound::Upper, None, 0);
+    assert_eq!(tt.probe(9).unwrap().best_move, Some(mv));
+}
+
+#[test]
+fn hashfull_grows_while_searching() {
+    let mut searcher = Searcher::new();
+    searcher.set_hash_size(1);
+    let result = searcher.search(&Game::from_fen(KIWIPETE).unwrap(), SearchLimits::depth(3));
+    assert!(result.hashfull > 0);
+    assert!(result.hashfull <= 1000);
+}
+
+#[test]
+fn table_is_reused_by_the_next_search() {
+    let g = Game::from_fen(KIWIPETE).unwrap();
+    let mut searcher = Searcher::new();
+    let first = searcher.search(&g, SearchLimits::depth(3));
+    let second = searcher.search(&g, SearchLimits::depth(3));
+    assert!(second.nodes < first.nodes);
+    assert_eq!(second.score, first.score);
+
+    searcher.clear_hash();
+    let cleared = searcher.search(&g, SearchLimits::depth(3));
+    assert_eq!(cleared.nodes, first.nodes);
+}
@@ -0,0 +1,123 @@
+use std::time::{Duration, Instant};
+use crate::engine::player::{play_game, MoveProvider, Score};
+use crate::engine::search::{SearchLimits, Searcher};
+use crate::game::errors::GameErr;
+use crate::game::{Game, Move, Square};
+use crate::uci::client::{go_command, UciEngine, UciInfo};
+
+fn fake_engine() -> UciEngine {
+    let script = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/fixtures/fake_uci_engine.sh");
+    UciEngine::spawn_with_args("sh", [script]).unwrap()
+}
+
+#[test]
+fn parses_info_lines() {
+    let info = UciInfo::parse("info depth 12 seldepth 18 multipv 1 score cp -34 upperbound nodes 123456 nps 800000 time 154 pv e2e4 e7e5 g1f3").unwrap();
+    assert_eq!(info.depth, Some(12));
+    assert_eq!(info.seldepth, Some(18));
+    assert_eq!(info.score, Some(Score::Centipawns(-34)));
+    assert_eq!(info.nodes, Some(123456));
+    assert_eq!(info.nps, Some(800000));
+    assert_eq!(info.time, Some(Duration::from_millis(154)));
+    assert_eq!(info.pv, vec![
+        Move::new(Square::E2, Square::E4), Move::new(Square::E7, Square::E5), Move::new(Square::G1, Square::F3),
+    ]);
+
+    let info = UciInfo::parse("info score mate -2 pv d8h4 currmove e2e4").unwrap();
+    assert_eq!(info.score, Some(Score::Mate(-2)));
+    assert_eq!(info.pv.len(), 1);
+    assert_eq!(UciInfo::parse("info string hello world").unwrap().string.as_deref(), Some("hello world"));
+    assert_eq!(UciInfo::parse("bestmove e2e4"), None);
+}
+
+#[test]
+fn go_commands() {
+    assert_eq!(go_command(SearchLimits::depth(8)), "go depth 8");
+    assert_eq!(go_command(SearchLimits::movetime(Duration::from_millis(250))), "go movetime 250");
+    assert_eq!(go_command(SearchLimits::default()), "go movetime 1000");
+}
+
+#[test]
+fn handshake_and_search() {
+    let mut engine = fake_engine();
+    assert_eq!(engine.engine_name(), "Fake Engine 1.0");
+
+    let mut g = Game::new();
+    g.make_move(Move::new(Square::D2, Square::D4)).unwrap();
+    engine.set_option("Move", "g8f6").unwrap();
+    let result = engine.go(&g, SearchLimits::depth(2)).unwrap();
+    assert_eq!(result.best_move, Some(Move::new(Square::G8, Square::F6)));
+    assert_eq!(result.ponder, Some(Move::new(Square::E7, Square::E5)));
+    assert_eq!(result.info[0].string.as_deref(), Some("position startpos moves d2d4"));
+    assert_eq!(result.info.last().unwrap().depth, Some(2));
+    assert_eq!(result.score(), Some(Score::Mate(3)));
+    engine.quit().unwrap();
+}
+
+#[test]
+fn stops_an_infinite_search() {
+    let mut engine = fake_engine();
+    engine.start_infinite(&Game::new()).unwrap();
+    let result = engine.stop().unwrap();
+    assert_eq!(result.best_move, Some(Move::new(Square::E2, Square::E4)));
+    assert_eq!(result.info.len(), 2);
+}
+
+#[test]
+fn times_out_and_kills_a_hanging_engine() {
+    let mut engine = fake_engine();
+    engine.set_timeout(Duration::from_millis(200));
+    engine.set_option("Hang", "true").unwrap();
+
+    let start = Instant::now();
+    let result = engine.go(&Game::new(), SearchLimits::movetime(Duration::from_millis(100)));
+    assert!(matches!(result, Err(GameErr::Engine(e)) if e.contains("did not answer")));
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(engine.quit().is_err());
+}
+
+#[test]
+fn stops_a_hanging_depth_search() {
+    let mut engine = fake_engine();
+    engine.set_timeout(Duration::from_millis(200));
+    engine.set_max_search_time(Duration::from_millis(100));
+    engine.set_option("Hang", "true").unwrap();
+
+    let start = Instant::now();
+    let result = engine.go(&Game::new(), SearchLimits::depth(30));
+    assert!(matches!(result, Err(GameErr::Engine(e)) if e.contains("did not answer")));
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn skips_the_late_answer_to_stop() {
+    let mut engine = fake_engine();
+    engine.set_timeout(Duration::from_millis(100));
+    engine.set_option("Delay", "0.3").unwrap();
+    engine.start_infinite(&Game::new()).unwrap();
+    assert!(engine.stop().is_err());
+
+    engine.set_timeout(Duration::from_secs(5));
+    engine.set_option("Move", "d2d4").unwrap();
+    let result = engine.go(&Game::new(), SearchLimits::depth(2)).unwrap();
+    assert_eq!(result.best_move, Some(Move::new(Square::D2, Square::D4)));
+    assert_eq!(result.info.len(), 3);
+}
+
+#[test]
+fn reports_a_missing_binary() {
+    assert!(matches!(UciEngine::spawn("/nonexistent/engine"), Err(GameErr::Engine(_))));
+}
+
+#[test]
+fn plays_a_game_against_the_builtin_engine() {
+    // The fake engine always plays h7h6, so its second move is illegal.
+    let mut white = Searcher::new();
+    let mut black = fake_engine();
+    black.set_option("Move", "h7h6").unwrap();
+    let mut g = Game::new();
+    let result = play_game(&mut g, &mut white, &mut black, SearchLimits::depth(1), |_, _| {});
+    assert!(matches!(result, Err(GameErr::Engine(e)) if e.starts_with("Fake Engine 1.0 played h7h6")));
+    assert_eq!(g.history().len(), 3);
+    assert_eq!(black.name(), "Fake Engine 1.0");
+}
@@ -0,0 +1,130 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::game::{Color, Move};
+use crate::uci::server::UciServer;
+
+/// A writer the test can read back while the server holds it.
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Output {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap().lines().map(String::from).collect()
+    }
+}
+
+fn server() -> (UciServer<Output>, Output) {
+    let out = Output::default();
+    (UciServer::new(out.clone()), out)
+}
+
+#[test]
+fn handshake() {
+    let (mut server, out) = server();
+    server.handle("uci");
+    server.handle("isready");
+    let lines = out.lines();
+    assert!(lines[0].starts_with("id name "));
+    assert!(lines[1].starts_with("id author "));
+    assert!(lines.iter().any(|l| l.starts_with("option name Move Overhead")));
+    assert!(lines.iter().any(|l| l.starts_with("option name SyzygyPath")));
+    assert_eq!(lines[lines.len() - 2], "uciok");
+    assert_eq!(lines[lines.len() - 1], "readyok");
+}
+
+#[test]
+fn position_with_moves() {
+    let (mut server, _) = server();
+    server.handle("position startpos moves e2e4 e7e5 g1f3");
+    assert_eq!(server.game().history().len(), 3);
+    assert_eq!(server.game().current_player, Color::Black);
+
+    server.handle("position fen 4k3/8/8/8/8/8/8/4K2R w K - 0 1 moves e1g1");
+    assert_eq!(server.game().fen, "4k3/8/8/8/8/8/8/5RK1 b - - 1 1");
+}
+
+#[test]
+fn invalid_commands_are_reported() {
+    let (mut server, out) = server();
+    assert!(server.handle("position startpos moves e2e5"));
+    assert!(server.handle("setoption name Ponder value true"));
+    assert!(server.handle("frobnicate"));
+    assert!(out.lines().iter().all(|l| l.starts_with("info string ")));
+    assert_eq!(out.lines().len(), 3);
+    assert!(server.game().history().is_empty());
+}
+
+#[test]
+fn go_depth_sends_info_and_bestmove() {
+    let (mut server, out) = server();
+    server.handle("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    server.handle("go depth 3");
+    server.wait();
+    let lines = out.lines();
+    assert!(lines.iter().any(|l| l.starts_with("info depth 1 score mate 1 ") && l.ends_with("pv a1a8")));
+    assert_eq!(lines.last().unwrap(), "bestmove a1a8");
+}
+
+#[test]
+fn stop_after_bestmove_does_not_end_the_next_search() {
+    let (mut server, out) = server();
+    server.handle("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    server.handle("go depth 3");
+    // The search is done, but not yet waited for.
+    while !out.lines().last().is_some_and(|l| l.starts_with("bestmove")) {
+        thread::sleep(Duration::from_millis(1));
+    }
+    server.handle("stop");
+    let before = out.lines().len();
+    server.handle("go depth 3");
+    server.wait();
+    let lines = out.lines()[before..].to_vec();
+    assert!(lines.iter().any(|l| l.starts_with("info depth 1 score mate 1 ")));
+    assert_eq!(lines.last().unwrap(), "bestmove a1a8");
+}
+
+#[test]
+fn go_with_clock_uses_part_of_the_time() {
+    let (mut server, out) = server();
+    server.handle("position startpos");
+    let start = Instant::now();
+    server.handle("go wtime 3000 btime 3000 movestogo 30");
+    server.wait();
+    assert!(start.elapsed() < Duration::from_millis(1000));
+    let last = out.lines().last().unwrap().clone();
+    let mv: Move = last.strip_prefix("bestmove ").unwrap().parse().unwrap();
+    assert!(server.game().legal_moves().contains(&mv));
+}
+
+#[test]
+fn infinite_search_waits_for_stop() {
+    let (mut server, out) = server();
+    server.handle("position startpos");
+    server.handle("go infinite");
+    thread::sleep(Duration::from_millis(200));
+    server.handle("isready");
+    assert!(out.lines().contains(&"readyok".to_string()));
+    assert!(!out.lines().iter().any(|l| l.starts_with("bestmove")));
+    server.handle("stop");
+    assert!(out.lines().last().unwrap().starts_with("bestmove "));
+}
+
+#[test]
+fn quit_stops_the_search() {
+    let (mut server, out) = server();
+    server.handle("go infinite");
+    assert!(!server.handle("quit"));
+    assert!(out.lines().last().unwrap().starts_with("bestmove "));
+}
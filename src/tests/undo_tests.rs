@@ -0,0 +1,128 @@
+use crate::game::errors::GameErr;
+use crate::game::{Color, Game, Move, Piece, PieceType, Square};
+
+type Snapshot = ([Option<Piece>; 64], String, Color, i32, i32, String, String, i32, Option<Square>);
+
+fn snapshot(g: &Game) -> Snapshot {
+    (
+        *g.board(),
+        g.fen.clone(),
+        g.current_player,
+        g.score_white,
+        g.score_black,
+        g.white_can_castle.clone(),
+        g.black_can_castle.clone(),
+        g.half_time_moves,
+        g.en_passant_target(),
+    )
+}
+
+#[test]
+fn undo_without_moves_fails() {
+    let mut g = Game::new();
+    assert_eq!(g.undo(), Err(GameErr::NothingToUndo));
+    assert_eq!(g.redo(), Err(GameErr::NothingToRedo));
+}
+
+#[test]
+fn undo_restores_every_position() {
+    // Castling, en passant, a rook capture and a promotion.
+    let mut g = Game::from_fen("r3k2r/P5p1/8/8/7P/8/8/R3K2R b KQkq - 4 20").unwrap();
+    let moves = [
+        Move::new(Square::E8, Square::C8),
+        Move::new(Square::H4, Square::H5),
+        Move::new(Square::G7, Square::G5),
+        Move::new(Square::H5, Square::G6),
+        Move::new(Square::H8, Square::H1),
+        Move::new(Square::E1, Square::E2),
+        Move::new(Square::C8, Square::C7),
+        Move::with_promotion(Square::A7, Square::A8, PieceType::Queen),
+    ];
+    let mut snapshots = vec![snapshot(&g)];
+    for mv in moves {
+        g.make_move(mv).unwrap();
+        snapshots.push(snapshot(&g));
+    }
+    assert_eq!(g.history().len(), moves.len());
+
+    snapshots.pop();
+    for mv in moves.iter().rev() {
+        assert_eq!(g.undo(), Ok(*mv));
+        assert_eq!(snapshot(&g), snapshots.pop().unwrap());
+    }
+    assert!(g.history().is_empty());
+}
+
+#[test]
+fn unchecked_moves_play_like_checked_ones() {
+    let start = Game::from_fen("r3k2r/P5p1/8/8/7P/8/8/R3K2R b KQkq - 4 20").unwrap();
+    for mv in start.legal_moves() {
+        let mut checked = start.clone();
+        let mut unchecked = start.clone();
+        checked.make_move(mv).unwrap();
+        unchecked.make_move_unchecked(mv);
+        assert_eq!(unchecked.board(), checked.board());
+        assert_eq!(*unchecked.bitboards(), *checked.bitboards());
+        assert_eq!(unchecked.zobrist_key(), checked.zobrist_key());
+        assert_eq!((unchecked.score_black, unchecked.half_time_moves), (checked.score_black, checked.half_time_moves));
+        assert_eq!(unchecked.undo(), Ok(mv));
+        assert_eq!(snapshot(&unchecked), snapshot(&start));
+    }
+}
+
+#[test]
+fn history_records_captures() {
+    let mut g = Game::new();
+    g.move_piece(('e', 2), ('e', 4)).unwrap();
+    g.move_piece(('d', 7), ('d', 5)).unwrap();
+    g.move_piece(('e', 4), ('d', 5)).unwrap();
+
+    let record = g.history().last().unwrap();
+    assert_eq!(record.mv, Move::new(Square::E4, Square::D5));
+    assert_eq!(record.piece, Piece { color: Color::White, piece_type: PieceType::Pawn });
+    assert_eq!(record.captured, Some(Piece { color: Color::Black, piece_type: PieceType::Pawn }));
+    assert_eq!(record.points, 1);
+    assert_eq!(record.enpassang_target, Some(Square::D6));
+}
+
+#[test]
+fn redo_plays_undone_moves_again() {
+    let mut g = Game::new();
+    g.move_piece(('e', 2), ('e', 4)).unwrap();
+    g.move_piece(('e', 7), ('e', 5)).unwrap();
+    let after_two = snapshot(&g);
+
+    g.undo().unwrap();
+    g.undo().unwrap();
+    assert_eq!(snapshot(&g), snapshot(&Game::new()));
+
+    assert_eq!(g.redo(), Ok(Move::new(Square::E2, Square::E4)));
+    assert_eq!(g.redo(), Ok(Move::new(Square::E7, Square::E5)));
+    assert_eq!(snapshot(&g), after_two);
+    assert_eq!(g.redo(), Err(GameErr::NothingToRedo));
+}
+
+#[test]
+fn new_move_clears_redo() {
+    let mut g = Game::new();
+    g.move_piece(('e', 2), ('e', 4)).unwrap();
+    g.undo().unwrap();
+    g.move_piece(('d', 2), ('d', 4)).unwrap();
+    assert_eq!(g.redo(), Err(GameErr::NothingToRedo));
+}
+
+#[test]
+fn undo_forgets_repeated_positions() {
+    let mut g = Game::new();
+    g.move_piece(('g', 1), ('f', 3)).unwrap();
+    g.move_piece(('g', 8), ('f', 6)).unwrap();
+    g.move_piece(('f', 3), ('g', 1)).unwrap();
+    g.move_piece(('f', 6), ('g', 8)).unwrap();
+    assert_eq!(g.repetition_count(), 2);
+
+    g.undo().unwrap();
+    g.undo().unwrap();
+    g.undo().unwrap();
+    g.undo().unwrap();
+    assert_eq!(g.repetition_count(), 1);
+}
@@ -0,0 +1,62 @@
+use crate::game::{zobrist, Game, Move, Square};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/P1N2Q1p/1PPBBPPP/R3K2R w KQkq - 0 1";
+
+/// Checks the incremental key against a full computation after every move
+/// and every undo, `depth` plies deep.
+fn check_keys(g: &mut Game, depth: u32) {
+    assert_eq!(g.zobrist_key(), zobrist::hash(g), "{}", g.fen);
+    if depth == 0 {
+        return;
+    }
+    let before = g.zobrist_key();
+    for mv in g.legal_moves() {
+        g.make_move(mv).unwrap();
+        check_keys(g, depth - 1);
+        g.undo().unwrap();
+        assert_eq!(g.zobrist_key(), before);
+    }
+}
+
+#[test]
+fn incremental_key_matches_full_hash() {
+    check_keys(&mut Game::new(), 2);
+    check_keys(&mut Game::from_fen(KIWIPETE).unwrap(), 2);
+    // En passant, promotions and castling rights lost by captures on the corners.
+    check_keys(&mut Game::from_fen("r3k2r/1P6/8/3pP3/8/8/6p1/R3K2R w KQkq d6 0 1").unwrap(), 2);
+}
+
+#[test]
+fn transpositions_have_the_same_key() {
+    let mut a = Game::new();
+    for (from, to) in [(Square::G1, Square::F3), (Square::G8, Square::F6), (Square::B1, Square::C3)] {
+        a.make_move(Move::new(from, to)).unwrap();
+    }
+    let mut b = Game::new();
+    for (from, to) in [(Square::B1, Square::C3), (Square::G8, Square::F6), (Square::G1, Square::F3)] {
+        b.make_move(Move::new(from, to)).unwrap();
+    }
+    assert_eq!(a.zobrist_key(), b.zobrist_key());
+    assert_eq!(a.zobrist_key(), Game::from_fen(&a.fen).unwrap().zobrist_key());
+}
+
+#[test]
+fn side_castling_and_en_passant_change_the_key() {
+    let white = Game::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    let black = Game::from_fen("4k3/8/8/8/8/8/8/R3K2R b KQ - 0 1").unwrap();
+    let no_castling = Game::from_fen("4k3/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap();
+    assert_ne!(white.zobrist_key(), black.zobrist_key());
+    assert_ne!(white.zobrist_key(), no_castling.zobrist_key());
+
+    let capturable = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    let without = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_ne!(capturable.zobrist_key(), without.zobrist_key());
+}
+
+#[test]
+fn unusable_en_passant_square_is_ignored() {
+    let mut g = Game::new();
+    g.make_move(Move::new(Square::E2, Square::E4)).unwrap();
+    let without = Game::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    assert_eq!(g.zobrist_key(), without.zobrist_key());
+}
@@ -0,0 +1,393 @@
+use std::ffi::OsStr;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::engine::player::{MoveProvider, PlayerMove, Score};
+use crate::engine::search::SearchLimits;
+use crate::game::{Game, Move};
+use crate::game::errors::{GameErr, GameResult};
+
+const STARTPOS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+/// Thinking time per move when no limit is given.
+const DEFAULT_MOVETIME: Duration = Duration::from_millis(1000);
+/// How long the engine gets to answer a command, or to send `bestmove` after
+/// its movetime ran out.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a search without a movetime may run before it is stopped.
+const DEFAULT_MAX_SEARCH_TIME: Duration = Duration::from_secs(60);
+
+/// One `info` line of the engine. Fields the engine did not send are empty.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UciInfo {
+    pub depth: Option<u32>,
+    pub seldepth: Option<u32>,
+    pub score: Option<Score>,
+    pub nodes: Option<u64>,
+    pub nps: Option<u64>,
+    pub time: Option<Duration>,
+    pub pv: Vec<Move>,
+    /// Free text sent with `info string`.
+    pub string: Option<String>,
+}
+
+impl UciInfo {
+    /// Parses an `info` line, `None` when the line is something else.
+    pub fn parse(line: &str) -> Option<UciInfo> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.first() != Some(&"info") {
+            return None;
+        }
+        let mut info = UciInfo::default();
+        let number = |i: usize| tokens.get(i).and_then(|t| t.parse::<i64>().ok());
+        let mut i = 1;
+        while i < tokens.len() {
+            match tokens[i] {
+                "depth" => info.depth = number(i + 1).map(|v| v as u32),
+                "seldepth" => info.seldepth = number(i + 1).map(|v| v as u32),
+                "nodes" => info.nodes = number(i + 1).map(|v| v as u64),
+                "nps" => info.nps = number(i + 1).map(|v| v as u64),
+                "time" => info.time = number(i + 1).map(|v| Duration::from_millis(v as u64)),
+                "score" => {
+                    info.score = match (tokens.get(i + 1), number(i + 2)) {
+                        (Some(&"cp"), Some(cp)) => Some(Score::Centipawns(cp as i32)),
+                        (Some(&"mate"), Some(moves)) => Some(Score::Mate(moves as i32)),
+                        _ => None,
+                    };
+                    i += 1;
+                }
+                "pv" => {
+                    info.pv = tokens[i + 1..].iter().map_while(|t| t.parse().ok()).collect();
+                    i += 1 + info.pv.len();
+                    continue;
+                }
+                "string" => {
+                    info.string = Some(tokens[i + 1..].join(" "));
+                    break;
+                }
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            }
+            i += 2;
+        }
+        Some(info)
+    }
+}
+
+/// The answer to `go`: the best move and the `info` lines sent while searching.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UciSearchResult {
+    /// `None` when the engine answered `bestmove 0000`, it has no legal move.
+    pub best_move: Option<Move>,
+    pub ponder: Option<Move>,
+    pub info: Vec<UciInfo>,
+}
+
+impl UciSearchResult {
+    /// The score of the deepest line the engine reported.
+    pub fn score(&self) -> Option<Score> {
+        self.info.iter().rev().find_map(|info| info.score)
+    }
+}
+
+/// A local chess engine binary speaking UCI, running as a child process.
+/// The engine is shut down with `quit` when the value is dropped.
+pub struct UciEngine {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    name: String,
+    timeout: Duration,
+    max_search_time: Duration,
+    /// Searches that did not answer `stop` in time, their `bestmove` is
+    /// still to come and is skipped.
+    late_bestmoves: usize,
+    /// The `info` lines of the running search.
+    info: Vec<UciInfo>,
+}
+
+impl UciEngine {
+    /// Starts the engine and waits until it answered `uci` and `isready`.
+    pub fn spawn(path: impl AsRef<OsStr>) -> GameResult<Self> {
+        Self::spawn_with_args(path, std::iter::empty::<&str>())
+    }
+
+    /// Like `spawn`, passing `args` to the engine.
+    pub fn spawn_with_args(path: impl AsRef<OsStr>, args: impl IntoIterator<Item = impl AsRef<OsStr>>) -> GameResult<Self> {
+        let path = path.as_ref();
+        let mut child = Command::new(path)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| GameErr::Engine(format!("could not start {}: {}", path.to_string_lossy(), e)))?;
+        let stdin = child.stdin.take().ok_or_else(|| GameErr::Engine("no stdin".into()))?;
+        let stdout = child.stdout.take().ok_or_else(|| GameErr::Engine("no stdout".into()))?;
+
+        // Reading on a thread of its own lets every wait for the engine time out.
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = Self {
+            child,
+            stdin,
+            lines,
+            name: path.to_string_lossy().into_owned(),
+            timeout: DEFAULT_TIMEOUT,
+            max_search_time: DEFAULT_MAX_SEARCH_TIME,
+            late_bestmoves: 0,
+            info: Vec::new(),
+        };
+        engine.send("uci")?;
+        let deadline = Instant::now() + engine.timeout;
+        loop {
+            let line = engine.expect_line(Some(deadline))?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = name.trim().into();
+            }
+            if line.trim() == "uciok" {
+                break;
+            }
+        }
+        engine.is_ready()?;
+        Ok(engine)
+    }
+
+    /// The name the engine sent in the handshake.
+    pub fn engine_name(&self) -> &str {
+        &self.name
+    }
+
+    /// How long the engine gets to answer, see `DEFAULT_TIMEOUT`.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// How long a depth or node limited search may run before it is
+    /// stopped, see `DEFAULT_MAX_SEARCH_TIME`.
+    pub fn set_max_search_time(&mut self, max_search_time: Duration) {
+        self.max_search_time = max_search_time;
+    }
+
+    /// Sends `isready` and waits for `readyok`.
+    pub fn is_ready(&mut self) -> GameResult<()> {
+        self.send("isready")?;
+        let deadline = Instant::now() + self.timeout;
+        while self.expect_line(Some(deadline))?.trim() != "readyok" {}
+        Ok(())
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> GameResult<()> {
+        self.send(&format!("setoption name {} value {}", name, value))?;
+        self.is_ready()
+    }
+
+    /// Sends the position and starts searching it, the result is collected
+    /// with `wait` or `stop`.
+    pub fn start_search(&mut self, game: &Game, limits: SearchLimits) -> GameResult<()> {
+        self.info.clear();
+        self.send(&position_command(game))?;
+        self.send(&go_command(limits))
+    }
+
+    /// Starts `go infinite`, the search runs until `stop`.
+    pub fn start_infinite(&mut self, game: &Game) -> GameResult<()> {
+        self.info.clear();
+        self.send(&position_command(game))?;
+        self.send("go infinite")
+    }
+
+    /// Searches the position. When the engine does not answer within its
+    /// movetime plus the timeout, or within the maximum search time for a
+    /// depth or node limit, it is sent `stop`.
+    pub fn go(&mut self, game: &Game, limits: SearchLimits) -> GameResult<UciSearchResult> {
+        self.start_search(game, limits)?;
+        let search_time = movetime(limits).map_or(self.max_search_time, |movetime| movetime + self.timeout);
+        match self.wait_until(Some(Instant::now() + search_time))? {
+            Some(result) => Ok(result),
+            None => self.stop(),
+        }
+    }
+
+    /// Stops the running search and returns its result.
+    pub fn stop(&mut self) -> GameResult<UciSearchResult> {
+        self.send("stop")?;
+        match self.wait_until(Some(Instant::now() + self.timeout))? {
+            Some(result) => Ok(result),
+            None => {
+                // Its answer must not be taken for the one of the next search.
+                self.late_bestmoves += 1;
+                Err(self.timed_out())
+            }
+        }
+    }
+
+    /// Waits for `bestmove` until `deadline`.
+    pub fn wait(&mut self, deadline: Instant) -> GameResult<UciSearchResult> {
+        self.wait_until(Some(deadline))?.ok_or_else(|| self.timed_out())
+    }
+
+    /// Waits for `bestmove`, `None` when the deadline passed first.
+    fn wait_until(&mut self, deadline: Option<Instant>) -> GameResult<Option<UciSearchResult>> {
+        loop {
+            let Some(line) = self.read_line(deadline)? else {
+                return Ok(None);
+            };
+            if let Some(info) = UciInfo::parse(&line) {
+                self.info.push(info);
+                continue;
+            }
+            let mut tokens = line.split_whitespace();
+            if tokens.next() != Some("bestmove") {
+                continue;
+            }
+            let best_move = match tokens.next() {
+                Some("0000") | Some("(none)") => None,
+                Some(mv) => Some(mv.parse()?),
+                None => return Err(GameErr::Engine(format!("{} sent bestmove without a move", self.name))),
+            };
+            let ponder = match (tokens.next(), tokens.next()) {
+                (Some("ponder"), Some(mv)) => mv.parse().ok(),
+                _ => None,
+            };
+            return Ok(Some(UciSearchResult { best_move, ponder, info: std::mem::take(&mut self.info) }));
+        }
+    }
+
+    /// Sends `quit` and waits for the engine to exit, it is killed when it
+    /// does not exit within the timeout.
+    pub fn quit(mut self) -> GameResult<()> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> GameResult<()> {
+        if self.child.try_wait().ok().flatten().is_some() {
+            return Ok(());
+        }
+        let _ = self.send("quit");
+        let deadline = Instant::now() + self.timeout;
+        while Instant::now() < deadline {
+            if self.child.try_wait().ok().flatten().is_some() {
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        Err(GameErr::Engine(format!("{} did not quit and was killed", self.name)))
+    }
+
+    fn send(&mut self, command: &str) -> GameResult<()> {
+        writeln!(self.stdin, "{}", command)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| GameErr::Engine(format!("could not write to {}: {}", self.name, e)))
+    }
+
+    /// The next line of the engine, `None` when the deadline passed first.
+    /// Without a deadline this waits for as long as the engine runs. The
+    /// late `bestmove` of a search that timed out is skipped, together with
+    /// the `info` lines collected before it.
+    fn read_line(&mut self, deadline: Option<Instant>) -> GameResult<Option<String>> {
+        loop {
+            let closed = || GameErr::Engine(format!("{} closed its output", self.name));
+            let line = match deadline {
+                Some(deadline) => match self.lines.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(line) => line,
+                    Err(RecvTimeoutError::Timeout) => return Ok(None),
+                    Err(RecvTimeoutError::Disconnected) => return Err(closed()),
+                },
+                None => self.lines.recv().map_err(|_| closed())?,
+            };
+            if self.late_bestmoves > 0 && line.split_whitespace().next() == Some("bestmove") {
+                self.late_bestmoves -= 1;
+                self.info.clear();
+                continue;
+            }
+            return Ok(Some(line));
+        }
+    }
+
+    fn expect_line(&mut self, deadline: Option<Instant>) -> GameResult<String> {
+        self.read_line(deadline)?.ok_or_else(|| self.timed_out())
+    }
+
+    fn timed_out(&self) -> GameErr {
+        GameErr::Engine(format!("{} did not answer in time", self.name))
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+impl MoveProvider for UciEngine {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn choose_move(&mut self, game: &Game, limits: SearchLimits) -> GameResult<PlayerMove> {
+        let result = self.go(game, limits)?;
+        let score = result.score();
+        let mv = result.best_move.ok_or_else(|| GameErr::Engine(format!("{} found no move", self.name)))?;
+        Ok(PlayerMove { mv, score })
+    }
+
+    fn new_game(&mut self) -> GameResult<()> {
+        self.send("ucinewgame")?;
+        self.is_ready()
+    }
+}
+
+/// The `position` command for the game, including the moves played so far.
+pub fn position_command(game: &Game) -> String {
+    let mut command = if game.start_fen() == STARTPOS {
+        "position startpos".to_string()
+    } else {
+        format!("position fen {}", game.start_fen())
+    };
+    if !game.history().is_empty() {
+        command.push_str(" moves");
+        for record in game.history() {
+            command.push_str(&format!(" {}", record.mv));
+        }
+    }
+    command
+}
+
+/// The `go` command, without any limit the engine gets `DEFAULT_MOVETIME`.
+pub fn go_command(limits: SearchLimits) -> String {
+    let mut command = "go".to_string();
+    if let Some(depth) = limits.depth {
+        command.push_str(&format!(" depth {}", depth));
+    }
+    if let Some(nodes) = limits.nodes {
+        command.push_str(&format!(" nodes {}", nodes));
+    }
+    if let Some(movetime) = movetime(limits) {
+        command.push_str(&format!(" movetime {}", movetime.as_millis()));
+    }
+    command
+}
+
+fn movetime(limits: SearchLimits) -> Option<Duration> {
+    match limits {
+        SearchLimits { movetime: Some(movetime), .. } => Some(movetime),
+        SearchLimits { depth: None, nodes: None, .. } => Some(DEFAULT_MOVETIME),
+        _ => None,
+    }
+}
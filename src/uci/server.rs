@@ -0,0 +1,301 @@
+use std::io::{BufRead, Write};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use crate::engine::player::Score;
+use crate::engine::search::{SearchLimits, SearchResult, Searcher};
+use crate::engine::syzygy::Tablebase;
+use crate::engine::tt::DEFAULT_HASH_MB;
+use crate::game::{Color, Game, Move};
+use crate::game::errors::{GameErr, GameResult};
+
+/// Time kept back per move for communication with the GUI, in milliseconds.
+const DEFAULT_MOVE_OVERHEAD: u64 = 30;
+/// Moves assumed to be left in the game when the GUI does not send `movestogo`.
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+/// A running search, see `UciServer::go`.
+struct Search {
+    /// Hands the searcher back when the search is done.
+    handle: JoinHandle<Searcher>,
+    /// The searcher's own stop flag.
+    stop: Arc<AtomicBool>,
+    /// Set by `stop`, an infinite search waits for it before sending `bestmove`.
+    stop_requested: Arc<AtomicBool>,
+    infinite: bool,
+}
+
+/// Runs the built-in engine behind the UCI protocol. Commands are passed to
+/// `handle` one line at a time, searches run on their own thread so `stop`
+/// and `isready` are answered while searching.
+pub struct UciServer<W: Write + Send + 'static> {
+    out: Arc<Mutex<W>>,
+    game: Game,
+    search: Option<Search>,
+    /// Kept between searches for its transposition table, `None` while searching.
+    searcher: Option<Searcher>,
+    move_overhead: u64,
+}
+
+impl<W: Write + Send + 'static> UciServer<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out: Arc::new(Mutex::new(out)),
+            game: Game::new(),
+            search: None,
+            searcher: Some(Searcher::new()),
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+        }
+    }
+
+    /// Reads commands until `quit` or the end of the input.
+    pub fn run(&mut self, input: impl BufRead) {
+        for line in input.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if !self.handle(&line) {
+                return;
+            }
+        }
+        self.finish_search();
+    }
+
+    /// Handles one command, returns false after `quit`.
+    pub fn handle(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some(&command) = tokens.first() else {
+            return true;
+        };
+        let result = match command {
+            "uci" => {
+                self.send(&format!("id name {} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")));
+                self.send(&format!("id author {}", authors()));
+                self.send(&format!("option name Hash type spin default {} min 1 max 1024", DEFAULT_HASH_MB));
+                self.send(&format!("option name Move Overhead type spin default {} min 0 max 5000", DEFAULT_MOVE_OVERHEAD));
+                self.send("option name SyzygyPath type string default <empty>");
+                self.send("uciok");
+                Ok(())
+            }
+            "isready" => {
+                self.send("readyok");
+                Ok(())
+            }
+            "ucinewgame" => {
+                self.finish_search();
+                self.searcher().clear_hash();
+                self.game = Game::new();
+                Ok(())
+            }
+            "setoption" => self.set_option(&tokens[1..]),
+            "position" => self.set_position(&tokens[1..]),
+            "go" => self.go(&tokens[1..]),
+            "stop" => {
+                self.stop();
+                Ok(())
+            }
+            "quit" => {
+                self.stop();
+                return false;
+            }
+            _ => Err(GameErr::Engine(format!("unknown command {}", command))),
+        };
+        if let Err(e) = result {
+            self.send(&format!("info string {}", e));
+        }
+        true
+    }
+
+    /// Waits until the running search has sent its `bestmove`, an infinite
+    /// search first has to be stopped.
+    pub fn wait(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.searcher = search.handle.join().ok();
+        }
+    }
+
+    /// Stops the running search, which then sends its `bestmove`.
+    pub fn stop(&mut self) {
+        // A search that already sent its `bestmove` is not stopped again.
+        if let Some(search) = self.search.as_ref().filter(|search| !search.handle.is_finished()) {
+            search.stop_requested.store(true, Ordering::Relaxed);
+            search.stop.store(true, Ordering::Relaxed);
+        }
+        self.wait();
+    }
+
+    /// Lets a limited search run to its end, like commands sent while
+    /// searching are handled by other engines. An infinite search is stopped.
+    fn finish_search(&mut self) {
+        if self.search.as_ref().is_some_and(|search| search.infinite) {
+            self.stop();
+        }
+        self.wait();
+    }
+
+    /// The searcher, only to be used while no search runs.
+    fn searcher(&mut self) -> &mut Searcher {
+        self.searcher.get_or_insert_with(Searcher::new)
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    fn send(&self, line: &str) {
+        send(&self.out, line);
+    }
+
+    /// `setoption name <name> [value <value>]`
+    fn set_option(&mut self, tokens: &[&str]) -> GameResult<()> {
+        if tokens.first() != Some(&"name") {
+            return Err(GameErr::Engine("expected setoption name <name> value <value>".into()));
+        }
+        let value_at = tokens.iter().position(|t| *t == "value");
+        let name = tokens[1..value_at.unwrap_or(tokens.len())].join(" ");
+        let value = value_at.map(|i| tokens[i + 1..].join(" ")).unwrap_or_default();
+        let invalid = || GameErr::Engine(format!("invalid value {} for {}", value, name));
+        match name.to_lowercase().as_str() {
+            "hash" => {
+                let megabytes: usize = value.parse().map_err(|_| invalid())?;
+                self.finish_search();
+                self.searcher().set_hash_size(megabytes.clamp(1, 1024));
+                Ok(())
+            }
+            "move overhead" => {
+                self.move_overhead = value.parse().map_err(|_| invalid())?;
+                Ok(())
+            }
+            "syzygypath" => {
+                let tablebase = match value.as_str() {
+                    "" | "<empty>" => None,
+                    paths => Some(Arc::new(Tablebase::open(paths)?)),
+                };
+                if let Some(tablebase) = &tablebase {
+                    self.send(&format!("info string found {} tablebase files with up to {} pieces",
+                                       tablebase.len(), tablebase.max_pieces()));
+                }
+                self.finish_search();
+                self.searcher().set_tablebase(tablebase);
+                Ok(())
+            }
+            _ => Err(GameErr::Engine(format!("unknown option {}", name))),
+        }
+    }
+
+    /// `position (startpos | fen <fen>) [moves <move>...]`
+    fn set_position(&mut self, tokens: &[&str]) -> GameResult<()> {
+        let moves_at = tokens.iter().position(|t| *t == "moves").unwrap_or(tokens.len());
+        let mut game = match tokens.first() {
+            Some(&"startpos") => Game::new(),
+            Some(&"fen") => Game::from_fen(&tokens[1..moves_at].join(" "))?,
+            _ => return Err(GameErr::Engine("expected startpos or fen".into())),
+        };
+        for token in tokens.iter().skip(moves_at + 1) {
+            let mv: Move = token.parse()?;
+            game.make_move(mv)?;
+        }
+        self.finish_search();
+        self.game = game;
+        Ok(())
+    }
+
+    /// `go [depth <n>] [nodes <n>] [movetime <ms>] [wtime <ms>] [btime <ms>]
+    /// [winc <ms>] [binc <ms>] [movestogo <n>] [infinite]`
+    fn go(&mut self, tokens: &[&str]) -> GameResult<()> {
+        self.finish_search();
+        let mut limits = SearchLimits::default();
+        let (mut time, mut increment, mut moves_to_go) = (None, 0, DEFAULT_MOVES_TO_GO);
+        let mut infinite = false;
+        let mut i = 0;
+        while i < tokens.len() {
+            let value = tokens.get(i + 1).and_then(|v| v.parse::<u64>().ok());
+            let mut takes_value = true;
+            match (tokens[i], self.game.current_player) {
+                ("depth", _) => limits.depth = value.map(|v| v as u32),
+                ("nodes", _) => limits.nodes = value,
+                ("movetime", _) => limits.movetime = value.map(Duration::from_millis),
+                ("wtime", Color::White) | ("btime", Color::Black) => time = value,
+                ("winc", Color::White) | ("binc", Color::Black) => increment = value.unwrap_or(0),
+                ("movestogo", _) => moves_to_go = value.unwrap_or(DEFAULT_MOVES_TO_GO).max(1),
+                ("wtime" | "btime" | "winc" | "binc", _) => {}
+                ("infinite", _) => {
+                    infinite = true;
+                    takes_value = false;
+                }
+                _ => takes_value = false,
+            }
+            i += if takes_value { 2 } else { 1 };
+        }
+        if limits.movetime.is_none() && !infinite && let Some(time) = time {
+            limits.movetime = Some(Duration::from_millis(self.time_for_move(time, increment, moves_to_go)));
+        }
+        self.start_search(limits, infinite);
+        Ok(())
+    }
+
+    /// Splits the remaining time evenly over the moves to go, never using more
+    /// than what is left after the move overhead.
+    fn time_for_move(&self, time: u64, increment: u64, moves_to_go: u64) -> u64 {
+        let available = time.saturating_sub(self.move_overhead);
+        (time / moves_to_go + increment * 3 / 4).min(available).max(1)
+    }
+
+    fn start_search(&mut self, limits: SearchLimits, infinite: bool) {
+        let mut searcher = self.searcher.take().unwrap_or_default();
+        let stop = searcher.stop_flag();
+        // A `stop` that came too late for the last search must not end this one.
+        stop.store(false, Ordering::Relaxed);
+        let stop_requested = Arc::new(AtomicBool::new(false));
+        let start = Instant::now();
+        let info_out = self.out.clone();
+        searcher.set_info_callback(move |result| send(&info_out, &info_line(result, start.elapsed())));
+
+        let game = self.game.clone();
+        let out = self.out.clone();
+        let requested = stop_requested.clone();
+        let handle = thread::spawn(move || {
+            let result = searcher.search(&game, limits);
+            // In infinite mode the best move is only sent after `stop`.
+            while infinite && !requested.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(5));
+            }
+            match result.best_move {
+                Some(mv) => send(&out, &format!("bestmove {}", mv)),
+                None => send(&out, "bestmove 0000"),
+            }
+            searcher
+        });
+        self.search = Some(Search { handle, stop, stop_requested, infinite });
+    }
+}
+
+/// The package authors, Cargo joins them with ':'. The package name stands in
+/// when the manifest lists none.
+fn authors() -> String {
+    match env!("CARGO_PKG_AUTHORS") {
+        "" => env!("CARGO_PKG_NAME").to_string(),
+        authors => authors.replace(':', ", "),
+    }
+}
+
+fn send<W: Write>(out: &Mutex<W>, line: &str) {
+    if let Ok(mut out) = out.lock() {
+        let _ = writeln!(out, "{}", line);
+        let _ = out.flush();
+    }
+}
+
+/// The `info` line for a completed iteration.
+pub fn info_line(result: &SearchResult, elapsed: Duration) -> String {
+    let score = match Score::from_search(result.score) {
+        Score::Centipawns(cp) => format!("cp {}", cp),
+        Score::Mate(moves) => format!("mate {}", moves),
+    };
+    let millis = elapsed.as_millis() as u64;
+    let nps = result.nodes * 1000 / millis.max(1);
+    let pv: Vec<String> = result.pv.iter().map(|mv| mv.to_string()).collect();
+    format!("info depth {} score {} nodes {} nps {} hashfull {} time {} pv {}",
+            result.depth, score, result.nodes, nps, result.hashfull, millis, pv.join(" "))
+}